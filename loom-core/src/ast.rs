@@ -1,9 +1,11 @@
 use crate::types::*;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 use crate::context::{LoomContext, Module};
 use crate::definition::ArgDefinition;
-use crate::error::{LoomError, LoomResult, UndefinedKind};
+use crate::error::{did_you_mean, suggest_similar, LoomError, LoomResult, UndefinedKind};
+use crate::event::channel::ExecutionEventKind;
 use crate::interceptor::context::ExecutionContext;
 
 /// A complete definition (recipe, job, pipeline, etc.)
@@ -17,6 +19,27 @@ pub struct Definition {
     pub module_index: usize,
 }
 
+impl Definition {
+    /// Man-page style help: kind and signature, parameters, and the directives applied to the definition
+    pub fn help(&self) -> String {
+        let mut help = format!(
+            "{} {}\n\n{}",
+            format!("{:?}", self.kind).to_lowercase(),
+            self.signature.name,
+            self.signature.help()
+        );
+
+        if !self.directives.is_empty() {
+            help.push_str("\nDIRECTIVES:\n");
+            for directive in self.directives.iter() {
+                help.push_str(&format!("    @{}\n", directive.name));
+            }
+        }
+
+        help
+    }
+}
+
 /// Block of statements
 #[derive(Debug, Clone, PartialEq)]
 pub struct Block {
@@ -32,17 +55,37 @@ pub enum Statement {
     Command {
         parts: Arc<[Expression]>,
         directives: Arc<[DirectiveCall]>, // Direttive anche sui singoli comandi
+        position: Position,
     },
 
-    /// Recipe/job call
+    /// Recipe/job call, with positional and named arguments (e.g. `build("x", target: "y")`)
     Call {
         name: Arc<str>,
-        args: Arc<[Expression]>,
+        args: Arc<[ArgDefinition]>,
         directives: Arc<[DirectiveCall]>, // Direttive anche sulle singole call
+        position: Position,
+    },
+
+    /// Variable assignment (e.g., `count = count + 1` or `items[0] = "x"`)
+    Assignment {
+        target: AssignmentTarget,
+        value: Arc<Expression>,
+        position: Position,
     },
 
 }
 
+impl Statement {
+    /// Source position of the statement, used for error reporting
+    pub fn position(&self) -> &Position {
+        match self {
+            Statement::Command { position, .. }
+            | Statement::Call { position, .. }
+            | Statement::Assignment { position, .. } => position,
+        }
+    }
+}
+
 /// Assignment targets
 #[derive(Debug, Clone, PartialEq)]
 pub enum AssignmentTarget {
@@ -128,6 +171,9 @@ pub enum BinaryOperator {
     Is, IsNot, // For "is empty", "is not empty"
 }
 
+/// Keyword accepted as right operand of `is` / `is not` (e.g. `output is not empty`)
+pub const EMPTY_KEYWORD: &str = "empty";
+
 /// Unary operators
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum UnaryOperator {
@@ -173,12 +219,105 @@ impl Block {
 }
 impl Expression {
 
-    /// Helper method to evaluate an expression into a LoomValue
+    /// Collapses binary and unary operations whose operands are all literals into a single literal.
+    /// Operations that would fail (e.g. division by zero) are left unfolded, so evaluating the folded
+    /// tree reports the same error as the original one
+    pub fn fold_constants(&self) -> Expression {
+        match self {
+            Expression::BinaryOp { left, operator, right } => {
+                let left = left.fold_constants();
+                let right = right.fold_constants();
+
+                if let (Expression::Literal(left_literal), Expression::Literal(right_literal)) = (&left, &right)
+                    && let Ok(LoomValue::Literal(folded)) = Self::evaluate_literal_binary_op(left_literal, operator, right_literal, None) {
+                    return Expression::Literal(folded);
+                }
+
+                Expression::BinaryOp {
+                    left: Arc::new(left),
+                    operator: *operator,
+                    right: Arc::new(right),
+                }
+            }
+            Expression::UnaryOp { operator, operand } => {
+                let operand = operand.fold_constants();
+
+                if let Expression::Literal(literal) = &operand
+                    && let Ok(folded) = Self::evaluate_literal_unary_op(operator, literal, None) {
+                    return Expression::Literal(folded);
+                }
+
+                Expression::UnaryOp {
+                    operator: *operator,
+                    operand: Arc::new(operand),
+                }
+            }
+            Expression::FunctionCall { name, args } => Expression::FunctionCall {
+                name: name.clone(),
+                args: args.iter().map(Expression::fold_constants).collect(),
+            },
+            Expression::IndexAccess { object, index } => Expression::IndexAccess {
+                object: Arc::new(object.fold_constants()),
+                index: Arc::new(index.fold_constants()),
+            },
+            Expression::Interpolation { parts } => Expression::Interpolation {
+                parts: parts.iter()
+                    .map(|part| match part {
+                        InterpolationPart::Expression(expr) => InterpolationPart::Expression(Arc::new(expr.fold_constants())),
+                        text => text.clone(),
+                    })
+                    .collect(),
+            },
+            other => other.clone(),
+        }
+    }
+
+    /// Helper method to evaluate an expression into a LoomValue.
+    /// When the context has a trace channel, emits `VariableResolved` for variables
+    /// and `ExpressionEvaluated` for every other non-literal expression.
     pub fn evaluate(
         &self,
         loom_context: &LoomContext,
         context: &ExecutionContext,
         position: Option<Position>,
+    ) -> LoomResult<LoomValue> {
+        let Some(trace) = &context.trace else {
+            return self.evaluate_untraced(loom_context, context, position);
+        };
+
+        let start_time = Instant::now();
+        let result = self.evaluate_untraced(loom_context, context, position);
+        // Tracing is best-effort: a closed channel must not fail the evaluation
+        let _ = match (self, &result) {
+            (Expression::Literal(_), _) => Ok(()),
+            (Expression::Variable(variable_name), Ok(value)) => trace.emit_with_context(
+                ExecutionEventKind::VariableResolved {
+                    variable_name: variable_name.to_string(),
+                    value: value.to_string(),
+                    scope: format!("{:?}", context.scope),
+                },
+                HashMap::new(),
+            ),
+            (_, result) => trace.emit_with_context(
+                ExecutionEventKind::ExpressionEvaluated {
+                    expression: format!("{:?}", self),
+                    result: match result {
+                        Ok(value) => value.to_string(),
+                        Err(error) => format!("error: {}", error),
+                    },
+                    evaluation_time_ms: start_time.elapsed().as_millis() as u64,
+                },
+                HashMap::new(),
+            ),
+        };
+        result
+    }
+
+    fn evaluate_untraced(
+        &self,
+        loom_context: &LoomContext,
+        context: &ExecutionContext,
+        position: Option<Position>,
     ) -> LoomResult<LoomValue> {
         match self {
             Expression::Literal(lit) => Ok(LoomValue::Literal(lit.clone())),
@@ -186,24 +325,47 @@ impl Expression {
             Expression::Variable(var_name) => {
                 context.get_variable(var_name)
                     .ok_or_else(|| {
+                        let candidates = context.variables.keys().map(|name| name.as_ref());
                         if let Some(pos) = position {
-                            LoomError::undefined(
+                            LoomError::undefined_among(
                                 var_name.to_string(),
                                 UndefinedKind::Variable,
-                                pos
+                                pos,
+                                candidates
                             )
                         } else {
-                            LoomError::execution(format!("Variable '{}' not found", var_name))
+                            LoomError::execution(format!(
+                                "Variable '{}' not found{}",
+                                var_name,
+                                did_you_mean(&suggest_similar(var_name, candidates))
+                            ))
                         }
                     })
             }
 
             Expression::FunctionCall { name, args } => {
+                let evaluated = args.iter()
+                    .map(|arg| arg.evaluate(loom_context, context, position.clone()))
+                    .collect::<LoomResult<Vec<_>>>()?;
+
                 // ✅ Invece di panic!, usa errore appropriato
-                Err(LoomError::not_implemented(
-                    "function calls",
-                    format!("Function '{}' with {} arguments", name, args.len())
-                ))
+                call_builtin(name, &evaluated, context).unwrap_or_else(|| Err(match position {
+                    Some(pos) => LoomError::undefined_among(
+                        name.to_string(),
+                        UndefinedKind::Function,
+                        pos,
+                        BUILTIN_FUNCTIONS.iter().copied()
+                    ),
+                    None => LoomError::not_implemented(
+                        "function calls",
+                        format!(
+                            "Function '{}' with {} arguments{}",
+                            name,
+                            args.len(),
+                            did_you_mean(&suggest_similar(name, BUILTIN_FUNCTIONS.iter().copied()))
+                        )
+                    ),
+                }))
             }
 
             Expression::IndexAccess { object, index } => {
@@ -213,16 +375,30 @@ impl Expression {
                 match (&obj_value, &index_value) {
                     (LoomValue::Literal(LiteralValue::Array(arr)),
                         LoomValue::Literal(LiteralValue::Number(idx))) => {
-                        let idx = *idx as usize;
-                        arr.get(idx)
-                            .cloned()
-                            .map(LoomValue::Literal)
-                            .ok_or_else(|| {
-                                LoomError::execution(format!(
-                                    "Array index {} out of bounds (length: {})",
-                                    idx, arr.len()
-                                ))
-                            })
+                        Self::resolve_index(*idx, arr.len())
+                            .map(|resolved| LoomValue::Literal(arr[resolved].clone()))
+                    }
+                    (LoomValue::Literal(LiteralValue::Json(serde_json::Value::Array(arr))),
+                        LoomValue::Literal(LiteralValue::Number(idx))) => {
+                        Self::resolve_index(*idx, arr.len())
+                            .map(|resolved| LoomValue::from_json(arr[resolved].clone()))
+                    }
+                    (LoomValue::Literal(LiteralValue::Json(json)),
+                        LoomValue::Literal(LiteralValue::String(key))) => {
+                        match json {
+                            serde_json::Value::Object(object) => object.get(key)
+                                .cloned()
+                                .map(LoomValue::from_json)
+                                .ok_or_else(|| LoomError::execution(format!(
+                                    "Key '{}' not found in JSON object (available keys: {})",
+                                    key, object.keys().cloned().collect::<Vec<_>>().join(", ")
+                                ))),
+                            other => Err(LoomError::expression(
+                                "index_access",
+                                format!("Cannot index JSON {} with key '{}': only JSON objects have keys", Self::json_type_name(other), key),
+                                position.unwrap_or_default()
+                            ))
+                        }
                     }
                     _ => Err(LoomError::expression(
                         "index_access",
@@ -237,18 +413,10 @@ impl Expression {
             }
 
             Expression::UnaryOp { operator, operand } => {
-                let value = operand.evaluate(loom_context, context, position.clone())?;
-                match (operator, &value) {
-                    (UnaryOperator::Not, LoomValue::Literal(LiteralValue::Boolean(b))) => {
-                        Ok(LoomValue::Literal(LiteralValue::Boolean(!b)))
-                    }
-                    (UnaryOperator::Minus, LoomValue::Literal(LiteralValue::Number(n))) => {
-                        Ok(LoomValue::Literal(LiteralValue::Number(-n)))
-                    }
-                    (UnaryOperator::Minus, LoomValue::Literal(LiteralValue::Float(f))) => {
-                        Ok(LoomValue::Literal(LiteralValue::Float(-f)))
-                    }
-                    _ => Err(LoomError::expression(
+                match operand.evaluate(loom_context, context, position.clone())? {
+                    LoomValue::Literal(literal) => Self::evaluate_literal_unary_op(operator, &literal, position)
+                        .map(LoomValue::Literal),
+                    value => Err(LoomError::expression(
                         "unary_operation",
                         format!("Cannot apply {:?} to {:?}", operator, value.type_name()),
                         position.unwrap_or_default()
@@ -318,6 +486,40 @@ impl Expression {
         context: &ExecutionContext,
         position: Option<Position>,
     ) -> LoomResult<LoomValue> {
+        // And/Or short-circuit: the right operand is evaluated only when it can change the result
+        if matches!(operator, BinaryOperator::And | BinaryOperator::Or) {
+            let left_bool = Self::evaluate_logical_operand(left, operator, loom_context, context, position.clone())?;
+            let short_circuit = match operator {
+                BinaryOperator::And => !left_bool,
+                _ => left_bool,
+            };
+            if short_circuit {
+                return Ok(LoomValue::Literal(LiteralValue::Boolean(left_bool)));
+            }
+            let right_bool = Self::evaluate_logical_operand(right, operator, loom_context, context, position)?;
+            return Ok(LoomValue::Literal(LiteralValue::Boolean(right_bool)));
+        }
+
+        // Is/IsNot: the right operand is the `empty` keyword, not a value to evaluate
+        if matches!(operator, BinaryOperator::Is | BinaryOperator::IsNot) {
+            if !matches!(right, Expression::Variable(keyword) if keyword.as_ref() == EMPTY_KEYWORD) {
+                return Err(LoomError::expression(
+                    "binary_operation",
+                    format!(
+                        "Operator {:?} expects the '{}' keyword as right operand (e.g. 'value is {}' or 'value is not {}')",
+                        operator, EMPTY_KEYWORD, EMPTY_KEYWORD, EMPTY_KEYWORD
+                    ),
+                    position.unwrap_or_default()
+                ));
+            }
+            let is_empty = Self::is_empty_value(&left.evaluate(loom_context, context, position)?);
+            let result = match operator {
+                BinaryOperator::Is => is_empty,
+                _ => !is_empty,
+            };
+            return Ok(LoomValue::Literal(LiteralValue::Boolean(result)));
+        }
+
         let left_val = left.evaluate(loom_context, context, position.clone())?;
         let right_val = right.evaluate(loom_context, context, position.clone())?;
 
@@ -325,6 +527,15 @@ impl Expression {
             (LoomValue::Literal(left_val), LoomValue::Literal(right_val)) => {
                 Self::evaluate_literal_binary_op(left_val, operator, right_val, position)
             }
+            // An absent value only equals another absent value: `null == <empty>` is false
+            (LoomValue::Empty, _) | (_, LoomValue::Empty)
+                if matches!(operator, BinaryOperator::Equal | BinaryOperator::NotEqual) =>
+            {
+                let equal = matches!((&left_val, &right_val), (LoomValue::Empty, LoomValue::Empty));
+                Ok(LoomValue::Literal(LiteralValue::Boolean(
+                    if matches!(operator, BinaryOperator::Equal) { equal } else { !equal }
+                )))
+            }
             _ => Err(LoomError::expression(
                 "binary_operation",
                 format!(
@@ -336,6 +547,70 @@ impl Expression {
         }
     }
 
+    /// Resolves an array index, where negative indices count from the end (`-1` is the last element)
+    fn resolve_index(index: i64, len: usize) -> LoomResult<usize> {
+        let resolved = if index < 0 { len as i64 + index } else { index };
+        usize::try_from(resolved).ok()
+            .filter(|resolved| *resolved < len)
+            .ok_or_else(|| LoomError::execution(format!(
+                "Array index {} out of bounds (length: {}){}",
+                index, len,
+                if index < 0 { ", negative indices count from the end" } else { "" }
+            )))
+    }
+
+    /// Name of a JSON value kind, for error messages
+    fn json_type_name(value: &serde_json::Value) -> &'static str {
+        match value {
+            serde_json::Value::Null => "null",
+            serde_json::Value::Bool(_) => "boolean",
+            serde_json::Value::Number(_) => "number",
+            serde_json::Value::String(_) => "string",
+            serde_json::Value::Array(_) => "array",
+            serde_json::Value::Object(_) => "object",
+        }
+    }
+
+    /// A value is empty when it is `LoomValue::Empty`, `null`, an empty string, an empty array or a null/empty JSON value
+    fn is_empty_value(value: &LoomValue) -> bool {
+        match value {
+            LoomValue::Empty | LoomValue::Literal(LiteralValue::Null) => true,
+            LoomValue::Literal(LiteralValue::String(s)) => s.is_empty(),
+            LoomValue::Literal(LiteralValue::Array(items)) => items.is_empty(),
+            LoomValue::Literal(LiteralValue::Json(json)) => match json {
+                serde_json::Value::Null => true,
+                serde_json::Value::String(s) => s.is_empty(),
+                serde_json::Value::Array(items) => items.is_empty(),
+                serde_json::Value::Object(fields) => fields.is_empty(),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Evaluate an operand of And/Or, requiring it to be a boolean
+    fn evaluate_logical_operand(
+        operand: &Expression,
+        operator: &BinaryOperator,
+        loom_context: &LoomContext,
+        context: &ExecutionContext,
+        position: Option<Position>,
+    ) -> LoomResult<bool> {
+        match operand.evaluate(loom_context, context, position.clone())? {
+            LoomValue::Literal(LiteralValue::Boolean(value)) => Ok(value),
+            LoomValue::Literal(other) => Err(LoomError::expression(
+                "binary_operation",
+                format!("Operator {:?} requires boolean operands, found {}", operator, other.type_name()),
+                position.unwrap_or_default()
+            )),
+            other => Err(LoomError::expression(
+                "binary_operation",
+                format!("Operator {:?} requires boolean operands, found {}", operator, other.type_name()),
+                position.unwrap_or_default()
+            ))
+        }
+    }
+
     fn evaluate_literal_binary_op(
         left: &LiteralValue,
         operator: &BinaryOperator,
@@ -349,18 +624,18 @@ impl Expression {
 
         match (left, operator, right) {
             // Arithmetic operations
-            (Number(a), Add, Number(b)) => Ok(LoomValue::Literal(Number(a + b))),
+            (Number(a), Add, Number(b)) => Self::checked_number(a.checked_add(*b), "addition", format_args!("{} + {}", a, b), pos),
             (Float(a), Add, Float(b)) => Ok(LoomValue::Literal(Float(a + b))),
             (Number(a), Add, Float(b)) => Ok(LoomValue::Literal(Float(*a as f64 + b))),
             (Float(a), Add, Number(b)) => Ok(LoomValue::Literal(Float(a + *b as f64))),
             (String(a), Add, String(b)) => Ok(LoomValue::Literal(String(format!("{}{}", a, b)))),
 
-            (Number(a), Subtract, Number(b)) => Ok(LoomValue::Literal(Number(a - b))),
+            (Number(a), Subtract, Number(b)) => Self::checked_number(a.checked_sub(*b), "subtraction", format_args!("{} - {}", a, b), pos),
             (Float(a), Subtract, Float(b)) => Ok(LoomValue::Literal(Float(a - b))),
             (Number(a), Subtract, Float(b)) => Ok(LoomValue::Literal(Float(*a as f64 - b))),
             (Float(a), Subtract, Number(b)) => Ok(LoomValue::Literal(Float(a - *b as f64))),
 
-            (Number(a), Multiply, Number(b)) => Ok(LoomValue::Literal(Number(a * b))),
+            (Number(a), Multiply, Number(b)) => Self::checked_number(a.checked_mul(*b), "multiplication", format_args!("{} * {}", a, b), pos),
             (Float(a), Multiply, Float(b)) => Ok(LoomValue::Literal(Float(a * b))),
             (Number(a), Multiply, Float(b)) => Ok(LoomValue::Literal(Float(*a as f64 * b))),
             (Float(a), Multiply, Number(b)) => Ok(LoomValue::Literal(Float(a * *b as f64))),
@@ -369,7 +644,9 @@ impl Expression {
                 if *b == 0 {
                     Err(LoomError::expression("division", "Division by zero", pos))
                 } else {
-                    Ok(LoomValue::Literal(Number(a / b)))
+                    a.checked_div(*b)
+                        .map(|result| LoomValue::Literal(Number(result)))
+                        .ok_or_else(|| LoomError::expression("division", format!("Integer overflow in {} / {}", a, b), pos))
                 }
             }
             (Float(a), Divide, Float(b)) => {
@@ -380,7 +657,37 @@ impl Expression {
                 }
             }
 
+            (Number(a), Modulo, Number(b)) => {
+                if *b == 0 {
+                    Err(LoomError::expression("modulo", "Modulo by zero", pos))
+                } else {
+                    // i64::MIN % -1 overflows
+                    a.checked_rem(*b)
+                        .map(|result| LoomValue::Literal(Number(result)))
+                        .ok_or_else(|| LoomError::expression("modulo", format!("Integer overflow in {} % {}", a, b), pos))
+                }
+            }
+            (Float(a), Modulo, Float(b)) => {
+                if *b == 0.0 {
+                    Err(LoomError::expression("modulo", "Modulo by zero", pos))
+                } else {
+                    Ok(LoomValue::Literal(Float(a % b)))
+                }
+            }
+
             // Comparison operations
+            // Mixed Number/Float operands are compared by value, so 1 == 1.0
+            (Number(a), op @ (Equal | NotEqual | Less | LessEqual | Greater | GreaterEqual), Float(b)) => {
+                Ok(LoomValue::Literal(Boolean(Self::compare_numbers(*a as f64, op, *b))))
+            }
+            (Float(a), op @ (Equal | NotEqual | Less | LessEqual | Greater | GreaterEqual), Number(b)) => {
+                Ok(LoomValue::Literal(Boolean(Self::compare_numbers(*a, op, *b as f64))))
+            }
+            // A literal null and a JSON null are the same value
+            (Null, op @ (Equal | NotEqual), Json(serde_json::Value::Null))
+            | (Json(serde_json::Value::Null), op @ (Equal | NotEqual), Null) => {
+                Ok(LoomValue::Literal(Boolean(matches!(op, Equal))))
+            }
             (a, Equal, b) => Ok(LoomValue::Literal(Boolean(a == b))),
             (a, NotEqual, b) => Ok(LoomValue::Literal(Boolean(a != b))),
 
@@ -388,6 +695,18 @@ impl Expression {
             (Float(a), Less, Float(b)) => Ok(LoomValue::Literal(Boolean(a < b))),
             (String(a), Less, String(b)) => Ok(LoomValue::Literal(Boolean(a < b))),
 
+            (Number(a), LessEqual, Number(b)) => Ok(LoomValue::Literal(Boolean(a <= b))),
+            (Float(a), LessEqual, Float(b)) => Ok(LoomValue::Literal(Boolean(a <= b))),
+            (String(a), LessEqual, String(b)) => Ok(LoomValue::Literal(Boolean(a <= b))),
+
+            (Number(a), Greater, Number(b)) => Ok(LoomValue::Literal(Boolean(a > b))),
+            (Float(a), Greater, Float(b)) => Ok(LoomValue::Literal(Boolean(a > b))),
+            (String(a), Greater, String(b)) => Ok(LoomValue::Literal(Boolean(a > b))),
+
+            (Number(a), GreaterEqual, Number(b)) => Ok(LoomValue::Literal(Boolean(a >= b))),
+            (Float(a), GreaterEqual, Float(b)) => Ok(LoomValue::Literal(Boolean(a >= b))),
+            (String(a), GreaterEqual, String(b)) => Ok(LoomValue::Literal(Boolean(a >= b))),
+
             // String operations
             (String(s), Contains, String(sub)) => {
                 Ok(LoomValue::Literal(Boolean(s.contains(sub))))
@@ -417,4 +736,157 @@ impl Expression {
         }
     }
 
-}
\ No newline at end of file
+    /// Result of a checked integer operation, an error on overflow
+    fn checked_number(
+        result: Option<i64>,
+        operation: &str,
+        expression: std::fmt::Arguments,
+        position: Position,
+    ) -> LoomResult<LoomValue> {
+        result
+            .map(|result| LoomValue::Literal(LiteralValue::Number(result)))
+            .ok_or_else(|| LoomError::expression(operation, format!("Integer overflow in {}", expression), position))
+    }
+
+    /// Applies a unary operator to a literal, failing when the operator does not support it or overflows
+    fn evaluate_literal_unary_op(
+        operator: &UnaryOperator,
+        operand: &LiteralValue,
+        position: Option<Position>,
+    ) -> LoomResult<LiteralValue> {
+        match (operator, operand) {
+            (UnaryOperator::Not, LiteralValue::Boolean(b)) => Ok(LiteralValue::Boolean(!b)),
+            // -i64::MIN overflows
+            (UnaryOperator::Minus, LiteralValue::Number(n)) => n.checked_neg()
+                .map(LiteralValue::Number)
+                .ok_or_else(|| LoomError::expression(
+                    "negation",
+                    format!("Integer overflow in -({})", n),
+                    position.unwrap_or_default()
+                )),
+            (UnaryOperator::Minus, LiteralValue::Float(f)) => Ok(LiteralValue::Float(-f)),
+            _ => Err(LoomError::expression(
+                "unary_operation",
+                format!("Cannot apply {:?} to {:?}", operator, operand.type_name()),
+                position.unwrap_or_default()
+            )),
+        }
+    }
+
+    /// Compare two numeric values already widened to f64
+    fn compare_numbers(left: f64, operator: &BinaryOperator, right: f64) -> bool {
+        match operator {
+            BinaryOperator::Equal => left == right,
+            BinaryOperator::NotEqual => left != right,
+            BinaryOperator::Less => left < right,
+            BinaryOperator::LessEqual => left <= right,
+            BinaryOperator::Greater => left > right,
+            BinaryOperator::GreaterEqual => left >= right,
+            _ => false,
+        }
+    }
+
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{execution_context, string};
+
+    fn number(value: i64) -> Expression {
+        Expression::Literal(LiteralValue::Number(value))
+    }
+
+    fn float(value: f64) -> Expression {
+        Expression::Literal(LiteralValue::Float(value))
+    }
+
+    fn boolean(value: bool) -> LoomValue {
+        LoomValue::Literal(LiteralValue::Boolean(value))
+    }
+
+    fn binary(left: Expression, operator: BinaryOperator, right: Expression) -> Expression {
+        Expression::BinaryOp { left: Arc::new(left), operator, right: Arc::new(right) }
+    }
+
+    fn evaluate(expression: &Expression, context: &ExecutionContext) -> LoomResult<LoomValue> {
+        expression.evaluate(&LoomContext::new(), context, None)
+    }
+
+    #[test]
+    fn fold_constants_collapses_literal_operations() {
+        let context = execution_context(vec![]);
+        let expression = binary(number(60), BinaryOperator::Multiply, binary(number(60), BinaryOperator::Subtract, number(0)));
+
+        let folded = expression.fold_constants();
+
+        assert_eq!(folded, number(3600));
+        assert_eq!(evaluate(&folded, &context).unwrap(), evaluate(&expression, &context).unwrap());
+    }
+
+    #[test]
+    fn fold_constants_keeps_variables_and_failing_operations() {
+        let context = execution_context(vec![("count", LoomValue::Literal(LiteralValue::Number(2)))]);
+        let partial = binary(Expression::Variable("count".into()), BinaryOperator::Add, binary(number(3), BinaryOperator::Multiply, number(4)));
+        let folded = partial.fold_constants();
+        assert_eq!(folded, binary(Expression::Variable("count".into()), BinaryOperator::Add, number(12)));
+        assert_eq!(evaluate(&folded, &context).unwrap(), evaluate(&partial, &context).unwrap());
+
+        let negated_min = Expression::UnaryOp { operator: UnaryOperator::Minus, operand: Arc::new(number(i64::MIN)) };
+        for failing in [
+            binary(number(1), BinaryOperator::Divide, number(0)),
+            binary(number(i64::MAX), BinaryOperator::Add, number(1)),
+            binary(number(i64::MIN), BinaryOperator::Subtract, number(1)),
+            binary(number(i64::MAX), BinaryOperator::Multiply, number(2)),
+            negated_min,
+        ] {
+            let folded = failing.fold_constants();
+            assert_eq!(folded, failing);
+            assert_eq!(
+                evaluate(&folded, &context).unwrap_err().to_string(),
+                evaluate(&failing, &context).unwrap_err().to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn modulo_on_numbers_and_floats() {
+        let context = execution_context(vec![]);
+        assert_eq!(evaluate(&binary(number(5), BinaryOperator::Modulo, number(2)), &context).unwrap(), LoomValue::Literal(LiteralValue::Number(1)));
+        assert_eq!(evaluate(&binary(float(5.5), BinaryOperator::Modulo, float(2.0)), &context).unwrap(), LoomValue::Literal(LiteralValue::Float(1.5)));
+        assert!(evaluate(&binary(number(5), BinaryOperator::Modulo, number(0)), &context).is_err());
+        assert!(evaluate(&binary(number(i64::MIN), BinaryOperator::Modulo, number(-1)), &context).is_err());
+    }
+
+    #[test]
+    fn greater_on_numbers_floats_and_strings() {
+        let context = execution_context(vec![]);
+        assert_eq!(evaluate(&binary(number(3), BinaryOperator::Greater, number(2)), &context).unwrap(), boolean(true));
+        assert_eq!(evaluate(&binary(float(1.5), BinaryOperator::Greater, float(2.5)), &context).unwrap(), boolean(false));
+        assert_eq!(evaluate(&binary(string("b"), BinaryOperator::Greater, string("a")), &context).unwrap(), boolean(true));
+    }
+
+    #[test]
+    fn greater_equal_on_numbers_floats_and_strings() {
+        let context = execution_context(vec![("count", LoomValue::Literal(LiteralValue::Number(10)))]);
+        assert_eq!(evaluate(&binary(Expression::Variable("count".into()), BinaryOperator::GreaterEqual, number(10)), &context).unwrap(), boolean(true));
+        assert_eq!(evaluate(&binary(float(2.0), BinaryOperator::GreaterEqual, float(2.5)), &context).unwrap(), boolean(false));
+        assert_eq!(evaluate(&binary(string("a"), BinaryOperator::GreaterEqual, string("a")), &context).unwrap(), boolean(true));
+    }
+
+    #[test]
+    fn less_equal_on_numbers_floats_and_strings() {
+        let context = execution_context(vec![]);
+        assert_eq!(evaluate(&binary(number(2), BinaryOperator::LessEqual, number(2)), &context).unwrap(), boolean(true));
+        assert_eq!(evaluate(&binary(float(3.5), BinaryOperator::LessEqual, float(2.5)), &context).unwrap(), boolean(false));
+        assert_eq!(evaluate(&binary(string("a"), BinaryOperator::LessEqual, string("b")), &context).unwrap(), boolean(true));
+    }
+
+    #[test]
+    fn mixed_number_and_float_comparisons() {
+        let context = execution_context(vec![]);
+        assert_eq!(evaluate(&binary(number(1), BinaryOperator::Equal, float(1.0)), &context).unwrap(), boolean(true));
+        assert_eq!(evaluate(&binary(number(2), BinaryOperator::Greater, float(1.5)), &context).unwrap(), boolean(true));
+        assert_eq!(evaluate(&binary(float(3.0), BinaryOperator::LessEqual, number(3)), &context).unwrap(), boolean(true));
+        assert_eq!(evaluate(&binary(number(1), BinaryOperator::Less, float(2.5)), &context).unwrap(), boolean(true));
+    }
+}
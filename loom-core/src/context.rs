@@ -1,8 +1,8 @@
 use crate::ast::*;
 use crate::types::*;
-use crate::error::{LoomError, LoomResult};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use crate::error::{did_you_mean, suggest_similar, LoomError, LoomResult, UndefinedKind};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 // TODO: In futuro pensasre se integrare il supporto di namespace
 
@@ -30,12 +30,26 @@ pub struct LoomContext {
 
 #[derive(Debug, PartialEq)]
 pub struct Module {
+    /// File da cui è stato caricato il modulo, usato per risolvere il grafo degli import
+    pub path: PathBuf,
     pub definitions: HashMap<DefinitionId, Arc<Definition>>,
     pub enums: HashMap<EnumId, Arc<EnumDef>>,
     pub variables: HashMap<Arc<str>, LoomValue>,
     pub dependencies: HashMap<PathBuf, Vec<ImportKind>>,
 }
 
+/// Riepilogo di una definition restituito da `LoomContext::describe`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefinitionDescription {
+    pub name: Arc<str>,
+    pub kind: DefinitionKind,
+    pub parameters: Vec<ParameterDefinition>,
+    /// Nomi delle direttive applicate alla definition, senza @
+    pub directives: Vec<Arc<str>>,
+    /// File del modulo che contiene la definition
+    pub module: PathBuf,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ImportKind {
     ImportAll,
@@ -70,7 +84,7 @@ impl LoomContext {
     }
     
 
-    /// Find a definition by name
+    /// Find a definition by name or alias
     pub fn find_definition(&self, name: &str) -> Option<Arc<Definition>> {
         self.definitions_ref.get(name)
             .and_then(|index|
@@ -79,6 +93,25 @@ impl LoomContext {
             ).map(Arc::clone)
     }
 
+    /// Registra `alias` come nome alternativo di `canonical`, puntando allo stesso indice.
+    /// Se `canonical` è a sua volta un alias viene risolto subito alla sua definition,
+    /// quindi non possono formarsi catene o cicli di alias
+    pub fn register_alias(&mut self, alias: &str, canonical: &str) -> LoomResult<()> {
+        if self.definitions_ref.contains_key(alias) {
+            return Err(LoomError::validation(format!(
+                "Cannot register alias '{}': a definition or alias with the same name already exists", alias
+            )));
+        }
+
+        let index = *self.definitions_ref.get(canonical)
+            .ok_or_else(|| LoomError::validation(format!(
+                "Cannot register alias '{}': definition '{}' not found", alias, canonical
+            )))?;
+
+        self.definitions_ref.insert(Arc::from(alias), index);
+        Ok(())
+    }
+
     /// Find an enum by name
     pub fn find_enum(&self, name: &str) -> Option<Arc<EnumDef>> {
         self.enums_def_ref.get(name)
@@ -103,28 +136,84 @@ impl LoomContext {
     //     self.variables.insert(name, value);
     // }
 
-    // /// Get all definitions of a specific kind
-    // pub fn get_definitions_by_kind(&self, kind: DefinitionKind) -> Vec<&Definition> {
-    //     self.definitions
-    //         .iter()
-    //         .filter(|def| def.kind == kind)
-    //     .collect()
-    // }
+    /// Nomi con cui una definition può essere richiamata, alias compresi
+    pub fn definition_names(&self) -> impl Iterator<Item = &str> {
+        self.definitions_ref.keys().map(|name| name.as_ref())
+    }
 
-    // /// Validate that all referenced definitions exist
-    // pub fn validate_references(&self) -> Result<(), Vec<String>> {
-    //     let mut errors = Vec::new();
-    // 
-    //     for definition in &self.definitions {
-    //         self.validate_definition_references(&definition.signature.name, definition, &mut errors);
-    //     }
-    // 
-    //     if errors.is_empty() {
-    //         Ok(())
-    //     } else {
-    //         Err(errors)
-    //     }
-    // }
+    /// Errore per una definition inesistente, con i nomi più simili come suggerimento.
+    /// Con la posizione della chiamata è un `DefinitionNotFoundError`, altrimenti un errore di esecuzione
+    pub fn missing_definition(&self, name: &str, position: Option<Position>) -> LoomError {
+        match position {
+            Some(position) => {
+                let mut available = self.definition_names().map(str::to_string).collect::<Vec<_>>();
+                available.sort();
+                LoomError::definition_not_found(name, available, position)
+            }
+            None => LoomError::execution(format!(
+                "Cannot find the definition: '{}'{}",
+                name,
+                did_you_mean(&suggest_similar(name, self.definition_names()))
+            )),
+        }
+    }
+
+    /// `UndefinedError` per un enum inesistente (es. il tipo di un parametro), con gli enum più simili come suggerimento
+    pub fn missing_enum(&self, name: &str, position: Position) -> LoomError {
+        LoomError::undefined_among(
+            name,
+            UndefinedKind::Enum,
+            position,
+            self.enums_def_ref.keys().map(|name| name.as_ref())
+        )
+    }
+
+    /// Tutte le definition caricate, di tutti i moduli, in ordine non garantito
+    pub fn definitions(&self) -> impl Iterator<Item = &Definition> {
+        self.modules.values()
+            .flat_map(|module| module.definitions.values())
+            .map(|definition| definition.as_ref())
+    }
+
+    /// Le definition di un certo tipo, di tutti i moduli
+    pub fn definitions_by_kind(&self, kind: DefinitionKind) -> impl Iterator<Item = &Definition> {
+        self.definitions()
+            .filter(move |definition| definition.kind == kind)
+    }
+
+    /// Riepilogo di tutte le definition (nome, tipo, parametri, direttive e modulo) ordinato per nome,
+    /// pensato per tooling come autocompletamento e listing
+    pub fn describe(&self) -> Vec<DefinitionDescription> {
+        let mut descriptions = self.modules.values()
+            .flat_map(|module| module.definitions.values().map(move |definition| DefinitionDescription {
+                name: definition.signature.name.clone(),
+                kind: definition.kind.clone(),
+                parameters: definition.signature.parameters.to_vec(),
+                directives: definition.directives.iter().map(|directive| directive.name.clone()).collect(),
+                module: module.path.clone(),
+            }))
+            .collect::<Vec<_>>();
+        descriptions.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.module.cmp(&b.module)));
+        descriptions
+    }
+
+    /// Verifica che tutte le definition richiamate esistano, restituendo tutti gli errori trovati
+    /// invece di fermarsi al primo: utile come controllo preliminare prima di eseguire
+    pub fn validate_references(&self) -> Result<(), Vec<LoomError>> {
+        let mut errors = Vec::new();
+
+        for module in self.modules.values() {
+            for definition in module.definitions.values() {
+                self.validate_definition_references(definition, &mut errors);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 
     // fn update_import_graph(&mut self, file_path: &PathBuf) -> Result<(), String> {
     //     let file = self.files.get(file_path).ok_or("File not found")?;
@@ -227,29 +316,315 @@ impl LoomContext {
     //     Ok(())
     // }
 
-    fn validate_definition_references(&self, _name: &str, definition: &Definition, errors: &mut Vec<LoomError>) {
-        // Validate that all referenced jobs/recipes exist
-        self.validate_block_references(definition.body.clone(), errors);
+    /// Ordina topologicamente i moduli in base ai loro import, restituendo l'ordine di caricamento
+    /// (le dipendenze prima di chi le importa). Se due o più file si importano a vicenda
+    /// restituisce un ImportError con il ciclo completo, es. "a.loom -> b.loom -> a.loom"
+    pub fn validate_import_graph(&self) -> LoomResult<Vec<PathBuf>> {
+        let graph: HashMap<&Path, Vec<&Path>> = self.modules.values()
+            .map(|module| {
+                let mut imports: Vec<&Path> = module.dependencies.keys().map(PathBuf::as_path).collect();
+                // Ordine deterministico, così l'errore riporta sempre lo stesso ciclo
+                imports.sort();
+                (module.path.as_path(), imports)
+            })
+            .collect();
+
+        let mut roots: Vec<&Path> = graph.keys().copied().collect();
+        roots.sort();
+
+        let mut visited = HashSet::new();
+        let mut path = Vec::new();
+        let mut order = Vec::with_capacity(graph.len());
+        for root in roots {
+            Self::visit_import(root, &graph, &mut visited, &mut path, &mut order)?;
+        }
+
+        Ok(order)
+    }
+
+    /// Visita in profondità del grafo degli import: `path` contiene i file in visita,
+    /// quindi ritrovarci un file significa aver chiuso un ciclo
+    fn visit_import<'a>(
+        file: &'a Path,
+        graph: &HashMap<&'a Path, Vec<&'a Path>>,
+        visited: &mut HashSet<&'a Path>,
+        path: &mut Vec<&'a Path>,
+        order: &mut Vec<PathBuf>,
+    ) -> LoomResult<()> {
+        if let Some(start) = path.iter().position(|it| *it == file) {
+            let cycle = path[start..].iter()
+                .chain(std::iter::once(&file))
+                .map(|it| it.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(LoomError::import(
+                format!("Circular import detected: {}", cycle),
+                file.display().to_string(),
+                Position::default()
+            ));
+        }
+        if !visited.insert(file) {
+            return Ok(());
+        }
+
+        path.push(file);
+        for dependency in graph.get(file).into_iter().flatten() {
+            Self::visit_import(dependency, graph, visited, path, order)?;
+        }
+        path.pop();
+
+        order.push(file.to_path_buf());
+        Ok(())
+    }
+
+    /// Grafo delle chiamate tra definition in formato Graphviz DOT: un cluster per modulo,
+    /// un nodo per definition etichettato con il tipo e un arco per ogni `Statement::Call`.
+    /// Le chiamate a definition inesistenti puntano a un nodo tratteggiato
+    pub fn call_graph_dot(&self) -> String {
+        self.call_graph_dot_with(false)
     }
 
-    fn validate_block_references(&self, blocks: Arc<[Block]>, errors: &mut Vec<LoomError>) {
-        for block in blocks.iter() {
-            for statement in block.statements.iter() {
-                match statement {
-                    Statement::Call { name, .. } => {
-                        if !self.definitions_ref.contains_key(name) {
-                            errors.push(LoomError::execution(format!("Undefined reference: {}", name)));
+    /// Come `call_graph_dot`, con `include_symbols` aggiunge ai cluster anche enum e variabili dei moduli
+    pub fn call_graph_dot_with(&self, include_symbols: bool) -> String {
+        let mut modules = self.modules.values().collect::<Vec<_>>();
+        modules.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut dot = String::from("digraph loom {\n    rankdir=LR;\n");
+        let mut edges = Vec::new();
+        let mut missing = HashSet::new();
+
+        for (index, module) in modules.iter().enumerate() {
+            dot.push_str(&format!("    subgraph cluster_{} {{\n", index));
+            dot.push_str(&format!("        label={};\n", dot_quote(&module.path.to_string_lossy())));
+
+            let mut definitions = module.definitions.values().collect::<Vec<_>>();
+            definitions.sort_by(|a, b| a.signature.name.cmp(&b.signature.name));
+            for definition in definitions {
+                let kind = format!("{:?}", definition.kind).to_lowercase();
+                dot.push_str(&format!(
+                    "        {} [label={}, shape={}];\n",
+                    dot_quote(&definition.signature.name),
+                    dot_quote(&format!("{}\\n({})", definition.signature.name, kind)),
+                    match definition.kind {
+                        DefinitionKind::Recipe => "box",
+                        DefinitionKind::Job => "ellipse",
+                        DefinitionKind::Pipeline => "hexagon",
+                        DefinitionKind::Schedule => "octagon",
+                    }
+                ));
+
+                for statement in definition.body.iter().flat_map(|block| block.statements.iter()) {
+                    match statement {
+                        Statement::Call { name, .. } => {
+                            // Gli alias puntano al nodo della definition canonica
+                            let target = match self.find_definition(name) {
+                                Some(target) => target.signature.name.clone(),
+                                None => {
+                                    missing.insert(name.clone());
+                                    name.clone()
+                                }
+                            };
+                            edges.push((definition.signature.name.clone(), target));
                         }
+                        // Negli stage di una pipeline un job può essere referenziato per nome come comando
+                        Statement::Command { parts, .. } if definition.kind == DefinitionKind::Pipeline => {
+                            let name = parts.iter()
+                                .map(|part| match part {
+                                    Expression::Literal(LiteralValue::String(text)) => Some(text.as_str()),
+                                    _ => None,
+                                })
+                                .collect::<Option<String>>();
+                            if let Some(job) = name.and_then(|name| self.find_definition(name.trim()))
+                                && job.kind == DefinitionKind::Job {
+                                edges.push((definition.signature.name.clone(), job.signature.name.clone()));
+                            }
+                        }
+                        _ => {}
                     }
-                    _ => {}
                 }
             }
+
+            if include_symbols {
+                let mut enums = module.enums.values().map(|it| it.name.clone()).collect::<Vec<_>>();
+                enums.sort();
+                for name in enums {
+                    dot.push_str(&format!(
+                        "        {} [label={}, shape=note];\n",
+                        dot_quote(&format!("enum:{}:{}", index, name)),
+                        dot_quote(&format!("{}\\n(enum)", name))
+                    ));
+                }
+                let mut variables = module.variables.keys().cloned().collect::<Vec<_>>();
+                variables.sort();
+                for name in variables {
+                    dot.push_str(&format!(
+                        "        {} [label={}, shape=plaintext];\n",
+                        dot_quote(&format!("var:{}:{}", index, name)),
+                        dot_quote(&format!("{}\\n(variable)", name))
+                    ));
+                }
+            }
+
+            dot.push_str("    }\n");
+        }
+
+        let mut missing = missing.into_iter().collect::<Vec<_>>();
+        missing.sort();
+        for name in missing {
+            dot.push_str(&format!(
+                "    {} [label={}, style=dashed];\n",
+                dot_quote(&name),
+                dot_quote(&format!("{}\\n(missing)", name))
+            ));
+        }
+
+        edges.sort();
+        edges.dedup();
+        for (from, to) in edges {
+            dot.push_str(&format!("    {} -> {};\n", dot_quote(&from), dot_quote(&to)));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn validate_definition_references(&self, definition: &Definition, errors: &mut Vec<LoomError>) {
+        // I tipi dei parametri che non sono builtin devono riferire un enum esistente
+        for parameter in definition.signature.parameters.iter() {
+            if let Some(param_type) = &parameter.param_type
+                && !BUILTIN_PARAMETER_TYPES.contains(&param_type.as_ref())
+                && self.find_enum(param_type).is_none() {
+                errors.push(self.missing_enum(param_type, definition.position.clone()));
+            }
+        }
+
+        // Validate that all referenced jobs/recipes exist
+        for block in definition.body.iter() {
+            self.validate_block_references(block, errors);
+        }
+    }
+
+    fn validate_block_references(&self, block: &Block, errors: &mut Vec<LoomError>) {
+        for statement in block.statements.iter() {
+            if let Statement::Call { name, position, .. } = statement
+                && !self.definitions_ref.contains_key(name) {
+                errors.push(LoomError::undefined_among(
+                    name.to_string(),
+                    UndefinedKind::Recipe,
+                    position.clone(),
+                    self.definition_names()
+                ));
+            }
         }
     }
 }
 
+/// Identificatore DOT tra doppi apici, con gli apici interni escapati
+fn dot_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\\\""))
+}
+
 impl Default for LoomContext {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+impl LoomContext {
+    /// Contesto con un solo modulo `main.loom` che contiene le definitions indicate
+    pub(crate) fn with_definitions(definitions: Vec<Definition>) -> Self {
+        let module_id = uuid::Uuid::new_v4();
+        let definitions: HashMap<DefinitionId, Arc<Definition>> = definitions.into_iter()
+            .map(|definition| (uuid::Uuid::new_v4(), Arc::new(definition)))
+            .collect();
+
+        let mut context = Self::new();
+        for (definition_id, definition) in &definitions {
+            context.definitions_ref.insert(definition.signature.name.clone(), (module_id, *definition_id));
+        }
+        context.modules.insert(module_id, Arc::new(Module {
+            path: PathBuf::from("main.loom"),
+            definitions,
+            enums: HashMap::new(),
+            variables: HashMap::new(),
+            dependencies: HashMap::new(),
+        }));
+        context
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{call, definition, echo, recipe, string};
+    use super::*;
+
+    #[test]
+    fn validate_references_reports_dangling_calls() {
+        let context = LoomContext::with_definitions(vec![
+            recipe("test", vec![echo("test", vec![])]),
+            recipe("build", vec![call("test", vec![], vec![]), call("deploy", vec![], vec![])]),
+        ]);
+
+        let errors = context.validate_references().unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            LoomError::UndefinedError { name, kind: UndefinedKind::Recipe, .. } if name == "deploy"
+        ));
+    }
+
+    #[test]
+    fn call_graph_dot_lists_call_edges() {
+        let context = LoomContext::with_definitions(vec![
+            definition(DefinitionKind::Job, "compile", vec![vec![echo("compile", vec![])]]),
+            recipe("build", vec![call("compile", vec![], vec![]), call("lint", vec![], vec![])]),
+            definition(DefinitionKind::Pipeline, "ci", vec![vec![Statement::Command {
+                parts: vec![string("compile")].into(),
+                directives: Vec::new().into(),
+                position: Position::default(),
+            }]]),
+        ]);
+
+        let dot = context.call_graph_dot();
+        let edges: Vec<_> = dot.lines().map(str::trim).filter(|line| line.contains("->")).collect();
+
+        assert_eq!(edges, [
+            r#""build" -> "compile";"#,
+            r#""build" -> "lint";"#,
+            r#""ci" -> "compile";"#,
+        ]);
+        assert!(dot.contains(r#""compile" [label="compile\n(job)", shape=ellipse];"#), "{}", dot);
+        assert!(dot.contains(r#""lint" [label="lint\n(missing)", style=dashed];"#), "{}", dot);
+    }
+
+    #[test]
+    fn validate_references_reports_parameters_typed_to_a_missing_enum() {
+        let typed = |name: &str, param_types: &[&str]| {
+            let mut definition = recipe(name, vec![echo(name, vec![])]);
+            definition.signature.parameters = param_types.iter()
+                .map(|param_type| ParameterDefinition {
+                    name: "target".into(),
+                    param_type: Some((*param_type).into()),
+                    default_value: None,
+                    required: true,
+                    varargs: false,
+                })
+                .collect::<Vec<_>>()
+                .into();
+            definition
+        };
+        let context = LoomContext::with_definitions(vec![
+            typed("build", &["string", "duration"]),
+            typed("deploy", &["Enviroment"]),
+        ]);
+
+        let errors = context.validate_references().unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            LoomError::UndefinedError { name, kind: UndefinedKind::Enum, .. } if name == "Enviroment"
+        ));
+    }
+}
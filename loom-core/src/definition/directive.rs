@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use crate::ast::DirectiveCall;
-use crate::definition::{ArgDefinition, ParameterDefinition};
+use crate::definition::{format_help_parameters, ArgDefinition, ParameterDefinition};
 use crate::error::LoomResult;
 use crate::interceptor::scope::DirectiveScope;
 use crate::types::LoomValue;
@@ -37,4 +37,48 @@ pub trait DirectiveDefinition: Send + Sync {
 
     /// Trasforma il DirectiveCall in parametri strutturati per l'executor
     fn parse_args(&self, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>>;
+
+    /// Help in stile man page: firma, parametri con tipo, default e obbligatorietà, e scope.
+    /// I parametri deprecati sono marcati con `[DEPRECATED]`
+    fn help(&self) -> String {
+        let parameters = self.parameters();
+
+        let usage = parameters.iter()
+            .map(|param| match &param.default_value {
+                Some(default) => format!("{}: {} = {}", param.name, param.param_type, default),
+                None if param.required => format!("{}: {}", param.name, param.param_type),
+                None => format!("{}?: {}", param.name, param.param_type),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let rows = parameters.iter()
+            .map(|param| {
+                let mut flags = match &param.default_value {
+                    Some(default) => format!("optional, default: {}", default),
+                    None if param.required => "required".to_string(),
+                    None => "optional".to_string(),
+                };
+                if param.deprecated {
+                    flags.push_str("  [DEPRECATED]");
+                }
+                (param.name.clone(), param.param_type.to_string(), flags, param.description.clone())
+            })
+            .collect::<Vec<_>>();
+
+        let scope = self.scope().iter()
+            .map(|scope| format!("{:?}", scope).to_lowercase())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!(
+            "@{} - {}\n\nUSAGE:\n    @{}({})\n\n{}\nSCOPE:\n    {}\n",
+            self.name(),
+            self.description(),
+            self.name(),
+            usage,
+            format_help_parameters(&rows),
+            if scope.is_empty() { "(any)".to_string() } else { scope },
+        )
+    }
 }
\ No newline at end of file
@@ -1,5 +1,8 @@
+use std::collections::HashMap;
+use serde_json::Value;
 use crate::ast::Expression;
-use crate::types::LoomValue;
+use crate::error::{LoomError, LoomResult};
+use crate::types::{parse_duration, LiteralValue, LoomValue};
 
 pub mod directive;
 pub mod function;
@@ -12,6 +15,8 @@ pub struct ParameterDefinition {
     pub required: bool,
     pub default_value: Option<LoomValue>,
     pub description: String,
+    /// Parametro ancora accettato ma da non usare, evidenziato nell'help
+    pub deprecated: bool,
 }
 /// Argomento di una direttiva
 #[derive(Debug, Clone, PartialEq)]
@@ -29,4 +34,161 @@ pub enum ParameterType {
     Array(Box<ParameterType>),
     Json,
     Enum(Vec<String>), // Per valori predefiniti
-}
\ No newline at end of file
+    /// Stringa di durata come `30s` o `2h30m`, vedi `parse_duration`
+    Duration,
+    /// Oggetto JSON. A differenza di `Json`, che accetta qualsiasi valore, rifiuta array e scalari.
+    /// I campi elencati sono obbligatori e validati con il loro tipo; una lista vuota accetta qualsiasi oggetto
+    Object(Vec<(String, ParameterType)>),
+}
+
+impl ParameterType {
+    /// Verifica che un valore letterale sia compatibile con il tipo del parametro
+    pub fn validate_literal_type(&self, value: &LiteralValue) -> LoomResult<()> {
+        match (self, value) {
+            (ParameterType::String, LiteralValue::String(_))
+            | (ParameterType::Number, LiteralValue::Number(_) | LiteralValue::Float(_))
+            | (ParameterType::Boolean, LiteralValue::Boolean(_))
+            | (ParameterType::Json, _) => Ok(()),
+            (ParameterType::Array(item), LiteralValue::Array(values)) => {
+                values.iter().try_for_each(|value| item.validate_literal_type(value))
+            }
+            (ParameterType::Array(item), LiteralValue::Json(Value::Array(values))) => {
+                values.iter().try_for_each(|value| item.validate_json(value))
+            }
+            (ParameterType::Object(fields), LiteralValue::Json(Value::Object(object))) => {
+                fields.iter().try_for_each(|(name, field_type)| {
+                    let value = object.get(name)
+                        .ok_or_else(|| LoomError::validation(format!("Missing field '{}' of type {}", name, field_type)))?;
+                    field_type.validate_json(value).map_err(|err| {
+                        let message = match err {
+                            LoomError::ValidationError { message, .. } => message,
+                            other => other.to_string(),
+                        };
+                        LoomError::validation(format!("Invalid field '{}': {}", name, message))
+                    })
+                })
+            }
+            (ParameterType::Enum(values), LiteralValue::String(value)) if !values.contains(value) => {
+                Err(LoomError::validation(format!("Expected one of {}, found '{}'", values.join(", "), value)))
+            }
+            (ParameterType::Enum(_), LiteralValue::String(_)) => Ok(()),
+            (ParameterType::Duration, LiteralValue::String(value)) => parse_duration(value).map(|_| ()),
+            (ParameterType::Object(_), LiteralValue::Json(json)) => {
+                Err(LoomError::validation(format!("Expected {}, found json {}", self, Self::json_kind(json))))
+            }
+            _ => Err(LoomError::validation(format!("Expected {}, found {}", self, value.type_name()))),
+        }
+    }
+
+    /// Valida un valore JSON (es. un campo di un oggetto) convertendolo nel letterale corrispondente
+    fn validate_json(&self, value: &Value) -> LoomResult<()> {
+        match LoomValue::from_json(value.clone()) {
+            LoomValue::Literal(literal) => self.validate_literal_type(&literal),
+            other => Err(LoomError::validation(format!("Expected {}, found {}", self, other.type_name()))),
+        }
+    }
+
+    fn json_kind(value: &Value) -> &'static str {
+        match value {
+            Value::Null => "null",
+            Value::Bool(_) => "boolean",
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+            Value::Object(_) => "object",
+        }
+    }
+}
+
+impl std::fmt::Display for ParameterType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParameterType::String => write!(f, "string"),
+            ParameterType::Number => write!(f, "number"),
+            ParameterType::Boolean => write!(f, "boolean"),
+            ParameterType::Array(item) => write!(f, "array<{}>", item),
+            ParameterType::Json => write!(f, "json"),
+            ParameterType::Enum(values) => write!(f, "{}", values.join(" | ")),
+            ParameterType::Duration => write!(f, "duration"),
+            ParameterType::Object(fields) if fields.is_empty() => write!(f, "object"),
+            ParameterType::Object(fields) => {
+                let fields: Vec<String> = fields.iter()
+                    .map(|(name, field_type)| format!("{}: {}", name, field_type))
+                    .collect();
+                write!(f, "object {{ {} }}", fields.join(", "))
+            }
+        }
+    }
+}
+
+/// Aggiunge ai parametri già risolti quelli omessi che hanno un `default_value`
+pub fn apply_parameter_defaults(parameters: &[ParameterDefinition], params: &mut HashMap<String, LoomValue>) {
+    for parameter in parameters {
+        if let Some(default) = &parameter.default_value
+            && !params.contains_key(&parameter.name) {
+            params.insert(parameter.name.clone(), default.clone());
+        }
+    }
+}
+
+/// Formatta le righe della sezione PARAMETERS dell'help, allineando nomi e tipi in colonna.
+/// Ogni riga è (nome, tipo, flag, descrizione)
+pub(crate) fn format_help_parameters(rows: &[(String, String, String, String)]) -> String {
+    let name_width = rows.iter().map(|(name, ..)| name.len()).max().unwrap_or(0);
+    let type_width = rows.iter().map(|(_, param_type, ..)| param_type.len()).max().unwrap_or(0);
+
+    let mut help = String::from("PARAMETERS:\n");
+    if rows.is_empty() {
+        help.push_str("    (none)\n");
+    }
+    for (name, param_type, flags, description) in rows {
+        let line = format!("    {:<name_width$}   {:<type_width$}   {}", name, param_type, flags);
+        help.push_str(line.trim_end());
+        help.push('\n');
+        if !description.is_empty() {
+            help.push_str(&format!("        {}\n", description));
+        }
+    }
+    help
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(value: &str) -> LiteralValue {
+        LiteralValue::String(value.to_string())
+    }
+
+    #[test]
+    fn duration_accepts_only_valid_duration_strings() {
+        assert!(ParameterType::Duration.validate_literal_type(&string("2h30m")).is_ok());
+        assert!(ParameterType::Duration.validate_literal_type(&string("soon")).is_err());
+        assert!(ParameterType::Duration.validate_literal_type(&LiteralValue::Number(30)).is_err());
+    }
+
+    fn json(value: &str) -> LiteralValue {
+        LiteralValue::Json(serde_json::from_str(value).unwrap())
+    }
+
+    #[test]
+    fn object_accepts_objects_matching_its_fields() {
+        let any = ParameterType::Object(vec![]);
+        assert!(any.validate_literal_type(&json(r#"{"anything": [1, 2]}"#)).is_ok());
+
+        let target = ParameterType::Object(vec![("host".to_string(), ParameterType::String), ("port".to_string(), ParameterType::Number)]);
+        assert!(target.validate_literal_type(&json(r#"{"host": "localhost", "port": 8080, "extra": true}"#)).is_ok());
+        assert!(target.validate_literal_type(&json(r#"{"host": "localhost"}"#)).is_err());
+        assert!(target.validate_literal_type(&json(r#"{"host": "localhost", "port": "8080"}"#)).is_err());
+    }
+
+    #[test]
+    fn object_rejects_arrays_and_scalars_unlike_json() {
+        let object = ParameterType::Object(vec![]);
+        for value in [json("[1, 2]"), json("3"), string("{}")] {
+            assert!(object.validate_literal_type(&value).is_err(), "{:?}", value);
+            assert!(ParameterType::Json.validate_literal_type(&value).is_ok(), "{:?}", value);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::error::{ErrorSeverity, LoomError, LoomResult};
+
+/// Accumulates errors and warnings instead of failing on the first one, for consumers
+/// (IDE, linter, `loom check`) that want every diagnostic at once.
+/// The severity of each diagnostic is the one reported by `LoomError::severity`
+#[derive(Debug, Default)]
+pub struct DiagnosticCollector {
+    diagnostics: Vec<LoomError>,
+}
+
+impl DiagnosticCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, error: LoomError) {
+        self.diagnostics.push(error);
+    }
+
+    /// Records the error of a failed result and returns the value of a successful one
+    pub fn record<T>(&mut self, result: LoomResult<T>) -> Option<T> {
+        result.map_err(|error| self.push(error)).ok()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    /// Diagnostics with `ErrorSeverity::Error`
+    pub fn errors(&self) -> impl Iterator<Item = &LoomError> {
+        self.with_severity(ErrorSeverity::Error)
+    }
+
+    /// Diagnostics with `ErrorSeverity::Warning`, e.g. deprecated parameters
+    pub fn warnings(&self) -> impl Iterator<Item = &LoomError> {
+        self.with_severity(ErrorSeverity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// All diagnostics, in the order they were collected
+    pub fn into_diagnostics(self) -> Vec<LoomError> {
+        self.diagnostics
+    }
+
+    /// Fails with every diagnostic if at least one is an error, otherwise returns the warnings
+    pub fn finish(self) -> Result<Vec<LoomError>, Vec<LoomError>> {
+        if self.has_errors() {
+            Err(self.diagnostics)
+        } else {
+            Ok(self.diagnostics)
+        }
+    }
+
+    fn with_severity(&self, severity: ErrorSeverity) -> impl Iterator<Item = &LoomError> {
+        self.diagnostics.iter().filter(move |error| error.severity() == severity)
+    }
+}
+
+impl Extend<LoomError> for DiagnosticCollector {
+    fn extend<I: IntoIterator<Item = LoomError>>(&mut self, errors: I) {
+        self.diagnostics.extend(errors);
+    }
+}
@@ -1,7 +1,11 @@
 use crate::types::Position;
 use std::fmt;
+use std::sync::Arc;
 use crate::interceptor::scope::ExecutionScope;
 
+/// Original error kept by the `From` conversions, exposed through `std::error::Error::source`
+pub type ErrorSource = Arc<dyn std::error::Error + Send + Sync>;
+
 /// Main error type for Loom operations
 #[derive(Debug, Clone)]
 pub enum LoomError {
@@ -15,6 +19,8 @@ pub enum LoomError {
     ValidationError {
         message: String,
         position: Option<Position>,
+        /// Underlying error, e.g. the `serde_json::Error` of a JSON conversion
+        source: Option<ErrorSource>,
     },
 
     /// Runtime execution errors
@@ -43,12 +49,16 @@ pub enum LoomError {
         name: String,
         kind: UndefinedKind,
         position: Position,
+        /// Closest known names, shown as "did you mean" hints
+        suggestions: Vec<String>,
     },
 
     /// I/O and file system errors
     IoError {
         message: String,
         path: Option<String>,
+        /// Underlying `std::io::Error`, when converted from one (see `io_kind`)
+        source: Option<ErrorSource>,
     },
 
     /// Configuration errors
@@ -126,6 +136,17 @@ pub enum LoomError {
         chain_position: usize,
         cause: Box<LoomError>,
     },
+
+    /// Errori multipli (es. branch paralleli falliti), ognuno con la posizione del branch
+    AggregateError {
+        message: String,
+        errors: Vec<(usize, LoomError)>,
+    },
+
+    /// Esecuzione annullata tramite il CancellationToken
+    Cancelled {
+        message: String,
+    },
     
 }
 
@@ -161,6 +182,8 @@ pub enum InterceptorError {
     ParameterValidation {
         name: String,
         message: String,
+        /// Uso di un parametro deprecato: segnalato con severità Warning invece che Error
+        deprecated: bool,
     },
     // Chain execution errors
     ChainExecution {
@@ -190,7 +213,9 @@ pub enum UndefinedKind {
     Job,
     Pipeline,
     Variable,
+    EnvVariable,
     Function,
+    Directive,
     Enum,
     EnumVariant,
     Import,
@@ -213,6 +238,7 @@ impl LoomError {
         Self::ValidationError {
             message: message.into(),
             position: None,
+            source: None,
         }
     }
 
@@ -221,6 +247,7 @@ impl LoomError {
         Self::ValidationError {
             message: message.into(),
             position: Some(position),
+            source: None,
         }
     }
 
@@ -233,6 +260,15 @@ impl LoomError {
         }
     }
 
+    /// Create an execution error with position
+    pub fn execution_at(message: impl Into<String>, position: Position) -> Self {
+        Self::ExecutionError {
+            message: message.into(),
+            position: Some(position),
+            cause: None,
+        }
+    }
+
     /// Create an execution error with cause
     pub fn execution_with_cause(message: impl Into<String>, cause: LoomError) -> Self {
         Self::ExecutionError {
@@ -257,6 +293,32 @@ impl LoomError {
             name: name.into(),
             kind,
             position,
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Create an undefined reference error suggesting the candidates closest to `name`
+    pub fn undefined_among<'a>(
+        name: impl Into<String>,
+        kind: UndefinedKind,
+        position: Position,
+        candidates: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        let name = name.into();
+        Self::UndefinedError {
+            suggestions: suggest_similar(&name, candidates),
+            name,
+            kind,
+            position,
+        }
+    }
+
+    /// Create an import error
+    pub fn import(message: impl Into<String>, import_path: impl Into<String>, position: Position) -> Self {
+        Self::ImportError {
+            message: message.into(),
+            import_path: import_path.into(),
+            position,
         }
     }
 
@@ -265,6 +327,7 @@ impl LoomError {
         Self::IoError {
             message: message.into(),
             path: None,
+            source: None,
         }
     }
 
@@ -273,6 +336,23 @@ impl LoomError {
         Self::IoError {
             message: message.into(),
             path: Some(path.into()),
+            source: None,
+        }
+    }
+
+    /// Create a configuration error
+    pub fn config(message: impl Into<String>) -> Self {
+        Self::ConfigError {
+            message: message.into(),
+            path: None,
+        }
+    }
+
+    /// Create a configuration error with path
+    pub fn config_with_path(message: impl Into<String>, path: impl Into<String>) -> Self {
+        Self::ConfigError {
+            message: message.into(),
+            path: Some(path.into()),
         }
     }
 
@@ -360,6 +440,19 @@ impl LoomError {
             error: InterceptorError::ParameterValidation {
                 name: name.into(),
                 message: message.into(),
+                deprecated: false,
+            },
+            interceptor_stack: Vec::new(),
+        }
+    }
+
+    /// Create a warning for the use of a deprecated parameter (see `severity`)
+    pub fn deprecated_parameter(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::InterceptorError {
+            error: InterceptorError::ParameterValidation {
+                name: name.into(),
+                message: message.into(),
+                deprecated: true,
             },
             interceptor_stack: Vec::new(),
         }
@@ -528,6 +621,39 @@ impl LoomError {
         }
     }
 
+    /// Create an aggregate error from the failed branches and their positions
+    pub fn aggregate(message: impl Into<String>, errors: Vec<(usize, LoomError)>) -> Self {
+        Self::AggregateError {
+            message: message.into(),
+            errors,
+        }
+    }
+
+    /// Create a cancellation error
+    pub fn cancelled(message: impl Into<String>) -> Self {
+        Self::Cancelled {
+            message: message.into(),
+        }
+    }
+
+    /// Whether the error comes from a cancelled execution
+    pub fn is_cancelled(&self) -> bool {
+        match self {
+            Self::Cancelled { .. } => true,
+            Self::InterceptorChainError { cause, .. } => cause.is_cancelled(),
+            _ => false,
+        }
+    }
+
+    /// Whether the error comes from a failed command (non-zero exit code or timeout)
+    pub fn is_command_execution(&self) -> bool {
+        match self {
+            Self::InterceptorError { error: InterceptorError::CommandExecution { .. }, .. } => true,
+            Self::InterceptorChainError { cause, .. } => cause.is_command_execution(),
+            _ => false,
+        }
+    }
+
     /// Get the error position if available
     pub fn position(&self) -> Option<&Position> {
         match self {
@@ -541,19 +667,84 @@ impl LoomError {
         }
     }
 
+    /// Kind of the underlying I/O error (e.g. `NotFound` vs `PermissionDenied`), when the error
+    /// was converted from a `std::io::Error`
+    pub fn io_kind(&self) -> Option<std::io::ErrorKind> {
+        match self {
+            Self::IoError { source: Some(source), .. } => {
+                source.downcast_ref::<std::io::Error>().map(std::io::Error::kind)
+            }
+            _ => None,
+        }
+    }
+
     /// Get error severity level
     pub fn severity(&self) -> ErrorSeverity {
         match self {
-            _ => ErrorSeverity::Error
+            Self::InterceptorError {
+                error: InterceptorError::ParameterValidation { deprecated: true, .. },
+                ..
+            } => ErrorSeverity::Warning,
+            Self::NotImplementedError { .. } => ErrorSeverity::Warning,
+            Self::InterceptorChainError { cause, .. } => cause.severity(),
+            _ => ErrorSeverity::Error,
         }
     }
 
+    /// Prepend an interceptor name to the stack of an interceptor error.
+    /// Frames are added while the error bubbles up, so the outermost interceptor ends up first.
+    pub fn with_interceptor_frame(mut self, name: impl Into<String>) -> Self {
+        if let Self::InterceptorError { interceptor_stack, .. } = &mut self {
+            interceptor_stack.insert(0, name.into());
+        }
+        self
+    }
+
+    /// Prepend context (e.g. the layer the error is bubbling through) to the error message.
+    /// Variants with a `message` are updated in place, keeping their kind and position;
+    /// structured variants without one (e.g. `TypeError`) are wrapped as the cause of an `ExecutionError`
     pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        let context = context.into();
+        if let Self::InterceptorChainError { interceptor_name, chain_position, cause } = self {
+            return Self::InterceptorChainError { interceptor_name, chain_position, cause: Box::new(cause.with_context(context)) };
+        }
         match &mut self {
-            Self::ExecutionError { message, .. } => {
-                *message = format!("{}: {}", context.into(), message);
+            Self::ParseError { message, .. }
+            | Self::ValidationError { message, .. }
+            | Self::ExecutionError { message, .. }
+            | Self::ImportError { message, .. }
+            | Self::IoError { message, .. }
+            | Self::ConfigError { message, .. }
+            | Self::PluginError { message, .. }
+            | Self::SystemError { message, .. }
+            | Self::ConcurrencyError { message, .. }
+            | Self::ExpressionError { message, .. }
+            | Self::AggregateError { message, .. }
+            | Self::Cancelled { message } => {
+                *message = format!("{}: {}", context, message);
+            }
+            Self::InterceptorError { error, .. } => {
+                let message = match error {
+                    InterceptorError::Directive { message, .. }
+                    | InterceptorError::Global { message, .. }
+                    | InterceptorError::Execution { message, .. }
+                    | InterceptorError::CommandExecution { message, .. }
+                    | InterceptorError::DefinitionResolution { message, .. }
+                    | InterceptorError::ParameterValidation { message, .. }
+                    | InterceptorError::ChainExecution { message }
+                    | InterceptorError::ContextAccess { message }
+                    | InterceptorError::PipelineExecution { message, .. }
+                    | InterceptorError::JobExecution { message, .. } => message,
+                };
+                *message = format!("{}: {}", context, message);
+            }
+            _ => {
+                return Self::ExecutionError {
+                    message: context,
+                    position: self.position().cloned(),
+                    cause: Some(Box::new(self)),
+                };
             }
-            _ => {}
         }
         self
     }
@@ -571,18 +762,18 @@ impl fmt::Display for LoomError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::ParseError { message, position } => {
-                write!(f, "Parse error at {}:{}: {}", position.line, position.column, message)
+                write!(f, "Parse error at {}: {}", position, message)
             }
-            Self::ValidationError { message, position } => {
+            Self::ValidationError { message, position, .. } => {
                 if let Some(pos) = position {
-                    write!(f, "Validation error at {}:{}: {}", pos.line, pos.column, message)
+                    write!(f, "Validation error at {}: {}", pos, message)
                 } else {
                     write!(f, "Validation error: {}", message)
                 }
             }
             Self::ExecutionError { message, position, cause } => {
                 if let Some(pos) = position {
-                    write!(f, "Execution error at {}:{}: {}", pos.line, pos.column, message)?;
+                    write!(f, "Execution error at {}: {}", pos, message)?;
                 } else {
                     write!(f, "Execution error: {}", message)?;
                 }
@@ -592,18 +783,18 @@ impl fmt::Display for LoomError {
                 Ok(())
             }
             Self::ImportError { message, import_path, position } => {
-                write!(f, "Import error at {}:{} importing '{}': {}",
-                       position.line, position.column, import_path, message)
+                write!(f, "Import error at {} importing '{}': {}",
+                       position, import_path, message)
             }
             Self::TypeError { expected, found, position } => {
-                write!(f, "Type error at {}:{}: expected {}, found {}",
-                       position.line, position.column, expected, found)
+                write!(f, "Type error at {}: expected {}, found {}",
+                       position, expected, found)
             }
-            Self::UndefinedError { name, kind, position } => {
-                write!(f, "Undefined {} '{}' at {}:{}",
-                       kind, name, position.line, position.column)
+            Self::UndefinedError { name, kind, position, suggestions } => {
+                write!(f, "Undefined {} '{}' at {}{}",
+                       kind, name, position, did_you_mean(suggestions))
             }
-            Self::IoError { message, path } => {
+            Self::IoError { message, path, .. } => {
                 if let Some(path) = path {
                     write!(f, "I/O error on '{}': {}", path, message)
                 } else {
@@ -629,8 +820,8 @@ impl fmt::Display for LoomError {
             }
             Self::ConversionError { from_type, to_type, value, position } => {
                 if let Some(pos) = position {
-                    write!(f, "Conversion error at {}:{}: cannot convert '{}' from {} to {}",
-                           pos.line, pos.column, value, from_type, to_type)
+                    write!(f, "Conversion error at {}: cannot convert '{}' from {} to {}",
+                           pos, value, from_type, to_type)
                 } else {
                     write!(f, "Conversion error: cannot convert '{}' from {} to {}",
                            value, from_type, to_type)
@@ -641,25 +832,26 @@ impl fmt::Display for LoomError {
                        resource, operation, message)
             }
             Self::ExpressionError { expression_type, message, position } => {
-                write!(f, "Expression error in {} at {}:{}: {}",
-                       expression_type, position.line, position.column, message)
+                write!(f, "Expression error in {} at {}: {}",
+                       expression_type, position, message)
             }
             Self::NotImplementedError { feature, context, position } => {
                 if let Some(pos) = position {
-                    write!(f, "Feature '{}' not implemented in context '{}' at {}:{}",
-                           feature, context, pos.line, pos.column)
+                    write!(f, "Feature '{}' not implemented in context '{}' at {}",
+                           feature, context, pos)
                 } else {
                     write!(f, "Feature '{}' not implemented in context '{}'",
                            feature, context)
                 }
             }
             Self::DefinitionNotFoundError { name, available_definitions, position } => {
-                write!(f, "Definition '{}' not found at {}:{}. Available definitions: [{}]",
-                       name, position.line, position.column,
-                       available_definitions.join(", "))
+                let suggestions = suggest_similar(name, available_definitions.iter().map(String::as_str));
+                write!(f, "Definition '{}' not found at {}. Available definitions: [{}]{}",
+                       name, position,
+                       available_definitions.join(", "), did_you_mean(&suggestions))
             }
             Self::ParameterError { definition_name, expected_count, provided_count, parameter_name, position } => {
-                let pos_str = position.as_ref().map(|p| format!(" at {}:{}", p.line, p.column)).unwrap_or_default();
+                let pos_str = position.as_ref().map(|p| format!(" at {}", p)).unwrap_or_default();
                 if let Some(param) = parameter_name {
                     write!(f, "Parameter error in '{}'{}: invalid parameter '{}'",
                            definition_name, pos_str, param)
@@ -672,6 +864,16 @@ impl fmt::Display for LoomError {
                 write!(f, "Interceptor chain error at position {} in '{}': {}",
                        chain_position, interceptor_name, cause)
             }
+            Self::AggregateError { message, errors } => {
+                write!(f, "{} ({} failed):", message, errors.len())?;
+                for (index, error) in errors {
+                    write!(f, "\n  [branch {}] {}", index, error)?;
+                }
+                Ok(())
+            }
+            Self::Cancelled { message } => {
+                write!(f, "Execution cancelled: {}", message)
+            }
             Self::InterceptorError { error, interceptor_stack } => {
                 let stack = 
                     interceptor_stack.join(", ");
@@ -732,7 +934,7 @@ impl fmt::Display for LoomError {
                             stack
                         )
                     },
-                    InterceptorError::ParameterValidation { name, message } => {
+                    InterceptorError::ParameterValidation { name, message, .. } => {
                         write!(
                             f,
                             "Parameter validation error: '{}' - {}.\nThe following interceptor have been already been executed: [ {} ]",
@@ -794,6 +996,66 @@ impl fmt::Display for LoomError {
     }
 }
 
+/// Maximum number of "did you mean" suggestions
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Candidates within a small edit distance of `name`, closest first (at most `MAX_SUGGESTIONS`).
+/// The accepted distance grows with the length of the name, so short names only match near-identical candidates
+pub fn suggest_similar<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let threshold = (name.chars().count() / 3).clamp(1, 3);
+
+    let mut matches = candidates.into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .collect::<Vec<_>>();
+    matches.sort();
+    matches.dedup();
+
+    matches.into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Suffix for error messages: empty without suggestions, otherwise ". Did you mean 'a' or 'b'?"
+pub fn did_you_mean(suggestions: &[String]) -> String {
+    match suggestions {
+        [] => String::new(),
+        [single] => format!(". Did you mean '{}'?", single),
+        [rest @ .., last] => format!(
+            ". Did you mean {} or '{}'?",
+            rest.iter().map(|it| format!("'{}'", it)).collect::<Vec<_>>().join(", "),
+            last
+        ),
+    }
+}
+
+/// Edit distance (insertions, deletions, substitutions and adjacent transpositions) between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    // Only the last two rows of the matrix are needed: a transposition looks two characters back
+    let mut before_previous = vec![0; b.len() + 1];
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+
+    for i in 0..a.len() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for j in 0..b.len() {
+            let mut distance = (previous[j] + usize::from(a[i] != b[j]))
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+            if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                distance = distance.min(before_previous[j - 1] + 1);
+            }
+            current[j + 1] = distance;
+        }
+        before_previous = std::mem::replace(&mut previous, current);
+    }
+
+    previous[b.len()]
+}
+
 impl fmt::Display for UndefinedKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -801,7 +1063,9 @@ impl fmt::Display for UndefinedKind {
             Self::Job => write!(f, "job"),
             Self::Pipeline => write!(f, "pipeline"),
             Self::Variable => write!(f, "variable"),
+            Self::EnvVariable => write!(f, "environment variable"),
             Self::Function => write!(f, "function"),
+            Self::Directive => write!(f, "directive"),
             Self::Enum => write!(f, "enum"),
             Self::EnumVariant => write!(f, "enum variant"),
             Self::Import => write!(f, "import"),
@@ -813,6 +1077,8 @@ impl std::error::Error for LoomError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
             Self::ExecutionError { cause: Some(cause), .. } => Some(cause.as_ref()),
+            Self::ValidationError { source: Some(source), .. }
+            | Self::IoError { source: Some(source), .. } => Some(source.as_ref()),
             _ => None,
         }
     }
@@ -821,14 +1087,22 @@ impl std::error::Error for LoomError {
 // Conversion from std::io::Error
 impl From<std::io::Error> for LoomError {
     fn from(error: std::io::Error) -> Self {
-        Self::io(error.to_string())
+        Self::IoError {
+            message: error.to_string(),
+            path: None,
+            source: Some(Arc::new(error)),
+        }
     }
 }
 
 // Conversion from serde_json::Error
 impl From<serde_json::Error> for LoomError {
     fn from(error: serde_json::Error) -> Self {
-        Self::validation(format!("JSON error: {}", error))
+        Self::ValidationError {
+            message: format!("JSON error: {}", error),
+            position: None,
+            source: Some(Arc::new(error)),
+        }
     }
 }
 
@@ -932,4 +1206,74 @@ macro_rules! job_error {
     ($name:expr, $fmt:expr, $($arg:tt)*) => {
         Err(crate::error::LoomError::job_execution($name, format!($fmt, $($arg)*)))
     };
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn severity_per_variant() {
+        assert_eq!(LoomError::deprecated_parameter("retry", "use 'times'").severity(), ErrorSeverity::Warning);
+        assert_eq!(LoomError::not_implemented("@cache", "directive").severity(), ErrorSeverity::Warning);
+        assert_eq!(LoomError::parse("unexpected token", Position::default()).severity(), ErrorSeverity::Error);
+        assert_eq!(LoomError::type_error("boolean", "string", Position::default()).severity(), ErrorSeverity::Error);
+        assert_eq!(LoomError::execution("exit code 1").severity(), ErrorSeverity::Error);
+    }
+
+    #[test]
+    fn severity_does_not_depend_on_the_message() {
+        assert_eq!(LoomError::parameter_validation("retry", "deprecated value").severity(), ErrorSeverity::Error);
+
+        let wrapped = LoomError::interceptor_chain("retry", 2, LoomError::deprecated_parameter("retry", "use 'times'"));
+        assert_eq!(wrapped.severity(), ErrorSeverity::Warning);
+    }
+
+    #[test]
+    fn with_context_prepends_to_the_message() {
+        let message = |error: LoomError| match error {
+            LoomError::ValidationError { message, .. }
+            | LoomError::IoError { message, .. }
+            | LoomError::ImportError { message, .. } => message,
+            LoomError::InterceptorError { error: InterceptorError::Directive { message, .. }, .. } => message,
+            other => panic!("unexpected variant {:?}", other),
+        };
+
+        assert_eq!(message(LoomError::validation("bad value").with_context("@retry")), "@retry: bad value");
+        assert_eq!(message(LoomError::io("not found").with_context("reading build.loom")), "reading build.loom: not found");
+        assert_eq!(
+            message(LoomError::import("cycle", "lib.loom", Position::default()).with_context("main.loom")),
+            "main.loom: cycle"
+        );
+        assert_eq!(message(LoomError::directive_interceptor("if", "not a boolean").with_context("build")), "build: not a boolean");
+    }
+
+    #[test]
+    fn with_context_wraps_variants_without_a_message() {
+        let error = LoomError::type_error("boolean", "string", Position::default()).with_context("@if condition");
+        let LoomError::ExecutionError { message, cause: Some(cause), .. } = error else {
+            panic!("expected an ExecutionError");
+        };
+        assert_eq!(message, "@if condition");
+        assert!(matches!(*cause, LoomError::TypeError { .. }));
+    }
+
+    #[test]
+    fn io_error_keeps_its_kind_and_source() {
+        use std::error::Error;
+
+        let error = LoomError::from(std::io::Error::new(std::io::ErrorKind::NotFound, "build.loom"));
+        assert!(matches!(error, LoomError::IoError { .. }));
+        assert_eq!(error.io_kind(), Some(std::io::ErrorKind::NotFound));
+        let source = error.source().expect("source should be populated");
+        assert_eq!(source.downcast_ref::<std::io::Error>().map(std::io::Error::kind), Some(std::io::ErrorKind::NotFound));
+    }
+
+    #[test]
+    fn json_error_keeps_its_source() {
+        use std::error::Error;
+
+        let error = LoomError::from(serde_json::from_str::<serde_json::Value>("{").unwrap_err());
+        assert!(matches!(error, LoomError::ValidationError { .. }));
+        assert!(error.source().is_some_and(|source| source.is::<serde_json::Error>()));
+    }
+}
@@ -3,12 +3,15 @@ use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc;
 use uuid::Uuid;
+use crate::event::secret::SecretMasker;
 
 /// Channel per comunicare eventi durante l'esecuzione
 #[derive(Debug, Clone)]
 pub struct ExecutionEventChannel {
     pub execution_id: Arc<String>,
     pub sender: mpsc::UnboundedSender<ExecutionEvent>,
+    /// Valori segreti mascherati in ogni evento prima dell'invio
+    pub secrets: SecretMasker,
 }
 
 impl ExecutionEventChannel {
@@ -16,10 +19,14 @@ impl ExecutionEventChannel {
         let (sender, receiver) = mpsc::unbounded_channel();
         let execution_id = Uuid::new_v4().to_string();
 
-        (Self { execution_id: Arc::new(execution_id), sender }, receiver)
+        (Self { execution_id: Arc::new(execution_id), sender, secrets: SecretMasker::new() }, receiver)
     }
 
-    pub fn emit(&self, event: ExecutionEvent) -> Result<(), String> {
+    pub fn emit(&self, mut event: ExecutionEvent) -> Result<(), String> {
+        if !self.secrets.is_empty() {
+            event.kind.mask_secrets(&self.secrets);
+            event.metadata.values_mut().for_each(|value| self.secrets.mask_in_place(value));
+        }
         self.sender.send(event)
             .map_err(|_| "Failed to send execution event".to_string())
     }
@@ -64,6 +71,11 @@ pub enum ExecutionEventKind {
         error: String,
         duration_ms: u64,
     },
+    /// Execution stopped through its cancellation token
+    ExecutionCancelled {
+        definition_name: String,
+        reason: String,
+    },
 
     // Command Events
     CommandStarted {
@@ -82,6 +94,19 @@ pub enum ExecutionEventKind {
         exit_code: Option<i32>,
         duration_ms: u64,
     },
+    /// Nuovo tentativo di un comando fallito, richiesto da un hook o da `ExecutorConfig::retries`
+    CommandRetry {
+        command: String,
+        attempt: u32,
+        max_attempts: u32,
+        delay_ms: u64,
+    },
+    /// Singola riga di output emessa mentre il comando è in esecuzione
+    CommandOutputLine {
+        command: String,
+        line: String,
+        is_stderr: bool,
+    },
 
     // Interceptor Events
     InterceptorTriggered {
@@ -101,6 +126,19 @@ pub enum ExecutionEventKind {
         parameters: HashMap<String, String>,
         result: String,
     },
+    /// Nuovo tentativo di un target fallito, richiesto da @retry
+    DirectiveRetry {
+        directive_name: String,
+        attempt: u32,
+        max_attempts: u32,
+        delay_ms: u64,
+        error: String,
+    },
+    /// Warning non bloccante di una direttiva, es. uso di un parametro deprecato
+    DirectiveWarning {
+        directive_name: String,
+        message: String,
+    },
 
     // Pipeline/Job Events (per il futuro)
     StageStarted {
@@ -164,6 +202,70 @@ pub enum ExecutionEventKind {
     },
 }
 
+impl ExecutionEventKind {
+    /// Stable name of the variant, used to filter events by type
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            ExecutionEventKind::ExecutionStarted { .. } => "ExecutionStarted",
+            ExecutionEventKind::ExecutionCompleted { .. } => "ExecutionCompleted",
+            ExecutionEventKind::ExecutionFailed { .. } => "ExecutionFailed",
+            ExecutionEventKind::ExecutionCancelled { .. } => "ExecutionCancelled",
+            ExecutionEventKind::CommandStarted { .. } => "CommandStarted",
+            ExecutionEventKind::CommandCompleted { .. } => "CommandCompleted",
+            ExecutionEventKind::CommandFailed { .. } => "CommandFailed",
+            ExecutionEventKind::CommandRetry { .. } => "CommandRetry",
+            ExecutionEventKind::CommandOutputLine { .. } => "CommandOutputLine",
+            ExecutionEventKind::InterceptorTriggered { .. } => "InterceptorTriggered",
+            ExecutionEventKind::InterceptorCompleted { .. } => "InterceptorCompleted",
+            ExecutionEventKind::DirectiveEvaluated { .. } => "DirectiveEvaluated",
+            ExecutionEventKind::DirectiveRetry { .. } => "DirectiveRetry",
+            ExecutionEventKind::DirectiveWarning { .. } => "DirectiveWarning",
+            ExecutionEventKind::StageStarted { .. } => "StageStarted",
+            ExecutionEventKind::StageCompleted { .. } => "StageCompleted",
+            ExecutionEventKind::JobStarted { .. } => "JobStarted",
+            ExecutionEventKind::JobCompleted { .. } => "JobCompleted",
+            ExecutionEventKind::HookTriggered { .. } => "HookTriggered",
+            ExecutionEventKind::ProgressUpdate { .. } => "ProgressUpdate",
+            ExecutionEventKind::ResourceUsage { .. } => "ResourceUsage",
+            ExecutionEventKind::Custom { .. } => "Custom",
+            ExecutionEventKind::VariableResolved { .. } => "VariableResolved",
+            ExecutionEventKind::ExpressionEvaluated { .. } => "ExpressionEvaluated",
+        }
+    }
+
+    /// Maschera i segreti in tutti i campi testuali che possono contenere valori valutati o output
+    pub fn mask_secrets(&mut self, secrets: &SecretMasker) {
+        match self {
+            ExecutionEventKind::ExecutionFailed { error: text, .. }
+            | ExecutionEventKind::ExecutionCancelled { reason: text, .. }
+            | ExecutionEventKind::CommandStarted { command: text, .. }
+            | ExecutionEventKind::CommandCompleted { command: text, .. }
+            | ExecutionEventKind::CommandRetry { command: text, .. }
+            | ExecutionEventKind::DirectiveRetry { error: text, .. }
+            | ExecutionEventKind::ProgressUpdate { current_task: text, .. } => secrets.mask_in_place(text),
+            ExecutionEventKind::CommandFailed { command, error, .. } => {
+                secrets.mask_in_place(command);
+                secrets.mask_in_place(error);
+            }
+            ExecutionEventKind::CommandOutputLine { command, line, .. } => {
+                secrets.mask_in_place(command);
+                secrets.mask_in_place(line);
+            }
+            ExecutionEventKind::DirectiveEvaluated { parameters, result, .. } => {
+                parameters.values_mut().for_each(|value| secrets.mask_in_place(value));
+                secrets.mask_in_place(result);
+            }
+            ExecutionEventKind::VariableResolved { value, .. } => secrets.mask_in_place(value),
+            ExecutionEventKind::ExpressionEvaluated { expression, result, .. } => {
+                secrets.mask_in_place(expression);
+                secrets.mask_in_place(result);
+            }
+            ExecutionEventKind::Custom { data, .. } => secrets.mask_json(data),
+            _ => {}
+        }
+    }
+}
+
 impl ExecutionEvent {
     pub fn is_error(&self) -> bool {
         matches!(self.kind,
@@ -176,7 +278,8 @@ impl ExecutionEvent {
         matches!(self.kind,
             ExecutionEventKind::ExecutionStarted { .. } |
             ExecutionEventKind::ExecutionCompleted { .. } |
-            ExecutionEventKind::ExecutionFailed { .. }
+            ExecutionEventKind::ExecutionFailed { .. } |
+            ExecutionEventKind::ExecutionCancelled { .. }
         )
     }
 
@@ -212,11 +315,10 @@ impl EventFilter {
         }
 
         if let Some(ref types) = self.event_types {
-            let event_type = std::mem::discriminant(&event.kind);
-            // Simplified type checking - in real impl you'd want proper type names
-            // if !types.contains(&format!("{:?}", event_type)) {
-            //     return false;
-            // }
+            let event_type = event.kind.kind_name();
+            if !types.iter().any(|t| t == event_type) {
+                return false;
+            }
         }
 
         if let Some((start, end)) = self.time_range {
@@ -1 +1,2 @@
-pub mod channel;
\ No newline at end of file
+pub mod channel;
+pub mod secret;
@@ -0,0 +1,63 @@
+use std::sync::{Arc, RwLock};
+
+/// Testo con cui vengono sostituiti i valori segreti
+pub const SECRET_MASK: &str = "****";
+
+/// Valori sensibili da non mostrare in output, errori ed eventi.
+/// I cloni condividono i valori registrati
+#[derive(Debug, Clone, Default)]
+pub struct SecretMasker {
+    // Ordinati dal più lungo, così un segreto che ne contiene un altro viene mascherato per intero
+    secrets: Arc<RwLock<Vec<String>>>,
+}
+
+impl SecretMasker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registra un valore da mascherare, i valori vuoti vengono ignorati
+    pub fn register(&self, value: impl Into<String>) {
+        let value = value.into();
+        if value.is_empty() {
+            return;
+        }
+        if let Ok(mut secrets) = self.secrets.write()
+            && !secrets.contains(&value) {
+            secrets.push(value);
+            secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.secrets.read().map_or(true, |secrets| secrets.is_empty())
+    }
+
+    /// Copia del testo con ogni valore segreto sostituito da `SECRET_MASK`
+    pub fn mask(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        self.mask_in_place(&mut text);
+        text
+    }
+
+    pub fn mask_in_place(&self, text: &mut String) {
+        let Ok(secrets) = self.secrets.read() else {
+            return;
+        };
+        for secret in secrets.iter() {
+            if text.contains(secret.as_str()) {
+                *text = text.replace(secret.as_str(), SECRET_MASK);
+            }
+        }
+    }
+
+    /// Maschera tutte le stringhe contenute in un valore JSON
+    pub fn mask_json(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(text) => self.mask_in_place(text),
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|item| self.mask_json(item)),
+            serde_json::Value::Object(entries) => entries.values_mut().for_each(|item| self.mask_json(item)),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::time::Duration;
+use crate::error::{LoomError, LoomResult};
+
+/// Token condiviso per annullare un'esecuzione in corso.
+/// I cloni condividono lo stato: annullare uno qualsiasi li annulla tutti, un `child_token`
+/// risulta annullato anche quando lo è il padre ma non viceversa
+pub use tokio_util::sync::CancellationToken;
+
+/// Controlli sull'annullamento usati dal motore e dagli interceptor
+#[async_trait::async_trait]
+pub trait CancellationTokenExt {
+    /// Errore se l'annullamento è stato richiesto, `operation` indica il punto in cui è stato rilevato
+    fn check(&self, operation: &str) -> LoomResult<()>;
+
+    /// Attende `delay`, interrompendosi con `LoomError::Cancelled` appena viene richiesto l'annullamento
+    async fn sleep(&self, delay: Duration, operation: &str) -> LoomResult<()>;
+}
+
+#[async_trait::async_trait]
+impl CancellationTokenExt for CancellationToken {
+    fn check(&self, operation: &str) -> LoomResult<()> {
+        if self.is_cancelled() {
+            Err(LoomError::cancelled(format!("cancelled before {}", operation)))
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn sleep(&self, delay: Duration, operation: &str) -> LoomResult<()> {
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => Ok(()),
+            _ = self.cancelled() => Err(LoomError::cancelled(format!("cancelled while {}", operation))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+    use super::*;
+
+    #[tokio::test]
+    async fn sleep_stops_as_soon_as_the_token_is_cancelled() {
+        let token = CancellationToken::new();
+        let child = token.child_token();
+        let start = Instant::now();
+
+        let (result, _) = tokio::join!(
+            child.sleep(Duration::from_secs(60), "waiting to retry"),
+            async { token.cancel() },
+        );
+
+        assert!(result.unwrap_err().is_cancelled());
+        assert!(start.elapsed() < Duration::from_secs(5), "{:?}", start.elapsed());
+    }
+}
@@ -2,11 +2,16 @@ use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tokio::sync::Semaphore;
 use crate::context::LoomContext;
 use crate::event::channel::ExecutionEventChannel;
+use crate::error::{LoomError, LoomResult};
+use crate::interceptor::cancellation::CancellationToken;
+use crate::interceptor::env_file::load_env_file;
+use crate::interceptor::hook::HookPayload;
 use crate::interceptor::hook::registry::HookRegistry;
-use crate::interceptor::scope::{ExecutionActivity, ExecutionScope};
+use crate::interceptor::scope::{ExecutionActivity, ExecutionHook, ExecutionScope};
 use crate::types::{LoomValue, ParallelizationKind};
 
 /// Execution context for runtime
@@ -21,13 +26,72 @@ pub struct ExecutionContext {
     pub scope: ExecutionScope,
     pub parallelization_kind: ParallelizationKind,
     pub metadata: HashMap<String, String>,
+    /// Channel su cui tracciare la risoluzione di variabili ed espressioni, presente solo in debug
+    pub trace: Option<ExecutionEventChannel>,
 }
 
 
+/// Environment usato quando né `LOOM_ENV` né `ENVIRONMENT` sono impostate
+pub const DEFAULT_ENVIRONMENT: &str = "development";
+
 impl ExecutionContext {
     pub fn get_variable(&self, name: &str) -> Option<LoomValue> {
         self.variables.get(name).map(|it| it.clone())
     }
+
+    /// Environment corrente, letto da `LOOM_ENV` o in alternativa da `ENVIRONMENT`
+    pub fn current_environment(&self) -> &str {
+        self.env_vars.get("LOOM_ENV")
+            .or_else(|| self.env_vars.get("ENVIRONMENT"))
+            .map_or(DEFAULT_ENVIRONMENT, String::as_str)
+    }
+}
+
+/// Opzioni con cui avviare un'esecuzione, applicate all'ExecutionContext iniziale
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionOptions {
+    /// Stampa i comandi senza eseguirli
+    pub dry_run: bool,
+    /// Working directory dei comandi, al posto della directory corrente del processo
+    pub working_dir: Option<String>,
+    /// Variabili d'ambiente aggiunte (o sovrascritte) a quelle del processo
+    pub extra_env: HashMap<String, String>,
+    /// File dotenv le cui variabili vengono aggiunte a quelle d'ambiente, prima di `extra_env`
+    pub env_file: Option<String>,
+    /// Se attivo i valori dell'`env_file` sostituiscono quelli del processo, altrimenti il processo ha la precedenza
+    pub env_file_overrides: bool,
+    /// Variabili aggiunte (o sovrascritte) a quelle del modulo
+    pub variables: HashMap<Arc<str>, LoomValue>,
+    /// Emette `VariableResolved` ed `ExpressionEvaluated` durante la valutazione, utile in debug
+    /// ma con un costo a ogni espressione valutata
+    pub trace_evaluation: bool,
+    /// Token con cui annullare l'esecuzione dall'esterno
+    pub cancellation: CancellationToken,
+}
+
+impl ExecutionOptions {
+    /// Applica le opzioni al context. Fallisce solo se l'`env_file` non è leggibile o è malformato
+    pub fn apply(&self, context: &mut ExecutionContext) -> LoomResult<()> {
+        context.dry_run = self.dry_run;
+        if let Some(working_dir) = &self.working_dir {
+            context.working_dir = Some(working_dir.clone());
+        }
+        if let Some(env_file) = &self.env_file {
+            for (key, value) in load_env_file(env_file)? {
+                if self.env_file_overrides || !context.env_vars.contains_key(&key) {
+                    context.env_vars.insert(key, value);
+                }
+            }
+        }
+        context.env_vars.extend(self.extra_env.iter().map(|(key, value)| (key.clone(), value.clone())));
+        context.variables.extend(self.variables.iter().map(|(key, value)| (key.clone(), value.clone())));
+        Ok(())
+    }
+
+    /// Indica se una chain costruita con le opzioni di default può essere riutilizzata
+    pub fn shares_cached_chain(&self) -> bool {
+        self.extra_env.is_empty() && self.variables.is_empty() && self.env_file.is_none()
+    }
 }
 
 #[derive(Clone)]
@@ -36,6 +100,10 @@ pub struct InterceptorContext<'a> {
     pub execution_context: Arc<RwLock<ExecutionContext>>,
     pub hook_registry: &'a HookRegistry,
     pub channel: ExecutionEventChannel,
+    /// Permessi condivisi dall'engine: ogni comando ne acquisisce uno prima di avviare il processo
+    pub command_slots: Arc<Semaphore>,
+    /// Annullamento richiesto dal chiamante, controllato tra un interceptor e l'altro e durante i comandi
+    pub cancellation: CancellationToken,
 }
 
 // impl<'a> Clone for InterceptorContext<'a> {
@@ -47,4 +115,42 @@ pub struct InterceptorContext<'a> {
 //             channel: self.channel.clone(),
 //         }
 //     }
-// }
\ No newline at end of file
+// }
+
+/// Nome della risorsa riportato negli errori di lock sull'ExecutionContext
+const EXECUTION_CONTEXT_RESOURCE: &str = "ExecutionContext";
+
+impl InterceptorContext<'_> {
+    /// Acquisisce l'ExecutionContext in lettura per `operation`.
+    /// Un lock avvelenato diventa un `LoomError::concurrency`; il guard va rilasciato prima di ogni `.await`
+    pub fn read_execution_context(&self, operation: &str) -> LoomResult<RwLockReadGuard<'_, ExecutionContext>> {
+        self.execution_context.read()
+            .map_err(|err| LoomError::concurrency(EXECUTION_CONTEXT_RESOURCE, operation, err.to_string()))
+    }
+
+    /// Acquisisce l'ExecutionContext in scrittura per `operation`, vedi `read_execution_context`
+    pub fn write_execution_context(&self, operation: &str) -> LoomResult<RwLockWriteGuard<'_, ExecutionContext>> {
+        self.execution_context.write()
+            .map_err(|err| LoomError::concurrency(EXECUTION_CONTEXT_RESOURCE, operation, err.to_string()))
+    }
+
+    /// Copia dell'InterceptorContext con un ExecutionContext proprio, ottenuto dallo stato attuale.
+    /// Usata dai branch paralleli, così `@cwd`, `@env`, `@for` e gli argomenti di un branch
+    /// non vengono sovrascritti o ripristinati dagli altri
+    pub fn snapshot(&self, operation: &str) -> LoomResult<Self> {
+        let execution_context = self.read_execution_context(operation)?.clone();
+        Ok(Self {
+            execution_context: Arc::new(RwLock::new(execution_context)),
+            ..self.clone()
+        })
+    }
+
+    /// Esegue gli hook registrati per `hook` sull'ExecutionContext condiviso.
+    /// Un hook che risponde `HookResult::Block` interrompe l'esecuzione con un errore
+    pub fn run_hooks(&self, hook: ExecutionHook, payload: &HookPayload) -> LoomResult<()> {
+        let mut execution_context = self.write_execution_context("run hooks")?;
+
+        self.hook_registry.execute_hooks(hook, &mut execution_context, payload)
+            .map_err(|reason| LoomError::execution(format!("Execution blocked by {:?} hook: {}", hook, reason)))
+    }
+}
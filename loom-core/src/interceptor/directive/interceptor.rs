@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use crate::ast::DirectiveCall;
 use crate::context::LoomContext;
+use crate::definition::ParameterDefinition;
 use crate::error::LoomResult;
 use crate::interceptor::context::{ExecutionContext, InterceptorContext};
 use crate::interceptor::{InterceptorChain, InterceptorResult};
@@ -11,10 +12,11 @@ use crate::types::LoomValue;
 pub trait DirectiveInterceptor: Send + Sync {
     fn directive_name(&self) -> &str;
 
-    /// Intercetta con accesso al hook registry
+    /// Intercetta con accesso al hook registry e ai parametri ottenuti da `parse_parameters`
     async fn intercept<'a>(
         &'a self,
         context: InterceptorContext<'a>,
+        params: &HashMap<String, LoomValue>,
         next: Box<InterceptorChain<'a>>,
     ) -> InterceptorResult;
 
@@ -28,8 +30,27 @@ pub trait DirectiveInterceptor: Send + Sync {
         call: &DirectiveCall
     ) -> LoomResult<HashMap<String, LoomValue>>;
 
+    /// Parametri accettati. Quelli con `default_value` omessi nella chiamata vengono aggiunti
+    /// con il valore di default alla mappa restituita da `parse_parameters`
+    fn parameters(&self) -> Vec<ParameterDefinition> { Vec::new() }
+
     fn priority(&self) -> i32 { 100 }
 
+    /// Se la direttiva, applicata ad un block, va applicata ad ogni comando figlio
+    /// invece che al block nel suo insieme (es. @timeout per singolo comando)
+    fn cascade(&self) -> bool { false }
+
+    /// Direttive che non possono comparire sullo stesso elemento di questa (nomi senza @).
+    /// Il conflitto vale in entrambe le direzioni, basta dichiararlo su una delle due
+    fn conflicts_with(&self) -> &[&str] { &[] }
+
+    /// Se la direttiva può comparire più volte sullo stesso elemento
+    fn repeatable(&self) -> bool { false }
+
+    /// Direttiva (nome senza @) che deve trovarsi sull'elemento fratello immediatamente precedente (es. @else dopo @if).
+    /// Costruendo la sequenza le due vengono accoppiate: entrambe ricevono in `PAIR_PARAM` lo stesso identificativo
+    fn follows(&self) -> Option<&str> { None }
+
     fn need_chain(&self) -> bool;
 
 }
\ No newline at end of file
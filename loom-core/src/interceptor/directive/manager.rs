@@ -1,13 +1,16 @@
 use std::collections::HashMap;
 use std::sync::Arc;
-use crate::ast::DirectiveCall;
+use crate::ast::{DirectiveCall, Expression};
 use crate::context::LoomContext;
-use crate::error::{LoomError, LoomResult};
+use crate::definition::{apply_parameter_defaults, ArgDefinition, ParameterDefinition};
+use crate::diagnostic::DiagnosticCollector;
+use crate::error::{LoomError, LoomResult, UndefinedKind};
+use crate::interceptor::ActiveInterceptor;
 use crate::interceptor::context::ExecutionContext;
-use crate::interceptor::directive::ActiveDirectiveInterceptor;
+use crate::interceptor::directive::{ActiveDirectiveInterceptor, PAIR_PARAM};
 use crate::interceptor::directive::interceptor::DirectiveInterceptor;
 use crate::interceptor::priority::PriorityRanges;
-use crate::loom_error;
+use crate::types::{LiteralValue, LoomValue};
 
 // Manager per interceptor di direttive
 pub struct DirectiveInterceptorManager {
@@ -26,7 +29,7 @@ impl DirectiveInterceptorManager {
         let priority = interceptor.priority();
 
         // Valida che la priorità sia nel range corretto per direttive
-        self.validate_directive_priority(priority)?;
+        self.validate_directive_priority(&name, priority)?;
 
         self.interceptors.insert(name, interceptor);
         Ok(())
@@ -41,17 +44,37 @@ impl DirectiveInterceptorManager {
     ) -> LoomResult<Vec<ActiveDirectiveInterceptor>> {
         let mut active = Vec::new();
 
+        self.validate_repetition(directives)?;
+        self.validate_conflicts(directives)?;
+
         for directive in directives {
             let interceptor = self.interceptors.get(&directive.name.to_string())
-                .ok_or_else(|| LoomError::execution(format!("Unknown directive: {}", directive.name)))?;
+                .ok_or_else(|| LoomError::undefined_among(
+                    directive.name.to_string(),
+                    UndefinedKind::Directive,
+                    directive.position.clone(),
+                    self.interceptors.keys().map(String::as_str)
+                ))?;
+
+            let parameters = interceptor.parameters();
+            let mut params = interceptor.parse_parameters(loom_context, context, directive)?;
+            apply_parameter_defaults(&parameters, &mut params);
 
-            let params = interceptor.parse_parameters(loom_context, context, directive)?;
+            // I parametri deprecati restano validi: il warning non blocca l'esecuzione
+            let warnings: Vec<LoomError> = Self::bind_arguments(&parameters, directive)
+                .filter(|(parameter, _)| parameter.deprecated)
+                .map(|(parameter, _)| Self::deprecated_warning(parameter, directive))
+                .collect();
+            for warning in warnings.iter() {
+                log::warn!("{}", warning);
+            }
 
             active.push(ActiveDirectiveInterceptor {
                 interceptor: interceptor.clone(),
                 params,
                 name: directive.name.to_string(),
                 priority: interceptor.priority(),
+                warnings,
             });
         }
 
@@ -61,7 +84,218 @@ impl DirectiveInterceptorManager {
         Ok(active)
     }
 
-    fn validate_directive_priority(&self, priority: i32) -> LoomResult<()> {
+    /// Controlli statici sulle direttive di un elemento, senza valutarne i parametri:
+    /// direttive sconosciute, ripetizioni, conflitti e parametri dichiarati da `DirectiveInterceptor::parameters`.
+    /// A differenza di `build_active` raccoglie tutti i problemi invece di fermarsi al primo
+    pub fn check(&self, directives: &[DirectiveCall], diagnostics: &mut DiagnosticCollector) {
+        diagnostics.record(self.validate_repetition(directives));
+        diagnostics.record(self.validate_conflicts(directives));
+
+        for directive in directives {
+            match self.interceptors.get(directive.name.as_ref()) {
+                Some(interceptor) => Self::check_parameters(interceptor.as_ref(), directive, diagnostics),
+                None => diagnostics.push(LoomError::undefined_among(
+                    directive.name.to_string(),
+                    UndefinedKind::Directive,
+                    directive.position.clone(),
+                    self.interceptors.keys().map(String::as_str)
+                )),
+            }
+        }
+    }
+
+    /// Verifica che ogni direttiva con `DirectiveInterceptor::follows` (es. @else) sia su un elemento
+    /// immediatamente preceduto da un fratello con la direttiva indicata (es. @if). Fallisce al primo errore
+    pub fn validate_sequence(&self, siblings: &[&[DirectiveCall]]) -> LoomResult<()> {
+        match self.sequence_errors(siblings).into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Come `validate_sequence`, ma raccoglie tutti gli elementi non accoppiati
+    pub fn check_sequence(&self, siblings: &[&[DirectiveCall]], diagnostics: &mut DiagnosticCollector) {
+        diagnostics.extend(self.sequence_errors(siblings));
+    }
+
+    fn sequence_errors(&self, siblings: &[&[DirectiveCall]]) -> Vec<LoomError> {
+        let mut errors = Vec::new();
+
+        for (index, directives) in siblings.iter().enumerate() {
+            for directive in directives.iter() {
+                let Some(leader) = self.interceptors.get(directive.name.as_ref())
+                    .and_then(|interceptor| interceptor.follows()) else {
+                    continue;
+                };
+                let paired = index > 0 && siblings[index - 1].iter().any(|previous| previous.name.as_ref() == leader);
+                if !paired {
+                    errors.push(LoomError::validation_at(
+                        format!("Directive '@{}' must directly follow an element with '@{}'", directive.name, leader),
+                        directive.position.clone(),
+                    ));
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Accoppia le direttive con `DirectiveInterceptor::follows` della chain di ogni elemento a quella
+    /// dell'elemento precedente: entrambe ricevono in `PAIR_PARAM` lo stesso identificativo.
+    /// Le chain vanno validate prima con `validate_sequence`
+    pub fn pair_siblings(&self, chains: &mut [Vec<ActiveInterceptor>]) {
+        for index in 1..chains.len() {
+            let (previous, current) = chains.split_at_mut(index);
+            let previous = &mut previous[index - 1];
+
+            for follower in current[0].iter_mut() {
+                let ActiveInterceptor::Directive(follower) = follower else {
+                    continue;
+                };
+                let Some(leader) = follower.interceptor.follows() else {
+                    continue;
+                };
+                let leader = previous.iter_mut().find_map(|interceptor| match interceptor {
+                    ActiveInterceptor::Directive(directive) if directive.name == leader => Some(directive),
+                    _ => None,
+                });
+
+                if let Some(leader) = leader {
+                    let pair = LoomValue::Literal(LiteralValue::String(uuid::Uuid::new_v4().to_string()));
+                    leader.params.insert(PAIR_PARAM.to_string(), pair.clone());
+                    follower.params.insert(PAIR_PARAM.to_string(), pair);
+                }
+            }
+        }
+    }
+
+    /// Confronta gli argomenti con i parametri dichiarati: i positional coprono i primi parametri in ordine,
+    /// i parametri deprecati producono un warning. Le direttive che non dichiarano parametri non vengono controllate
+    fn check_parameters(interceptor: &dyn DirectiveInterceptor, directive: &DirectiveCall, diagnostics: &mut DiagnosticCollector) {
+        let parameters = interceptor.parameters();
+        if parameters.is_empty() {
+            return;
+        }
+
+        let invalid = |name: &str, message: String| LoomError::parameter_validation(
+            name,
+            format!("@{} at {}: {}", directive.name, directive.position, message)
+        );
+
+        for arg in directive.args.iter() {
+            if let ArgDefinition::Named { name, .. } = arg
+                && !parameters.iter().any(|param| param.name == *name) {
+                diagnostics.push(invalid(name, "unknown parameter".to_string()));
+            }
+        }
+
+        for (parameter, value) in Self::bind_arguments(&parameters, directive) {
+            if parameter.deprecated {
+                diagnostics.push(Self::deprecated_warning(parameter, directive));
+            }
+            if let Expression::Literal(literal) = value
+                && let Err(error) = parameter.param_type.validate_literal_type(literal) {
+                let message = match error {
+                    LoomError::ValidationError { message, .. } => message,
+                    other => other.to_string(),
+                };
+                diagnostics.push(invalid(&parameter.name, message));
+            }
+        }
+
+        for parameter in parameters.iter() {
+            let provided = Self::bind_arguments(&parameters, directive)
+                .any(|(bound, _)| bound.name == parameter.name);
+            if parameter.required && parameter.default_value.is_none() && !provided {
+                diagnostics.push(invalid(&parameter.name, "missing required parameter".to_string()));
+            }
+        }
+    }
+
+    /// Associa ogni argomento al parametro dichiarato: i positional coprono i primi parametri in ordine,
+    /// i named quello con lo stesso nome. Gli argomenti senza parametro corrispondente vengono saltati
+    fn bind_arguments<'p>(
+        parameters: &'p [ParameterDefinition],
+        directive: &'p DirectiveCall
+    ) -> impl Iterator<Item = (&'p ParameterDefinition, &'p Expression)> {
+        let mut positional = 0;
+        directive.args.iter().filter_map(move |arg| match arg {
+            ArgDefinition::Named { name, value } => parameters.iter()
+                .find(|param| param.name == *name)
+                .map(|parameter| (parameter, value)),
+            ArgDefinition::Positional(value) => {
+                positional += 1;
+                parameters.get(positional - 1).map(|parameter| (parameter, value))
+            }
+        })
+    }
+
+    /// Warning per l'uso di un parametro deprecato, con la descrizione del parametro come suggerimento
+    fn deprecated_warning(parameter: &ParameterDefinition, directive: &DirectiveCall) -> LoomError {
+        let hint = if parameter.description.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", parameter.description)
+        };
+        LoomError::deprecated_parameter(
+            &parameter.name,
+            format!("@{} at {}: parameter is deprecated{}", directive.name, directive.position, hint)
+        )
+    }
+
+    /// Verifica che una direttiva non ripetibile compaia al massimo una volta sullo stesso elemento.
+    /// L'errore riporta la posizione della seconda occorrenza
+    fn validate_repetition(&self, directives: &[DirectiveCall]) -> LoomResult<()> {
+        for (index, directive) in directives.iter().enumerate() {
+            let repeatable = self.interceptors.get(directive.name.as_ref())
+                .is_none_or(|interceptor| interceptor.repeatable());
+            if let Some(first) = directives[..index].iter().find(|it| it.name == directive.name)
+                && !repeatable {
+                return Err(LoomError::validation_at(
+                    format!("Directive '@{}' cannot be repeated (first used at {})", directive.name, first.position),
+                    directive.position.clone(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Verifica che sullo stesso elemento non ci siano due direttive in conflitto tra loro.
+    /// L'errore riporta la posizione della seconda delle due
+    fn validate_conflicts(&self, directives: &[DirectiveCall]) -> LoomResult<()> {
+        let conflicts = |a: &str, b: &str| self.interceptors.get(a)
+            .is_some_and(|interceptor| interceptor.conflicts_with().contains(&b));
+
+        for (index, directive) in directives.iter().enumerate() {
+            for previous in &directives[..index] {
+                if conflicts(&previous.name, &directive.name) || conflicts(&directive.name, &previous.name) {
+                    return Err(LoomError::validation_at(
+                        format!("Directive '@{}' conflicts with '@{}' on the same element", directive.name, previous.name),
+                        directive.position.clone(),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Direttive registrate con la loro priorità
+    pub fn priorities(&self) -> Vec<(String, i32)> {
+        self.interceptors.iter()
+            .map(|(name, interceptor)| (name.clone(), interceptor.priority()))
+            .collect()
+    }
+
+    /// Indica se la direttiva registrata con questo nome va propagata ai comandi del block
+    pub fn is_cascading(&self, name: &str) -> bool {
+        self.interceptors.get(name)
+            .map(|interceptor| interceptor.cascade())
+            .unwrap_or(false)
+    }
+
+    fn validate_directive_priority(&self, name: &str, priority: i32) -> LoomResult<()> {
         let valid_ranges = [
             PriorityRanges::DIRECTIVE_HIGH,
             PriorityRanges::DIRECTIVE_NORMAL,
@@ -71,12 +305,132 @@ impl DirectiveInterceptorManager {
         let is_valid = valid_ranges.iter().any(|range| range.contains(&priority));
 
         if !is_valid {
-            return loom_error!(
-                "Directive interceptor priority {} is not in valid range. Use: DIRECTIVE_HIGH (7000-8000), DIRECTIVE_NORMAL (3000-5000), DIRECTIVE_SUPPORT (500-1000)",
+            return Err(LoomError::validation(format!(
+                "Directive interceptor '{}' priority {} is not in valid range. Use: DIRECTIVE_HIGH (7000-8000), DIRECTIVE_NORMAL (3000-5000), DIRECTIVE_SUPPORT (500-1000)",
+                name,
                 priority
-            );
+            )));
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::definition::ParameterType;
+    use crate::interceptor::context::InterceptorContext;
+    use crate::interceptor::{InterceptorChain, InterceptorResult};
+    use crate::test_support::{directive, execution_context, string};
+    use super::*;
+
+    /// Direttiva che passa al successivo, con i conflitti e i parametri indicati
+    struct Stub {
+        name: &'static str,
+        conflicts: &'static [&'static str],
+        parameters: Vec<ParameterDefinition>,
+    }
+
+    impl Stub {
+        fn new(name: &'static str) -> Self {
+            Self { name, conflicts: &[], parameters: Vec::new() }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl DirectiveInterceptor for Stub {
+        fn directive_name(&self) -> &str {
+            self.name
+        }
+
+        async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, _params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+            next(context).await
+        }
+
+        fn parse_parameters(&self, loom_context: &LoomContext, execution_context: &ExecutionContext, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+            DirectiveInterceptorManager::bind_arguments(&self.parameters, call)
+                .map(|(parameter, value)| Ok((parameter.name.clone(), value.evaluate(loom_context, execution_context, None)?)))
+                .collect()
+        }
+
+        fn parameters(&self) -> Vec<ParameterDefinition> {
+            self.parameters.clone()
+        }
+
+        fn priority(&self) -> i32 { 4000 }
+
+        fn conflicts_with(&self) -> &[&str] { self.conflicts }
+
+        fn need_chain(&self) -> bool {
+            true
+        }
+    }
+
+    fn manager(stubs: Vec<Stub>) -> DirectiveInterceptorManager {
+        let mut manager = DirectiveInterceptorManager::new();
+        for stub in stubs {
+            manager.register(Arc::new(stub)).unwrap();
+        }
+        manager
+    }
+
+    #[test]
+    fn conflicting_directives_on_the_same_element_are_rejected() {
+        let manager = manager(vec![
+            Stub { conflicts: &["sequential"], ..Stub::new("parallel") },
+            Stub::new("sequential"),
+        ]);
+        let loom_context = LoomContext::with_definitions(Vec::new());
+        let context = execution_context(Vec::new());
+
+        // Il conflitto è dichiarato solo su @parallel ma vale in entrambi gli ordini
+        for directives in [
+            vec![directive("parallel", vec![]), directive("sequential", vec![])],
+            vec![directive("sequential", vec![]), directive("parallel", vec![])],
+        ] {
+            let error = manager.build_active(&loom_context, &context, &directives).err().unwrap();
+            assert!(error.to_string().contains("conflicts with"), "{}", error);
+        }
+
+        assert!(manager.build_active(&loom_context, &context, &[directive("parallel", vec![])]).is_ok());
+    }
+
+    fn duration(value: &str) -> LoomValue {
+        LoomValue::Literal(LiteralValue::String(value.to_string()))
+    }
+
+    #[test]
+    fn omitted_parameters_take_their_default_value() {
+        let parameter = |name: &str, default_value: Option<LoomValue>| ParameterDefinition {
+            name: name.to_string(),
+            param_type: ParameterType::Duration,
+            required: false,
+            default_value,
+            description: String::new(),
+            deprecated: false,
+        };
+        let manager = manager(vec![Stub {
+            parameters: vec![parameter("duration", Some(duration("30s"))), parameter("grace", Some(duration("5s"))), parameter("delay", None)],
+            ..Stub::new("timeout")
+        }]);
+        let loom_context = LoomContext::with_definitions(Vec::new());
+        let context = execution_context(Vec::new());
+        let params = |args: Vec<ArgDefinition>| {
+            let mut active = manager.build_active(&loom_context, &context, &[directive("timeout", args)]).unwrap();
+            active.remove(0).params
+        };
+
+        let bare = params(vec![]);
+        assert_eq!(bare.get("duration"), Some(&duration("30s")));
+        assert_eq!(bare.get("grace"), Some(&duration("5s")));
+        assert!(!bare.contains_key("delay"));
+
+        let positional = params(vec![ArgDefinition::Positional(string("1m"))]);
+        assert_eq!(positional.get("duration"), Some(&duration("1m")));
+        assert_eq!(positional.get("grace"), Some(&duration("5s")));
+
+        let named = params(vec![ArgDefinition::Named { name: "grace".to_string(), value: string("10s") }]);
+        assert_eq!(named.get("duration"), Some(&duration("30s")));
+        assert_eq!(named.get("grace"), Some(&duration("10s")));
+    }
+}
@@ -1,10 +1,16 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use crate::error::LoomError;
 use crate::interceptor::directive::interceptor::DirectiveInterceptor;
 use crate::types::LoomValue;
 
 pub mod interceptor;
 pub mod manager;
+pub mod params;
+
+/// Parametro aggiunto dal motore alle due direttive accoppiate da `DirectiveInterceptor::follows`,
+/// con un identificativo univoco per la coppia
+pub const PAIR_PARAM: &str = "__pair";
 
 /// Interceptor di direttiva attivo con i suoi parametri
 #[derive(Clone)]
@@ -13,4 +19,7 @@ pub struct ActiveDirectiveInterceptor {
     pub params: HashMap<String, LoomValue>,
     pub name: String,
     pub priority: i32,
+    /// Warning (severità `ErrorSeverity::Warning`) rilevati costruendo l'interceptor, es. parametri deprecati.
+    /// Vengono emessi come `ExecutionEventKind::DirectiveWarning` quando la direttiva viene eseguita
+    pub warnings: Vec<LoomError>,
 }
\ No newline at end of file
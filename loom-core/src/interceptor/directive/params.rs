@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use crate::error::{LoomError, LoomResult};
+use crate::types::{LiteralValue, LoomValue};
+
+/// Accesso tipizzato ai parametri prodotti da `DirectiveInterceptor::parse_parameters`.
+/// Parametri mancanti o di tipo errato diventano errori `ParameterValidation` con il nome del parametro
+pub struct DirectiveParams<'a> {
+    directive: &'a str,
+    params: &'a HashMap<String, LoomValue>,
+}
+
+impl<'a> DirectiveParams<'a> {
+    pub fn new(directive: &'a str, params: &'a HashMap<String, LoomValue>) -> Self {
+        Self { directive, params }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.params.contains_key(name)
+    }
+
+    /// Valore grezzo del parametro, senza conversioni
+    pub fn value(&self, name: &str) -> LoomResult<&'a LoomValue> {
+        self.params.get(name).ok_or_else(|| LoomError::parameter_validation(
+            name,
+            format!("missing parameter for @{}", self.directive)
+        ))
+    }
+
+    /// Converte il parametro tramite le implementazioni di `TryFrom<&LoomValue>`, senza clonare il valore
+    pub fn get<T>(&self, name: &str) -> LoomResult<T>
+    where
+        T: TryFrom<&'a LoomValue, Error = LoomError>,
+    {
+        T::try_from(self.value(name)?).map_err(|err| {
+            let message = match err {
+                LoomError::ExecutionError { message, .. } => message,
+                other => other.to_string(),
+            };
+            LoomError::parameter_validation(name, format!("@{}: {}", self.directive, message))
+        })
+    }
+
+    /// Come `get`, ma None se il parametro non è presente
+    pub fn get_optional<T>(&self, name: &str) -> LoomResult<Option<T>>
+    where
+        T: TryFrom<&'a LoomValue, Error = LoomError>,
+    {
+        if self.contains(name) {
+            self.get(name).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Come `get`, con `default` se il parametro non è presente
+    pub fn get_or_default<T>(&self, name: &str, default: T) -> LoomResult<T>
+    where
+        T: TryFrom<&'a LoomValue, Error = LoomError>,
+    {
+        Ok(self.get_optional(name)?.unwrap_or(default))
+    }
+
+    pub fn get_string(&self, name: &str) -> LoomResult<String> {
+        self.get(name)
+    }
+
+    pub fn get_number(&self, name: &str) -> LoomResult<i64> {
+        self.get(name)
+    }
+
+    pub fn get_bool(&self, name: &str) -> LoomResult<bool> {
+        self.get(name)
+    }
+
+    pub fn get_array(&self, name: &str) -> LoomResult<Vec<LiteralValue>> {
+        self.get(name)
+    }
+}
@@ -1,27 +1,69 @@
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use crate::ast::Statement;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Instant, SystemTime};
+use crate::ast::{Definition, DirectiveCall, Expression, Statement};
 use crate::context::LoomContext;
-use crate::error::{LoomError, LoomResult};
-use crate::event::channel::ExecutionEventChannel;
+use crate::diagnostic::DiagnosticCollector;
+use crate::error::{InterceptorError, LoomError, LoomResult};
+use crate::event::channel::{ExecutionEvent, ExecutionEventChannel, ExecutionEventKind};
 use crate::InputArg;
 use crate::interceptor::{ActiveInterceptor, InterceptorChain, InterceptorResult};
-use crate::interceptor::context::{ExecutionContext, InterceptorContext};
+use crate::interceptor::cancellation::CancellationTokenExt;
+use crate::interceptor::context::{ExecutionContext, ExecutionOptions, InterceptorContext};
 use crate::interceptor::directive::ActiveDirectiveInterceptor;
 use crate::interceptor::directive::interceptor::DirectiveInterceptor;
 use crate::interceptor::directive::manager::DirectiveInterceptorManager;
-use crate::interceptor::executor::ActiveExecutorInterceptor;
+use crate::interceptor::executor::{ActiveExecutorInterceptor, ExecutorFactory};
+use crate::interceptor::executor::interceptor::ExecutorInterceptor;
+use crate::interceptor::executor::config::ExecutorConfig;
+use crate::interceptor::executor::implementation::assignment::AssignmentExecutorInterceptor;
 use crate::interceptor::executor::implementation::command::CommandExecutorInterceptor;
 use crate::interceptor::executor::implementation::composable::{SequenceChainInterceptor, SequentialExecutorInterceptor};
 use crate::interceptor::executor::implementation::definition::DefinitionExecutorInterceptor;
 use crate::interceptor::executor::implementation::empty_execute_intercept_next;
-use crate::interceptor::global::ActiveGlobalInterceptor;
+use crate::interceptor::executor::implementation::job::JobExecutorInterceptor;
+use crate::interceptor::executor::implementation::pipeline::StageExecutorInterceptor;
+use crate::interceptor::global::{ActiveGlobalInterceptor, GlobalInterceptorCategory};
 use crate::interceptor::global::config::GlobalInterceptorConfig;
 use crate::interceptor::global::interceptor::GlobalInterceptor;
 use crate::interceptor::global::manager::GlobalInterceptorManager;
+use crate::interceptor::hook::{HookHandler, HookPayload};
 use crate::interceptor::hook::registry::HookRegistry;
-use crate::interceptor::scope::{ExecutionActivity, ExecutionScope};
-use crate::types::ParallelizationKind;
+use crate::interceptor::plan::ExecutionPlan;
+use crate::interceptor::schedule::ScheduleTrigger;
+use crate::interceptor::scope::{ExecutionActivity, ExecutionHook, ExecutionScope};
+use crate::types::{DefinitionKind, LiteralValue, ParallelizationKind};
+use lru::LruCache;
+use tokio::sync::{mpsc, Semaphore};
+
+/// Profondità massima di default delle chiamate annidate tra definition
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 64;
+
+/// Numero massimo di default di chain mantenute in cache
+pub const DEFAULT_CHAIN_CACHE_CAPACITY: usize = 256;
+
+/// Statistiche della cache delle chain, per monitoring
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChainCacheStats {
+    /// Chain attualmente in cache
+    pub size: usize,
+    /// Chain mantenute al massimo prima di scartare quelle usate meno di recente
+    pub capacity: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// Volte in cui la cache è stata svuotata, ad esempio registrando interceptor
+    pub clears: u64,
+}
+
+/// Numero di comandi eseguibili contemporaneamente se non configurato: le CPU logiche della macchina
+pub fn default_max_concurrency() -> usize {
+    std::thread::available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(1)
+}
 
 /// Middleware Pattern (Filter Chain Pattern) ottimizzato
 /// Esegue i vari Task/Job/Command, ma, solo dopo aver eseguito
@@ -30,9 +72,26 @@ pub struct InterceptorEngine {
     global_manager: GlobalInterceptorManager,
     directive_manager: DirectiveInterceptorManager,
     hook_registry: HookRegistry,
+    // Configurazione applicata all'executor di ogni comando (es. timeout)
+    command_config: ExecutorConfig,
+    // Executor terminali custom, selezionati in base al prefisso del comando
+    executor_factories: Vec<(String, ExecutorFactory)>,
+    // Profondità massima di chiamate annidate tra definition
+    max_call_depth: usize,
+    // Limite di comandi in esecuzione contemporanea, condiviso da tutti i branch paralleli
+    max_concurrency: usize,
+    command_slots: Arc<Semaphore>,
+    // Ambiente del processo letto alla creazione dell'engine, base delle env di ogni esecuzione
+    env_snapshot: Arc<HashMap<String, String>>,
+    // Nomi di variabili e variabili d'ambiente il cui valore va mascherato in output ed eventi
+    secret_names: Vec<String>,
 
-    // Cache per evitare ricostruzione frequente di chain
-    chain_cache: RwLock<HashMap<String, Vec<ActiveInterceptor>>>,
+    // Cache per evitare ricostruzione frequente di chain, condivise per non copiarle a ogni esecuzione.
+    // Limitata: oltre la capacità vengono scartate le chain usate meno di recente
+    chain_cache: Mutex<LruCache<String, Arc<[ActiveInterceptor]>>>,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    cache_clears: AtomicU64,
 }
 
 impl InterceptorEngine {
@@ -41,40 +100,184 @@ impl InterceptorEngine {
             global_manager: GlobalInterceptorManager::new(),
             directive_manager: DirectiveInterceptorManager::new(),
             hook_registry: HookRegistry::new(),
-            chain_cache: RwLock::new(HashMap::new()),
+            command_config: ExecutorConfig::default(),
+            executor_factories: Vec::new(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            max_concurrency: default_max_concurrency(),
+            command_slots: Arc::new(Semaphore::new(default_max_concurrency())),
+            env_snapshot: Arc::new(std::env::vars().collect()),
+            secret_names: Vec::new(),
+            chain_cache: Mutex::new(LruCache::new(
+                NonZeroUsize::new(DEFAULT_CHAIN_CACHE_CAPACITY).unwrap_or(NonZeroUsize::MIN)
+            )),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
+            cache_clears: AtomicU64::new(0),
+        }
+    }
+
+    /// Limita il numero di comandi in esecuzione contemporanea in tutto l'engine (minimo 1),
+    /// indipendentemente dal `max_thread` dei singoli @parallel
+    pub fn with_max_concurrency(mut self, limit: usize) -> Self {
+        self.max_concurrency = limit.max(1);
+        self.command_slots = Arc::new(Semaphore::new(self.max_concurrency));
+        self
+    }
+
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
+    /// Numero massimo di chain in cache (minimo 1), oltre il quale vengono scartate quelle usate meno di recente
+    pub fn with_cache_capacity(self, capacity: usize) -> Self {
+        if let Ok(mut cache) = self.chain_cache.lock() {
+            cache.resize(NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN));
+        }
+        self
+    }
+
+    /// Rilegge l'ambiente del processo, da usare se è cambiato dopo la creazione dell'engine.
+    /// Le env fornite con `ExecutionOptions` restano comunque prioritarie
+    pub fn refresh_env_snapshot(&mut self) {
+        self.env_snapshot = Arc::new(std::env::vars().collect());
+        // Le direttive valutate in costruzione potrebbero dipendere dalle env precedenti
+        self.clear_cache();
+    }
+
+    /// Registra il nome di una variabile o variabile d'ambiente sensibile (es. `DB_PASSWORD`):
+    /// il suo valore viene sostituito con `****` nei comandi, nell'output, negli errori e negli eventi
+    pub fn register_secret(&mut self, name: impl Into<String>) {
+        let name = name.into();
+        if !self.secret_names.contains(&name) {
+            self.secret_names.push(name);
         }
     }
 
     /// Registra interceptor globale
     pub fn register_global(&mut self, interceptor: Arc<dyn GlobalInterceptor>) -> LoomResult<()> {
         // Invalida cache quando registriamo nuovi interceptor
-        if let Ok(mut cache) = self.chain_cache.write() {
-            cache.clear();
-        }
+        self.clear_cache();
         self.global_manager.register(interceptor)
     }
 
+    /// Rimuove un interceptor globale, invalidando la cache delle chain
+    pub fn unregister_global(&mut self, name: &str) -> LoomResult<()> {
+        self.clear_cache();
+        self.global_manager.unregister(name)
+    }
+
+    /// Interceptor globali registrati: nome, categoria e priorità
+    pub fn list_globals(&self) -> Vec<(String, GlobalInterceptorCategory, i32)> {
+        self.global_manager.list()
+    }
+
     /// Registra interceptor di direttiva
     pub fn register_directive(&mut self, interceptor: Arc<dyn DirectiveInterceptor>) -> LoomResult<()> {
-        if let Ok(mut cache) = self.chain_cache.write() {
-            cache.clear();
-        }
+        self.clear_cache();
         self.directive_manager.register(interceptor)
     }
 
+    /// Registra più interceptor globali, invalidando la cache una sola volta.
+    /// Si ferma al primo interceptor con priorità non valida e ne ritorna l'errore.
+    pub fn register_globals(
+        &mut self,
+        interceptors: impl IntoIterator<Item = Arc<dyn GlobalInterceptor>>
+    ) -> LoomResult<()> {
+        let result = interceptors.into_iter()
+            .try_for_each(|interceptor| self.global_manager.register(interceptor));
+        // Anche in caso di errore, alcuni interceptor potrebbero essere già stati registrati
+        self.clear_cache();
+        result
+    }
+
+    /// Registra più interceptor di direttiva, invalidando la cache una sola volta.
+    /// Si ferma al primo interceptor con priorità non valida e ne ritorna l'errore.
+    pub fn register_directives(
+        &mut self,
+        interceptors: impl IntoIterator<Item = Arc<dyn DirectiveInterceptor>>
+    ) -> LoomResult<()> {
+        let result = interceptors.into_iter()
+            .try_for_each(|interceptor| self.directive_manager.register(interceptor));
+        self.clear_cache();
+        result
+    }
+
     /// Configura interceptor globale
     pub fn configure_global(&mut self, name: &str, config: GlobalInterceptorConfig) -> LoomResult<()> {
-        if let Ok(mut cache) = self.chain_cache.write() {
-            cache.clear();
-        }
+        self.clear_cache();
         self.global_manager.configure(name, config)
     }
 
+    /// Registra un executor terminale custom per i comandi che iniziano con `prefix` (es. `docker:`).
+    ///
+    /// Precedenza nella scelta dell'executor di un comando:
+    /// 1. l'executor registrato con il prefisso più lungo che corrisponde all'inizio del comando;
+    ///    il prefisso viene valutato solo se la prima parte del comando è una stringa letterale
+    /// 2. altrimenti il `CommandExecutorInterceptor`, con la configurazione di `set_command_config`
+    pub fn register_executor(
+        &mut self,
+        prefix: impl Into<String>,
+        factory: impl Fn(Arc<[Expression]>) -> Arc<dyn ExecutorInterceptor> + Send + Sync + 'static,
+    ) -> LoomResult<()> {
+        let prefix = prefix.into();
+        if prefix.is_empty() {
+            return Err(LoomError::validation("Executor prefix cannot be empty"));
+        }
+        if self.executor_factories.iter().any(|(registered, _)| *registered == prefix) {
+            return Err(LoomError::validation(format!("An executor for prefix '{}' is already registered", prefix)));
+        }
+
+        self.executor_factories.push((prefix, Arc::new(factory)));
+        self.clear_cache();
+        Ok(())
+    }
+
+    /// Executor custom per il comando, se il suo prefisso corrisponde ad uno registrato
+    fn resolve_custom_executor(&self, parts: &Arc<[Expression]>) -> Option<ActiveExecutorInterceptor> {
+        let Some(Expression::Literal(LiteralValue::String(first))) = parts.first() else {
+            return None;
+        };
+
+        let (prefix, factory) = self.executor_factories.iter()
+            .filter(|(prefix, _)| first.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())?;
+
+        let mut stripped = parts.to_vec();
+        stripped[0] = Expression::Literal(LiteralValue::String(first[prefix.len()..].to_string()));
+
+        let executor = factory(stripped.into());
+        let config = executor.default_config();
+        Some(ActiveExecutorInterceptor::with_config(executor, config))
+    }
+
+    /// Registra un handler per uno degli hook del ciclo di esecuzione
+    pub fn register_hook(&mut self, handler: Arc<dyn HookHandler>) {
+        self.hook_registry.register_hook(handler);
+    }
+
+    /// Imposta la configurazione dell'executor dei comandi (es. timeout), dopo averla validata
+    pub fn set_command_config(&mut self, config: ExecutorConfig) -> LoomResult<()> {
+        config.validate()?;
+        self.command_config = config;
+        self.clear_cache();
+        Ok(())
+    }
+
+    /// Imposta la profondità massima di chiamate annidate tra definition
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+        self.clear_cache();
+    }
+
+    /// Applica le configurazioni degli interceptor globali lette da un file JSON
+    pub fn apply_global_config_file(&mut self, path: impl AsRef<std::path::Path>) -> LoomResult<()> {
+        self.clear_cache();
+        self.global_manager.apply_config_file(path)
+    }
+
     /// Override temporaneo
     pub fn override_global(&mut self, name: &str, enabled: bool) -> LoomResult<()> {
-        if let Ok(mut cache) = self.chain_cache.write() {
-            cache.clear();
-        }
+        self.clear_cache();
         self.global_manager.set_user_override(name, enabled)
     }
 
@@ -85,47 +288,110 @@ impl InterceptorEngine {
         def_name: &str, // Reference invece di owned String
         input_args: &[InputArg], // Slice invece di Vec owned
     ) -> InterceptorResult {
-        let definition_target = loom_context.find_definition(def_name)
-            .ok_or_else(|| LoomError::execution(format!("Cannot find the definition: '{}'", def_name)))?;
+        self.execute_with_channel(loom_context, def_name, input_args, ExecutionEventChannel::new().0).await
+    }
+
+    /// Come `execute`, ma restituisce anche il receiver degli eventi di esecuzione.
+    /// Il channel vive quanto la future: quando l'esecuzione termina tutti i sender vengono
+    /// rilasciati e il receiver si chiude, quindi va consumato in concorrenza con la future
+    /// (es. con `tokio::join!`)
+    pub fn execute_with_events<'a>(
+        &'a self,
+        loom_context: &'a LoomContext,
+        def_name: &'a str,
+        input_args: &'a [InputArg],
+    ) -> (mpsc::UnboundedReceiver<ExecutionEvent>, impl Future<Output = InterceptorResult> + 'a) {
+        let (channel, receiver) = ExecutionEventChannel::new();
+        (receiver, self.execute_with_channel(loom_context, def_name, input_args, channel))
+    }
+
+    /// Come `execute`, ma gli eventi di esecuzione vengono emessi sul channel indicato
+    pub async fn execute_with_channel(
+        &self,
+        loom_context: &LoomContext,
+        def_name: &str,
+        input_args: &[InputArg],
+        channel: ExecutionEventChannel,
+    ) -> InterceptorResult {
+        self.run(loom_context, def_name, input_args, &ExecutionOptions::default(), channel).await
+    }
 
-        let scope = ExecutionScope::from(definition_target.as_ref());
+    /// Come `execute`, ma l'ExecutionContext iniziale viene modificato secondo le opzioni
+    /// (dry run, working dir, variabili d'ambiente e variabili aggiuntive)
+    pub async fn execute_with_options(
+        &self,
+        loom_context: &LoomContext,
+        def_name: &str,
+        input_args: &[InputArg],
+        options: &ExecutionOptions,
+    ) -> InterceptorResult {
+        self.run(loom_context, def_name, input_args, options, ExecutionEventChannel::new().0).await
+    }
+
+    /// Unisce `execute_with_options` ed `execute_with_channel`, ad esempio per ricevere
+    /// gli eventi di `trace_evaluation`
+    pub async fn execute_with_options_and_channel(
+        &self,
+        loom_context: &LoomContext,
+        def_name: &str,
+        input_args: &[InputArg],
+        options: &ExecutionOptions,
+        channel: ExecutionEventChannel,
+    ) -> InterceptorResult {
+        self.run(loom_context, def_name, input_args, options, channel).await
+    }
+
+    async fn run(
+        &self,
+        loom_context: &LoomContext,
+        def_name: &str,
+        input_args: &[InputArg],
+        options: &ExecutionOptions,
+        channel: ExecutionEventChannel,
+    ) -> InterceptorResult {
+        let definition_target = loom_context.find_definition(def_name)
+            .ok_or_else(|| loom_context.missing_definition(def_name, None))?;
 
         // Costruisci ExecutionContext una volta sola
-        let context = ExecutionContext {
-            variables: loom_context.get_variables(def_name)
-                .cloned()
-                .unwrap_or_default(),
-            env_vars: std::env::vars().collect(),
-            working_dir: std::env::current_dir().ok()
-                .map(|p| p.to_string_lossy().to_string()),
-            dry_run: false,
-            metadata: HashMap::new(),
-            parallelization_kind: ParallelizationKind::Sequential,
-            scope,
-        };
+        let mut context = self.root_context(loom_context, def_name, definition_target.as_ref());
+        options.apply(&mut context)?;
+        context.trace = options.trace_evaluation.then(|| channel.clone());
+
+        for name in &self.secret_names {
+            if let Some(value) = context.env_vars.get(name) {
+                channel.secrets.register(value.as_str());
+            }
+            if let Some(value) = context.variables.get(name.as_str()) {
+                channel.secrets.register(value.to_string());
+            }
+        }
 
         let target = ExecutionActivity::from(definition_target.as_ref());
         let global_interceptors = self.global_manager.get_active(&context);
 
         // Usa cache per chain se disponibile
-        let cache_key = format!("{}_{}", def_name, input_args.len());
+        let cache_key = Self::cache_key(def_name, input_args);
         let interceptor_chain = {
-            if let Ok(cache) = self.chain_cache.read() {
+            // Le direttive sono valutate in costruzione: con variabili o env custom la chain in cache non è riutilizzabile
+            if options.shares_cached_chain() && let Ok(mut cache) = self.chain_cache.lock() {
                 if let Some(cached_chain) = cache.get(&cache_key) {
+                    self.cache_hits.fetch_add(1, Ordering::Relaxed);
                     cached_chain.clone()
                 } else {
-                    drop(cache); // Release read lock
-                    let chain = self.build_target_chain(
+                    drop(cache); // Release lock
+                    self.cache_misses.fetch_add(1, Ordering::Relaxed);
+                    let chain: Arc<[ActiveInterceptor]> = self.build_target_chain(
                         loom_context,
                         &context,
                         &target, // Reference invece di owned
                         &global_interceptors,
-                        Some(input_args)
-                    )?;
+                        Some(input_args),
+                        std::slice::from_ref(&definition_target.signature.name)
+                    )?.into();
 
                     // Cache la chain
-                    if let Ok(mut cache) = self.chain_cache.write() {
-                        cache.insert(cache_key, chain.clone());
+                    if let Ok(mut cache) = self.chain_cache.lock() {
+                        cache.put(cache_key, chain.clone());
                     }
 
                     chain
@@ -137,8 +403,9 @@ impl InterceptorEngine {
                     &context,
                     &target,
                     &global_interceptors,
-                    Some(input_args)
-                )?
+                    Some(input_args),
+                    std::slice::from_ref(&definition_target.signature.name)
+                )?.into()
             }
         };
 
@@ -146,11 +413,131 @@ impl InterceptorEngine {
             loom_context,
             execution_context: Arc::new(RwLock::new(context)),
             hook_registry: &self.hook_registry,
-            channel: ExecutionEventChannel::new().0,
+            channel,
+            command_slots: self.command_slots.clone(),
+            cancellation: options.cancellation.clone(),
         };
 
+        interceptor_context.run_hooks(
+            ExecutionHook::PreExecution,
+            &HookPayload::Custom {
+                data: HashMap::from([("definition".to_string(), serde_json::Value::from(def_name))]),
+            },
+        )?;
+
         // Esegui la chain unificata
-        Self::execute_chain(interceptor_context, &interceptor_chain).await
+        let result = Self::execute_chain(interceptor_context.clone(), &interceptor_chain).await;
+
+        match result {
+            Ok(result) => {
+                let payload = HookPayload::Result { result: result.clone() };
+                interceptor_context.run_hooks(ExecutionHook::PostExecution, &payload)?;
+                interceptor_context.run_hooks(ExecutionHook::OnSuccess, &payload)?;
+                Ok(result)
+            }
+            Err(error) => {
+                if error.is_cancelled() {
+                    let _ = interceptor_context.channel.emit_with_context(
+                        ExecutionEventKind::ExecutionCancelled {
+                            definition_name: def_name.to_string(),
+                            reason: error.to_string(),
+                        },
+                        HashMap::new(),
+                    );
+                }
+                // L'errore originale ha la precedenza su un eventuale Block degli hook
+                let payload = HookPayload::Error { error: error.to_string() };
+                let _ = interceptor_context.run_hooks(ExecutionHook::PostExecution, &payload);
+                // Per i comandi falliti gli OnError hook sono già stati eseguiti dal command executor
+                if !matches!(error, LoomError::InterceptorError { error: InterceptorError::CommandExecution { .. }, .. }) {
+                    let _ = interceptor_context.run_hooks(ExecutionHook::OnError, &payload);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Chiave della chain in cache: la chain contiene gli argomenti della chiamata (legati dal
+    /// `DefinitionExecutorInterceptor`), quindi chiamate con valori diversi non possono condividerla
+    fn cache_key(def_name: &str, input_args: &[InputArg]) -> String {
+        format!("{}{:?}", def_name, input_args)
+    }
+
+    /// Prossima esecuzione di uno schedule secondo la sua direttiva `@cron` o `@at`.
+    /// None se lo schedule non ha un trigger o non scatterà più
+    pub fn next_run(&self, loom_context: &LoomContext, def_name: &str) -> LoomResult<Option<SystemTime>> {
+        self.next_run_after(loom_context, def_name, SystemTime::now())
+    }
+
+    /// Come `next_run`, ma a partire da un istante arbitrario
+    pub fn next_run_after(
+        &self,
+        loom_context: &LoomContext,
+        def_name: &str,
+        after: SystemTime,
+    ) -> LoomResult<Option<SystemTime>> {
+        let definition = Self::find_schedule(loom_context, def_name)?;
+        let context = self.root_context(loom_context, def_name, definition.as_ref());
+
+        Ok(
+            ScheduleTrigger::from_directives(loom_context, &context, &definition.directives)?
+                .and_then(|trigger| trigger.next_after(after))
+        )
+    }
+
+    /// Esegue subito il corpo di uno schedule, senza attendere il suo trigger
+    pub async fn trigger_schedule(&self, loom_context: &LoomContext, def_name: &str) -> InterceptorResult {
+        Self::find_schedule(loom_context, def_name)?;
+        self.execute(loom_context, def_name, &[]).await
+    }
+
+    fn find_schedule(loom_context: &LoomContext, def_name: &str) -> LoomResult<Arc<Definition>> {
+        loom_context.find_definition(def_name)
+            .filter(|definition| definition.kind == DefinitionKind::Schedule)
+            .ok_or_else(|| LoomError::definition_resolution(def_name, "Cannot find Schedule"))
+    }
+
+    /// Costruisce il piano di esecuzione di una definition, senza eseguirla
+    pub fn plan(
+        &self,
+        loom_context: &LoomContext,
+        def_name: &str,
+        input_args: &[InputArg],
+    ) -> LoomResult<ExecutionPlan> {
+        let definition_target = loom_context.find_definition(def_name)
+            .ok_or_else(|| loom_context.missing_definition(def_name, None))?;
+
+        let context = self.root_context(loom_context, def_name, definition_target.as_ref());
+        let target = ExecutionActivity::from(definition_target.as_ref());
+        let global_interceptors = self.global_manager.get_active(&context);
+
+        let chain = self.build_target_chain(
+            loom_context,
+            &context,
+            &target,
+            &global_interceptors,
+            Some(input_args),
+            std::slice::from_ref(&definition_target.signature.name)
+        )?;
+
+        Ok(ExecutionPlan::from_chain(loom_context, &context, def_name, &chain))
+    }
+
+    /// ExecutionContext iniziale per l'esecuzione di una definition
+    fn root_context(&self, loom_context: &LoomContext, def_name: &str, definition: &Definition) -> ExecutionContext {
+        ExecutionContext {
+            variables: loom_context.get_variables(def_name)
+                .cloned()
+                .unwrap_or_default(),
+            env_vars: self.env_snapshot.as_ref().clone(),
+            working_dir: std::env::current_dir().ok()
+                .map(|p| p.to_string_lossy().to_string()),
+            dry_run: false,
+            metadata: HashMap::new(),
+            parallelization_kind: ParallelizationKind::Sequential,
+            scope: ExecutionScope::from(definition),
+            trace: None,
+        }
     }
 
     /// Build target chain ottimizzato - usa reference per evitare clone
@@ -161,36 +548,69 @@ impl InterceptorEngine {
         execution_target: &ExecutionActivity, // Reference
         global_interceptors: &[ActiveGlobalInterceptor], // Slice
         args: Option<&[InputArg]>, // Optional slice
+        call_stack: &[Arc<str>], // Definition in corso di costruzione, dalla radice
     ) -> LoomResult<Vec<ActiveInterceptor>> {
         match execution_target {
-            ExecutionActivity::Command(command) => {
+            ExecutionActivity::Command(command, inherited) => {
                 match command.as_ref() {
-                    Statement::Command { parts, directives } => {
+                    Statement::Command { parts, directives, position } => {
+                        let directives = self.with_cascading_directives(inherited, directives);
+                        // Le parti costanti vengono piegate una volta sola, la chain costruita finisce in cache
+                        let parts: Arc<[Expression]> = parts.iter().map(Expression::fold_constants).collect();
                         Ok(Self::plug_and_sort_chain(
                             global_interceptors,
-                            &self.directive_manager.build_active(loom_context, context, directives)?,
+                            &self.directive_manager.build_active(loom_context, context, &directives)?,
                             ActiveInterceptor::Executor(
-                                ActiveExecutorInterceptor::new(
-                                    Arc::new(CommandExecutorInterceptor(parts.clone()))
+                                self.resolve_custom_executor(&parts).unwrap_or_else(||
+                                    ActiveExecutorInterceptor::with_config(
+                                        Arc::new(CommandExecutorInterceptor(parts.clone(), position.clone())),
+                                        self.command_config.clone()
+                                    )
                                 )
                             )
                         ))
                     }
-                    Statement::Call { name, args, .. } => {
+                    Statement::Call { name, args, directives, position } => {
                         let definition_to_call = loom_context.find_definition(name.as_ref())
-                            .ok_or_else(|| LoomError::execution(format!("Definition non esistente: '{}'", name)))?;
+                            .ok_or_else(|| loom_context.missing_definition(name, Some(position.clone())))?;
+                        let call_stack = self.push_call(call_stack, &definition_to_call.signature.name)?;
 
                         let activity = ExecutionActivity::from(definition_to_call.as_ref());
                         let converted_args = definition_to_call.signature
-                            .positional_arg_from_expression(args.as_ref())?;
+                            .bind_call_args(args.as_ref())?;
 
-                        self.build_target_chain(
+                        let callee_chain = self.build_target_chain(
                             loom_context,
                             context,
                             &activity,
                             global_interceptors,
-                            Some(&converted_args)
-                        )
+                            Some(&converted_args),
+                            &call_stack
+                        )?;
+
+                        // Le direttive della chiamata avvolgono solo questa chiamata, attorno a quelle della definition
+                        let directives = self.with_cascading_directives(inherited, directives);
+                        if directives.is_empty() {
+                            return Ok(callee_chain);
+                        }
+
+                        Ok(Self::plug_and_sort_chain(
+                            &[],
+                            &self.directive_manager.build_active(loom_context, context, &directives)?,
+                            ActiveInterceptor::Executor(
+                                ActiveExecutorInterceptor::new(Arc::new(SequenceChainInterceptor(callee_chain)))
+                            )
+                        ))
+                    }
+                    // Un assegnamento non ha direttive né interceptor globali: modifica solo le variabili
+                    Statement::Assignment { target, value, position } => {
+                        Ok(vec![ActiveInterceptor::Executor(ActiveExecutorInterceptor::new(Arc::new(
+                            AssignmentExecutorInterceptor {
+                                target: target.clone(),
+                                value: Arc::new(value.fold_constants()),
+                                position: position.clone(),
+                            }
+                        )))])
                     }
                 }
             }
@@ -201,12 +621,19 @@ impl InterceptorEngine {
                     context,
                     execution_target,
                     global_interceptors,
-                    "block-sequence"
+                    "block-sequence",
+                    call_stack
                 )?;
 
+                // Le direttive a cascata sono già state applicate ai singoli comandi
+                let directives: Vec<DirectiveCall> = block.directives.iter()
+                    .filter(|directive| !self.directive_manager.is_cascading(&directive.name))
+                    .cloned()
+                    .collect();
+
                 Ok(Self::plug_and_sort_chain(
                     global_interceptors,
-                    &self.directive_manager.build_active(loom_context, context, &block.directives)?,
+                    &self.directive_manager.build_active(loom_context, context, &directives)?,
                     ActiveInterceptor::Executor(
                         ActiveExecutorInterceptor::new(
                             Arc::new(SequentialExecutorInterceptor(target, "Block".to_string()))
@@ -215,10 +642,105 @@ impl InterceptorEngine {
                 ))
             }
 
-            ExecutionActivity::Stage(_) => Ok(Vec::new()),
-            ExecutionActivity::Pipeline { .. } => Ok(Vec::new()),
-            ExecutionActivity::Job { .. } => Ok(Vec::new()),
-            ExecutionActivity::Schedule { .. } => Ok(Vec::new()),
+            ExecutionActivity::Stage { pipeline, name, stage } => {
+                let target = self.build_target_efficiently(
+                    loom_context,
+                    context,
+                    execution_target,
+                    global_interceptors,
+                    "stage-sequence",
+                    call_stack
+                )?;
+
+                Ok(Self::plug_and_sort_chain(
+                    global_interceptors,
+                    &self.directive_manager.build_active(loom_context, context, &stage.directives)?,
+                    ActiveInterceptor::Executor(
+                        ActiveExecutorInterceptor::new(Arc::new(
+                            StageExecutorInterceptor {
+                                pipeline: pipeline.clone(),
+                                name: name.clone(),
+                                children: target,
+                            }
+                        ))
+                    )
+                ))
+            }
+
+            ExecutionActivity::Pipeline { name, directives, .. } => {
+                // Ogni stage diventa una SequenceChainInterceptor, eseguite in ordine
+                let target = self.build_target_efficiently(
+                    loom_context,
+                    context,
+                    execution_target,
+                    global_interceptors,
+                    "pipeline-sequence",
+                    call_stack
+                )?;
+
+                Ok(Self::plug_and_sort_chain(
+                    global_interceptors,
+                    &self.directive_manager.build_active(loom_context, context, directives)?,
+                    ActiveInterceptor::Executor(
+                        ActiveExecutorInterceptor::new(
+                            Arc::new(SequentialExecutorInterceptor(target, name.to_string()))
+                        )
+                    )
+                ))
+            }
+
+            ExecutionActivity::Job { name, directives, stage, args: stage_args, .. } => {
+                // Ogni blocco diventa una SequenceChainInterceptor, eseguite secondo il ParallelizationKind
+                let target = self.build_target_efficiently(
+                    loom_context,
+                    context,
+                    execution_target,
+                    global_interceptors,
+                    "job-sequence",
+                    call_stack
+                )?;
+
+                Ok(Self::plug_and_sort_chain(
+                    global_interceptors,
+                    &self.directive_manager.build_active(loom_context, context, directives)?,
+                    ActiveInterceptor::Executor(
+                        ActiveExecutorInterceptor::new(Arc::new(
+                            JobExecutorInterceptor {
+                                name: name.clone(),
+                                stage: stage.clone(),
+                                children: target,
+                                args: args.map(|a| a.to_vec()).unwrap_or_else(|| stage_args.to_vec()),
+                            }
+                        ))
+                    )
+                ))
+            }
+            ExecutionActivity::Schedule { name, directives, .. } => {
+                let target = self.build_target_efficiently(
+                    loom_context,
+                    context,
+                    execution_target,
+                    global_interceptors,
+                    "schedule-sequence",
+                    call_stack
+                )?;
+
+                // @cron/@at descrivono solo quando eseguire lo schedule, non sono interceptor
+                let directives: Vec<DirectiveCall> = directives.iter()
+                    .filter(|directive| !ScheduleTrigger::is_trigger_directive(&directive.name))
+                    .cloned()
+                    .collect();
+
+                Ok(Self::plug_and_sort_chain(
+                    global_interceptors,
+                    &self.directive_manager.build_active(loom_context, context, &directives)?,
+                    ActiveInterceptor::Executor(
+                        ActiveExecutorInterceptor::new(
+                            Arc::new(SequentialExecutorInterceptor(target, name.to_string()))
+                        )
+                    )
+                ))
+            }
 
             ExecutionActivity::Definition { directives, name, .. } => {
                 let target = self.build_target_efficiently(
@@ -226,7 +748,8 @@ impl InterceptorEngine {
                     context,
                     execution_target,
                     global_interceptors,
-                    "definition-sequence"
+                    "definition-sequence",
+                    call_stack
                 )?;
 
                 Ok(Self::plug_and_sort_chain(
@@ -246,6 +769,26 @@ impl InterceptorEngine {
         }
     }
 
+    /// Aggiunge una definition allo stack delle chiamate, fallendo se è già presente
+    /// (ricorsione diretta o indiretta) o se si supera la profondità massima
+    fn push_call(&self, call_stack: &[Arc<str>], name: &Arc<str>) -> LoomResult<Vec<Arc<str>>> {
+        let mut stack = call_stack.to_vec();
+        stack.push(name.clone());
+
+        if call_stack.contains(name) {
+            return Err(LoomError::execution(format!(
+                "recursive definition cycle: {}", stack.join(" -> ")
+            )));
+        }
+        if call_stack.len() > self.max_call_depth {
+            return Err(LoomError::execution(format!(
+                "maximum call depth of {} exceeded: {}", self.max_call_depth, stack.join(" -> ")
+            )));
+        }
+
+        Ok(stack)
+    }
+
     /// Build target in modo più efficiente - evita clone multipli
     fn build_target_efficiently(
         &self,
@@ -254,25 +797,67 @@ impl InterceptorEngine {
         execution_target: &ExecutionActivity,
         global_interceptors: &[ActiveGlobalInterceptor],
         name: &str,
+        call_stack: &[Arc<str>],
     ) -> LoomResult<Vec<ActiveInterceptor>> {
         let children = execution_target.build_child(loom_context, context)?;
-        let mut result = Vec::with_capacity(children.len());
+        let siblings: Vec<&[DirectiveCall]> = children.iter().map(Self::own_directives).collect();
+        self.directive_manager.validate_sequence(&siblings)?;
 
-        for child in children {
-            let chain = self.build_target_chain(
+        let mut chains = Vec::with_capacity(children.len());
+        for child in children.iter() {
+            chains.push(self.build_target_chain(
                 loom_context,
                 context,
-                &child,
+                child,
                 global_interceptors,
-                None
-            )?;
+                None,
+                call_stack
+            )?);
+        }
+        // Le direttive accoppiate (es. @if/@else) condividono un identificativo legato a questa sequenza
+        self.directive_manager.pair_siblings(&mut chains);
+
+        Ok(
+            chains.into_iter()
+                .map(|chain| ActiveInterceptor::Executor(
+                    ActiveExecutorInterceptor::new(Arc::new(SequenceChainInterceptor(chain)))
+                ))
+                .collect()
+        )
+    }
 
-            result.push(ActiveInterceptor::Executor(
-                ActiveExecutorInterceptor::new(Arc::new(SequenceChainInterceptor(chain)))
-            ));
+    /// Direttive dichiarate sull'elemento stesso, senza quelle ereditate dal block
+    fn own_directives(activity: &ExecutionActivity) -> &[DirectiveCall] {
+        match activity {
+            ExecutionActivity::Command(statement, _) => Self::statement_directives(statement),
+            other => other.directives().unwrap_or_default(),
         }
+    }
 
-        Ok(result)
+    fn statement_directives(statement: &Statement) -> &[DirectiveCall] {
+        match statement {
+            Statement::Command { directives, .. } | Statement::Call { directives, .. } => directives,
+            Statement::Assignment { .. } => &[],
+        }
+    }
+
+    /// Unisce le direttive di un comando con quelle a cascata ereditate dal block.
+    /// Una direttiva dichiarata sul comando ha la precedenza su quella ereditata con lo stesso nome
+    fn with_cascading_directives(
+        &self,
+        inherited: &[DirectiveCall],
+        own: &[DirectiveCall],
+    ) -> Vec<DirectiveCall> {
+        let mut directives = own.to_vec();
+
+        for directive in inherited {
+            if self.directive_manager.is_cascading(&directive.name)
+                && !own.iter().any(|it| it.name == directive.name) {
+                directives.push(directive.clone());
+            }
+        }
+
+        directives
     }
 
     /// Combina interceptor in chain unificata - ottimizzato per evitare allocazioni
@@ -303,7 +888,9 @@ impl InterceptorEngine {
         unified
     }
 
-    /// Esegue la chain unificata - ottimizzata
+    /// Esegue la chain unificata - ottimizzata.
+    /// Il risultato restituito è quello dell'ultimo interceptor (l'executor terminale),
+    /// propagato a ritroso dagli interceptor che chiamano `next`
     pub async fn execute_chain<'a>(
         context: InterceptorContext<'a>,
         chain: &'a [ActiveInterceptor],
@@ -312,30 +899,7 @@ impl InterceptorEngine {
             return Err(LoomError::execution("Empty interceptor chain"));
         }
 
-        let mut index = 0;
-
-        // Cerca il primo interceptor che ha bisogno di chain
-        while index < chain.len() {
-            if chain[index].need_chain() {
-                return Self::execute_chain_recursive(context, chain, index).await;
-            } else {
-                // Esegui interceptor senza chain
-                let result = Self::launch_interceptor(
-                    context.clone(),
-                    chain,
-                    index,
-                    empty_execute_intercept_next()
-                ).await?;
-
-                // Se è l'ultimo o abbiamo un risultato conclusivo, return
-                if index == chain.len() - 1 {
-                    return Ok(result);
-                }
-            }
-            index += 1;
-        }
-
-        Err(LoomError::execution("No interceptor executed"))
+        Self::execute_chain_recursive(context, chain, 0).await
     }
 
     /// Esecuzione ricorsiva della chain - ottimizzata
@@ -347,6 +911,20 @@ impl InterceptorEngine {
         if index >= chain.len() {
             return Err(LoomError::execution("Chain index out of bounds"));
         }
+        context.cancellation.check(&format!("interceptor '{}'", chain[index].name()))?;
+
+        // Un interceptor intermedio che non usa la chain viene eseguito solo per i suoi effetti:
+        // il suo risultato non è conclusivo e l'esecuzione prosegue con il successivo
+        if !chain[index].need_chain() && index < chain.len() - 1 {
+            Self::launch_interceptor(
+                context.clone(),
+                chain,
+                index,
+                empty_execute_intercept_next()
+            ).await?;
+
+            return Box::pin(Self::execute_chain_recursive(context, chain, index + 1)).await;
+        }
 
         Self::launch_interceptor(
             context,
@@ -363,17 +941,58 @@ impl InterceptorEngine {
         index: usize,
         next: Box<InterceptorChain<'a>>
     ) -> InterceptorResult {
-        match &chain[index] {
+        let interceptor = &chain[index];
+        let channel = context.channel.clone();
+        let start_time = Instant::now();
+
+        // Gli eventi sono best-effort: un channel chiuso non deve bloccare l'esecuzione
+        let _ = channel.emit_with_context(
+            ExecutionEventKind::InterceptorTriggered {
+                interceptor_name: interceptor.name().to_string(),
+                interceptor_type: interceptor.interceptor_type().to_string(),
+                priority: interceptor.priority(),
+            },
+            HashMap::new(),
+        );
+
+        let result = match interceptor {
             ActiveInterceptor::Global(global) => {
                 global.interceptor.intercept(context, &global.config, next).await
             }
             ActiveInterceptor::Directive(directive) => {
-                directive.interceptor.intercept(context, next).await
+                for warning in directive.warnings.iter() {
+                    let message = match warning {
+                        LoomError::InterceptorError {
+                            error: InterceptorError::ParameterValidation { name, message, .. }, ..
+                        } => format!("'{}' - {}", name, message),
+                        other => other.to_string(),
+                    };
+                    let _ = channel.emit_with_context(
+                        ExecutionEventKind::DirectiveWarning {
+                            directive_name: directive.name.clone(),
+                            message,
+                        },
+                        HashMap::new(),
+                    );
+                }
+                directive.interceptor.intercept(context, &directive.params, next).await
             }
             ActiveInterceptor::Executor(executor) => {
                 executor.interceptor.intercept(context, &executor.config, next).await
             }
-        }
+        };
+
+        let _ = channel.emit_with_context(
+            ExecutionEventKind::InterceptorCompleted {
+                interceptor_name: interceptor.name().to_string(),
+                duration_ms: start_time.elapsed().as_millis() as u64,
+                success: result.is_ok(),
+            },
+            HashMap::new(),
+        );
+
+        // Lo stack si costruisce risalendo: l'interceptor più esterno finisce in testa
+        result.map_err(|error| error.with_interceptor_frame(interceptor.name()))
     }
 
     /// Create next chain - ottimizzato con bound checking
@@ -385,56 +1004,156 @@ impl InterceptorEngine {
             if next_index < chain.len() {
                 Box::pin(Self::execute_chain_recursive(context, chain, next_index))
             } else {
+                // Solo l'ultimo interceptor può arrivare qui, ed è l'executor terminale che non ha un next
+                let terminal = chain.last().map(|it| it.name().to_string()).unwrap_or_default();
                 Box::pin(async move {
-                    Err(LoomError::execution("End of interceptor chain reached"))
+                    Err(LoomError::execution(format!(
+                        "The terminal interceptor '{}' cannot call next: it must produce the result itself",
+                        terminal
+                    )))
                 })
             }
         })
     }
 
-    /// Diagnostica: lista interceptor attivi per un target - ottimizzata
-    pub fn list_active_interceptors(&self, target: ExecutionScope) -> Vec<(String, String, i32)> {
-        let context = ExecutionContext {
-            variables: HashMap::new(),
-            env_vars: std::env::vars().collect(),
-            working_dir: None,
-            dry_run: false,
-            metadata: HashMap::new(),
-            parallelization_kind: ParallelizationKind::Sequential,
-            scope: target,
-        };
+    /// Diagnostica: lista degli interceptor della chain di una definition (globali, direttive ed executor)
+    /// come (nome, tipo, priorità). Globali e direttive sono ordinati per priorità decrescente,
+    /// l'executor terminale è sempre l'ultimo perché eseguito dopo tutti gli altri
+    pub fn list_active_interceptors(
+        &self,
+        loom_context: &LoomContext,
+        def_name: &str,
+    ) -> LoomResult<Vec<(String, String, i32)>> {
+        let definition = loom_context.find_definition(def_name)
+            .ok_or_else(|| loom_context.missing_definition(def_name, None))?;
+        let context = self.root_context(loom_context, def_name, definition.as_ref());
 
-        let global = self.global_manager.get_active(&context);
-        let mut result = Vec::with_capacity(global.len());
+        let target = ExecutionActivity::from(definition.as_ref());
+        let global_interceptors = self.global_manager.get_active(&context);
 
-        for interceptor in &global {
-            result.push((
-                interceptor.name.clone(),
-                "global".to_string(),
-                interceptor.config.priority,
-            ));
-        }
+        let chain = self.build_target_chain(
+            loom_context,
+            &context,
+            &target,
+            &global_interceptors,
+            Some(&[]),
+            std::slice::from_ref(&definition.signature.name)
+        )?;
 
-        result.sort_unstable_by(|a, b| b.2.cmp(&a.2));
-        result
+        Ok(
+            chain.iter()
+                .map(|interceptor| (
+                    interceptor.name().to_string(),
+                    interceptor.interceptor_type().to_string(),
+                    interceptor.priority(),
+                ))
+                .collect()
+        )
     }
 
-    /// Valida che non ci siano conflitti di priorità
+    /// Valida che non ci siano interceptor dello stesso tipo con la stessa priorità:
+    /// l'ordinamento della chain non è stabile, quindi il loro ordine di esecuzione non sarebbe deterministico
     pub fn validate_priority_conflicts(&self) -> Result<(), Vec<String>> {
-        // Implementazione semplificata
-        Ok(())
+        let mut conflicts = Vec::new();
+
+        Self::collect_priority_conflicts(
+            "Global",
+            self.global_manager.list().into_iter().map(|(name, _, priority)| (name, priority)),
+            &mut conflicts,
+        );
+        Self::collect_priority_conflicts(
+            "Directive",
+            self.directive_manager.priorities(),
+            &mut conflicts,
+        );
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    /// Esegue tutti i controlli statici senza fermarsi al primo errore (es. per `loom check`):
+    /// riferimenti a definition, grafo degli import, conflitti di priorità e direttive di ogni elemento.
+    /// Errori e warning (es. parametri deprecati) si distinguono con `LoomError::severity`
+    pub fn validate_all(&self, loom_context: &LoomContext) -> Vec<LoomError> {
+        let mut diagnostics = DiagnosticCollector::new();
+
+        if let Err(errors) = loom_context.validate_references() {
+            diagnostics.extend(errors);
+        }
+        diagnostics.record(loom_context.validate_import_graph());
+        if let Err(conflicts) = self.validate_priority_conflicts() {
+            diagnostics.extend(conflicts.into_iter().map(LoomError::validation));
+        }
+
+        // Ordine deterministico dei diagnostic: per file e posizione della definition
+        let mut definitions: Vec<&Definition> = loom_context.modules.values()
+            .flat_map(|module| module.definitions.values().map(AsRef::as_ref))
+            .collect();
+        definitions.sort_by(|a, b| a.position.file.cmp(&b.position.file)
+            .then(a.position.line.cmp(&b.position.line))
+            .then(a.position.column.cmp(&b.position.column)));
+
+        for definition in definitions {
+            self.directive_manager.check(&definition.directives, &mut diagnostics);
+            // Una definition non ha fratelli: un @else sulla definition non ha un @if a cui agganciarsi
+            self.directive_manager.check_sequence(&[&definition.directives], &mut diagnostics);
+
+            let blocks: Vec<&[DirectiveCall]> = definition.body.iter().map(|block| block.directives.as_ref()).collect();
+            self.directive_manager.check_sequence(&blocks, &mut diagnostics);
+
+            for block in definition.body.iter() {
+                self.directive_manager.check(&block.directives, &mut diagnostics);
+                let statements: Vec<&[DirectiveCall]> = block.statements.iter().map(Self::statement_directives).collect();
+                for directives in statements.iter() {
+                    self.directive_manager.check(directives, &mut diagnostics);
+                }
+                self.directive_manager.check_sequence(&statements, &mut diagnostics);
+            }
+        }
+
+        diagnostics.into_diagnostics()
     }
 
-    /// Clear cache - utile per testing
+    fn collect_priority_conflicts(
+        kind: &str,
+        interceptors: impl IntoIterator<Item = (String, i32)>,
+        conflicts: &mut Vec<String>,
+    ) {
+        let mut by_priority: BTreeMap<i32, Vec<String>> = BTreeMap::new();
+        for (name, priority) in interceptors {
+            by_priority.entry(priority).or_default().push(name);
+        }
+
+        for (priority, mut names) in by_priority.into_iter().rev() {
+            if names.len() > 1 {
+                names.sort();
+                conflicts.push(format!(
+                    "{} interceptors [{}] share priority {}", kind, names.join(", "), priority
+                ));
+            }
+        }
+    }
+
+    /// Clear cache - utile per testing. Hit e miss non vengono azzerati
     pub fn clear_cache(&self) {
-        if let Ok(mut cache) = self.chain_cache.write() {
+        if let Ok(mut cache) = self.chain_cache.lock() {
             cache.clear();
         }
+        self.cache_clears.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Cache statistics per monitoring
-    pub fn cache_stats(&self) -> Option<usize> {
-        self.chain_cache.read().ok().map(|cache| cache.len())
+    pub fn cache_stats(&self) -> Option<ChainCacheStats> {
+        self.chain_cache.lock().ok().map(|cache| ChainCacheStats {
+            size: cache.len(),
+            capacity: cache.cap().get(),
+            hits: self.cache_hits.load(Ordering::Relaxed),
+            misses: self.cache_misses.load(Ordering::Relaxed),
+            clears: self.cache_clears.load(Ordering::Relaxed),
+        })
     }
 }
 
@@ -443,4 +1162,421 @@ impl Default for InterceptorEngine {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::ast::Block;
+    use crate::definition::{ArgDefinition, ParameterDefinition, ParameterType};
+    use crate::interceptor::directive::PAIR_PARAM;
+    use crate::interceptor::result::ExecutionResult;
+    use crate::test_support::{call, directive, echo, recipe, string};
+    use crate::types::{LoomValue, Position};
+    use super::*;
+
+    /// `@if(condition)` ridotto all'essenziale: salta il target se la condizione è falsa
+    struct SkipUnless;
+
+    #[async_trait::async_trait]
+    impl DirectiveInterceptor for SkipUnless {
+        fn directive_name(&self) -> &str {
+            "if"
+        }
+
+        async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+            match params.get("condition") {
+                Some(LoomValue::Literal(LiteralValue::Boolean(true))) => next(context).await,
+                _ => Ok(ExecutionResult::skipped("condition is false")),
+            }
+        }
+
+        fn parse_parameters(&self, loom_context: &LoomContext, execution_context: &ExecutionContext, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+            let [ArgDefinition::Positional(condition)] = call.args.as_ref() else {
+                return Err(LoomError::parameter_validation("if", "expected a condition"));
+            };
+            Ok(HashMap::from([("condition".to_string(), condition.evaluate(loom_context, execution_context, None)?)]))
+        }
+
+        fn priority(&self) -> i32 { 7800 }
+
+        fn need_chain(&self) -> bool {
+            true
+        }
+    }
+
+    /// `@else` ridotto all'essenziale: ritorna l'identificativo con cui il motore lo ha accoppiato al `@if`
+    struct PairedElse;
+
+    #[async_trait::async_trait]
+    impl DirectiveInterceptor for PairedElse {
+        fn directive_name(&self) -> &str {
+            "else"
+        }
+
+        async fn intercept<'a>(&'a self, _context: InterceptorContext<'a>, params: &HashMap<String, LoomValue>, _next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+            Ok(ExecutionResult::new(params.get(PAIR_PARAM).map(ToString::to_string), Some(0)))
+        }
+
+        fn parse_parameters(&self, _loom_context: &LoomContext, _execution_context: &ExecutionContext, _call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+            Ok(HashMap::new())
+        }
+
+        fn priority(&self) -> i32 { 7790 }
+
+        fn follows(&self) -> Option<&str> { Some("if") }
+
+        fn need_chain(&self) -> bool {
+            true
+        }
+    }
+
+    /// `@timeout` che conta quante volte avvolge un target, a cascata se indicato
+    #[derive(Default)]
+    struct CountingDirective {
+        cascade: bool,
+        count: AtomicU64,
+    }
+
+    #[async_trait::async_trait]
+    impl DirectiveInterceptor for CountingDirective {
+        fn directive_name(&self) -> &str {
+            "timeout"
+        }
+
+        async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, _params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            next(context).await
+        }
+
+        fn parse_parameters(&self, _loom_context: &LoomContext, _execution_context: &ExecutionContext, _call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+            Ok(HashMap::new())
+        }
+
+        fn priority(&self) -> i32 { 4000 }
+
+        fn cascade(&self) -> bool { self.cascade }
+
+        fn need_chain(&self) -> bool {
+            true
+        }
+    }
+
+    /// `@notify(channel: ...)` con il solo parametro deprecato `channel`
+    struct Notify;
+
+    #[async_trait::async_trait]
+    impl DirectiveInterceptor for Notify {
+        fn directive_name(&self) -> &str {
+            "notify"
+        }
+
+        async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, _params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+            next(context).await
+        }
+
+        fn parse_parameters(&self, _loom_context: &LoomContext, _execution_context: &ExecutionContext, _call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+            Ok(HashMap::new())
+        }
+
+        fn parameters(&self) -> Vec<ParameterDefinition> {
+            vec![ParameterDefinition {
+                name: "channel".to_string(),
+                param_type: ParameterType::String,
+                required: false,
+                default_value: None,
+                description: "use 'to' instead".to_string(),
+                deprecated: true,
+            }]
+        }
+
+        fn priority(&self) -> i32 { 600 }
+
+        fn need_chain(&self) -> bool {
+            true
+        }
+    }
+
+    /// Interceptor globale che passa al successivo senza fare altro
+    struct Passthrough(&'static str, i32);
+
+    #[async_trait::async_trait]
+    impl GlobalInterceptor for Passthrough {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            "Calls the next interceptor"
+        }
+
+        fn default_config(&self) -> GlobalInterceptorConfig {
+            GlobalInterceptorConfig {
+                enabled: true,
+                priority: self.1,
+                conditions: Vec::new(),
+                parameters: HashMap::new(),
+                user_overridable: true,
+            }
+        }
+
+        async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, _config: &GlobalInterceptorConfig, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+            next(context).await
+        }
+
+        fn need_chain(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn register_globals_clears_the_cache_once() {
+        let mut engine = InterceptorEngine::new();
+        let clears = engine.cache_stats().unwrap().clears;
+
+        engine.register_globals([
+            Arc::new(Passthrough("security", 9100)) as Arc<dyn GlobalInterceptor>,
+            Arc::new(Passthrough("audit", 8100)),
+            Arc::new(Passthrough("metrics", 5100)),
+        ]).unwrap();
+
+        let names: Vec<_> = engine.list_globals().into_iter().map(|(name, _, _)| name).collect();
+        assert_eq!(names, ["security", "audit", "metrics"]);
+        assert_eq!(engine.cache_stats().unwrap().clears, clears + 1);
+    }
+
+    #[tokio::test]
+    async fn command_directives_apply_only_to_their_command() {
+        let skip = directive("if", vec![ArgDefinition::Positional(Expression::Literal(LiteralValue::Boolean(false)))]);
+        let loom_context = LoomContext::with_definitions(vec![recipe("build", vec![echo("skipped", vec![skip]), echo("sibling", vec![])])]);
+        let mut engine = InterceptorEngine::new();
+        engine.register_directive(Arc::new(SkipUnless)).unwrap();
+
+        let result = engine.execute(&loom_context, "build", &[]).await.unwrap();
+
+        assert_eq!(result.output().map(str::trim), Some("sibling"));
+    }
+
+    #[tokio::test]
+    async fn cascading_block_directive_wraps_each_command() {
+        let mut build = recipe("build", vec![]);
+        build.body = vec![Block::new(vec![echo("a", vec![]), echo("b", vec![])], vec![directive("timeout", vec![])], vec![])].into();
+        let loom_context = LoomContext::with_definitions(vec![build]);
+        let cascade = Arc::new(CountingDirective { cascade: true, ..Default::default() });
+        let mut engine = InterceptorEngine::new();
+        engine.register_directive(cascade.clone()).unwrap();
+
+        let result = engine.execute(&loom_context, "build", &[]).await.unwrap();
+
+        assert_eq!(result.output().map(str::trim), Some("a\nb"));
+        assert_eq!(cascade.count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn call_directives_wrap_only_that_call() {
+        let loom_context = LoomContext::with_definitions(vec![
+            recipe("deploy", vec![echo("deploy", vec![])]),
+            recipe("build", vec![call("deploy", vec![], vec![directive("timeout", vec![])]), echo("after", vec![])]),
+        ]);
+        let timeout = Arc::new(CountingDirective::default());
+        let mut engine = InterceptorEngine::new();
+        engine.register_directive(timeout.clone()).unwrap();
+
+        let result = engine.execute(&loom_context, "build", &[]).await.unwrap();
+
+        assert_eq!(result.output().map(str::trim), Some("deploy\nafter"));
+        assert_eq!(timeout.count.load(Ordering::SeqCst), 1);
+        // La direttiva è legata alla chiamata, non alla definition chiamata
+        engine.execute(&loom_context, "deploy", &[]).await.unwrap();
+        assert_eq!(timeout.count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn interceptor_stack_lists_the_chain_from_the_outermost() {
+        let failing = Statement::Command {
+            parts: vec![Expression::Literal(LiteralValue::String("exit 3".to_string()))].into(),
+            directives: vec![directive("timeout", vec![])].into(),
+            position: Position::default(),
+        };
+        let loom_context = LoomContext::with_definitions(vec![recipe("build", vec![failing])]);
+        let mut engine = InterceptorEngine::new();
+        engine.register_global(Arc::new(Passthrough("security", 9100))).unwrap();
+        engine.register_directive(Arc::new(CountingDirective::default())).unwrap();
+
+        let error = engine.execute(&loom_context, "build", &[]).await.unwrap_err();
+
+        let LoomError::InterceptorError { interceptor_stack, .. } = error else {
+            panic!("expected an interceptor error, got {:?}", error);
+        };
+        // Gli interceptor globali avvolgono ogni livello: definition, block e comando
+        assert_eq!(interceptor_stack, ["security", "definition", "security", "Block", "security", "timeout", "command"]);
+    }
+
+    #[tokio::test]
+    async fn mutually_recursive_recipes_are_rejected() {
+        let loom_context = LoomContext::with_definitions(vec![
+            recipe("a", vec![call("b", vec![], vec![])]),
+            recipe("b", vec![call("a", vec![], vec![])]),
+        ]);
+
+        let error = InterceptorEngine::new().execute(&loom_context, "a", &[]).await.unwrap_err();
+
+        assert!(error.to_string().contains("recursive definition cycle: a -> b -> a"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn call_depth_is_configurable() {
+        let loom_context = LoomContext::with_definitions(vec![
+            recipe("a", vec![call("b", vec![], vec![])]),
+            recipe("b", vec![call("c", vec![], vec![])]),
+            recipe("c", vec![echo("c", vec![])]),
+        ]);
+        let mut engine = InterceptorEngine::new();
+        engine.set_max_call_depth(1);
+
+        let error = engine.execute(&loom_context, "a", &[]).await.unwrap_err();
+
+        assert!(error.to_string().contains("maximum call depth of 1 exceeded"), "{}", error);
+    }
+
+    #[test]
+    fn globals_sharing_a_priority_are_reported() {
+        let mut engine = InterceptorEngine::new();
+        engine.register_global(Arc::new(Passthrough("security", 9000))).unwrap();
+        assert!(engine.validate_priority_conflicts().is_ok());
+
+        engine.register_global(Arc::new(Passthrough("audit", 9000))).unwrap();
+        let conflicts = engine.validate_priority_conflicts().unwrap_err();
+
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].contains("audit") && conflicts[0].contains("security") && conflicts[0].contains("9000"), "{}", conflicts[0]);
+    }
+
+    #[tokio::test]
+    async fn command_result_bubbles_up_through_a_global() {
+        let loom_context = LoomContext::with_definitions(vec![recipe("build", vec![echo("hello", vec![])])]);
+        let mut engine = InterceptorEngine::new();
+        engine.register_global(Arc::new(Passthrough("security", 9100))).unwrap();
+
+        let result = engine.execute(&loom_context, "build", &[]).await.unwrap();
+
+        assert_eq!(result.output().map(str::trim), Some("hello"));
+        assert_eq!(result.exit_code(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn chain_cache_stays_bounded() {
+        let names = ["a", "b", "c", "d"];
+        let loom_context = LoomContext::with_definitions(names.iter().map(|name| recipe(name, vec![echo(name, vec![])])).collect());
+        let engine = InterceptorEngine::new().with_cache_capacity(2);
+
+        for name in names {
+            engine.execute(&loom_context, name, &[]).await.unwrap();
+        }
+        engine.execute(&loom_context, "d", &[]).await.unwrap();
+
+        let stats = engine.cache_stats().unwrap();
+        assert_eq!((stats.size, stats.capacity), (2, 2));
+        assert_eq!((stats.hits, stats.misses), (1, 4));
+    }
+
+    #[tokio::test]
+    async fn registered_secrets_are_masked_in_command_events() {
+        let print_password = Statement::Command {
+            parts: vec![
+                Expression::Literal(LiteralValue::String("echo ".to_string())),
+                Expression::FunctionCall { name: "env".into(), args: vec![Expression::Literal(LiteralValue::String("DB_PASSWORD".to_string()))].into() },
+            ].into(),
+            directives: Vec::new().into(),
+            position: Position::default(),
+        };
+        let loom_context = LoomContext::with_definitions(vec![recipe("build", vec![print_password])]);
+        let mut engine = InterceptorEngine::new();
+        engine.register_secret("DB_PASSWORD");
+        let mut options = ExecutionOptions::default();
+        options.extra_env.insert("DB_PASSWORD".to_string(), "hunter2".to_string());
+        let (channel, mut receiver) = ExecutionEventChannel::new();
+
+        let result = engine.execute_with_options_and_channel(&loom_context, "build", &[], &options, channel).await.unwrap();
+
+        let mut started = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            if let ExecutionEventKind::CommandStarted { command, .. } = event.kind {
+                started.push(command);
+            }
+        }
+        assert_eq!(started, ["echo ****"]);
+        assert_eq!(result.output().map(str::trim), Some("****"));
+    }
+
+    #[tokio::test]
+    async fn deprecated_parameters_warn_but_still_execute() {
+        let notify = directive("notify", vec![ArgDefinition::Named { name: "channel".to_string(), value: string("ops") }]);
+        let loom_context = LoomContext::with_definitions(vec![recipe("build", vec![echo("hello", vec![notify])])]);
+        let mut engine = InterceptorEngine::new();
+        engine.register_directive(Arc::new(Notify)).unwrap();
+        let (channel, mut receiver) = ExecutionEventChannel::new();
+
+        let result = engine.execute_with_channel(&loom_context, "build", &[], channel).await.unwrap();
+
+        let mut warnings = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            if let ExecutionEventKind::DirectiveWarning { directive_name, message } = event.kind {
+                warnings.push((directive_name, message));
+            }
+        }
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].0, "notify");
+        assert!(warnings[0].1.contains("'channel'") && warnings[0].1.contains("use 'to' instead"), "{}", warnings[0].1);
+        assert_eq!(result.output().map(str::trim), Some("hello"));
+    }
+
+    #[tokio::test]
+    async fn else_must_directly_follow_an_if() {
+        let if_true = || directive("if", vec![ArgDefinition::Positional(Expression::Literal(LiteralValue::Boolean(true)))]);
+        let loom_context = LoomContext::with_definitions(vec![
+            recipe("paired", vec![echo("a", vec![if_true()]), echo("b", vec![directive("else", vec![])])]),
+            recipe("detached", vec![echo("a", vec![if_true()]), echo("b", vec![]), echo("c", vec![directive("else", vec![])])]),
+        ]);
+        let mut engine = InterceptorEngine::new();
+        engine.register_directives([Arc::new(SkipUnless) as Arc<dyn DirectiveInterceptor>, Arc::new(PairedElse)]).unwrap();
+
+        let diagnostics = engine.validate_all(&loom_context);
+        assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+        assert!(diagnostics[0].to_string().contains("must directly follow an element with '@if'"), "{}", diagnostics[0]);
+
+        let error = engine.execute(&loom_context, "detached", &[]).await.unwrap_err();
+        assert!(error.to_string().contains("must directly follow"), "{}", error);
+
+        // Il @else riceve l'identificativo con cui è stato accoppiato al @if
+        let result = engine.execute(&loom_context, "paired", &[]).await.unwrap();
+        let pair = result.output().and_then(|output| output.lines().last()).unwrap_or_default();
+        assert_eq!(pair.len(), uuid::Uuid::nil().to_string().len(), "{:?}", result.output());
+    }
+
+    #[tokio::test]
+    async fn cached_chains_are_not_shared_between_different_argument_values() {
+        let mut deploy = recipe("deploy", vec![Statement::Command {
+            parts: vec![string("echo "), Expression::Variable("target".into())].into(),
+            directives: Vec::new().into(),
+            position: Position::default(),
+        }]);
+        deploy.signature.parameters = vec![crate::types::ParameterDefinition {
+            name: "target".into(),
+            param_type: None,
+            default_value: None,
+            required: true,
+            varargs: false,
+        }].into();
+        let loom_context = LoomContext::with_definitions(vec![deploy]);
+        let signature = loom_context.find_definition("deploy").unwrap().signature.clone();
+        let engine = InterceptorEngine::new();
+
+        for target in ["prod", "dev", "prod"] {
+            let args = signature.bind_call_args(&[ArgDefinition::Positional(string(target))]).unwrap();
+            let result = engine.execute(&loom_context, "deploy", &args).await.unwrap();
+            assert_eq!(result.output().map(str::trim), Some(target));
+        }
+
+        let stats = engine.cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+    }
+}
@@ -0,0 +1,71 @@
+use crate::error::{LoomError, LoomResult};
+
+/// Legge un file in formato dotenv (`KEY=VALUE`, una coppia per riga)
+pub fn load_env_file(path: &str) -> LoomResult<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| LoomError::io_with_path(err.to_string(), path))?;
+    parse_env_file(&content, path)
+}
+
+/// Interpreta il contenuto di un file dotenv. Righe vuote e commenti `#` vengono ignorati,
+/// il prefisso `export` è ammesso e i valori possono essere tra apici singoli o doppi.
+/// `path` viene usato solo nei messaggi di errore
+pub fn parse_env_file(content: &str, path: &str) -> LoomResult<Vec<(String, String)>> {
+    let mut entries = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let malformed = |message: &str| LoomError::config_with_path(
+            format!("line {}: {}", index + 1, message),
+            path
+        );
+
+        let line = line.strip_prefix("export ").map_or(line, str::trim_start);
+        let (key, value) = line.split_once('=')
+            .ok_or_else(|| malformed("expected KEY=VALUE"))?;
+
+        let key = key.trim();
+        let valid_key = key.chars().next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+            && key.chars().all(|char| char.is_ascii_alphanumeric() || char == '_' || char == '.');
+        if !valid_key {
+            return Err(malformed(&format!("invalid variable name '{}'", key)));
+        }
+
+        let value = parse_value(value.trim())
+            .ok_or_else(|| malformed(&format!("unterminated quoted value for '{}'", key)))?;
+        entries.push((key.to_string(), value));
+    }
+
+    Ok(entries)
+}
+
+/// Valore di una riga: tra apici viene preso alla lettera (con gli escape `\n`, `\"` e `\\`
+/// per i doppi apici), altrimenti fino a un eventuale commento ` #`. None se l'apice non è chiuso
+fn parse_value(value: &str) -> Option<String> {
+    if let Some(rest) = value.strip_prefix('\'') {
+        return rest.find('\'').map(|end| rest[..end].to_string());
+    }
+
+    if let Some(rest) = value.strip_prefix('"') {
+        let mut result = String::new();
+        let mut chars = rest.chars();
+        while let Some(char) = chars.next() {
+            match char {
+                '"' => return Some(result),
+                '\\' => match chars.next()? {
+                    'n' => result.push('\n'),
+                    other => result.push(other),
+                },
+                other => result.push(other),
+            }
+        }
+        return None;
+    }
+
+    let value = value.find(" #").map_or(value, |comment| &value[..comment]);
+    Some(value.trim_end().to_string())
+}
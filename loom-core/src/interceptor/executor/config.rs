@@ -1,4 +1,101 @@
-#[derive(Default, Clone)]
+use std::time::Duration;
+use crate::error::{LoomError, LoomResult};
+
+/// Chiave di `ExecutionContext::metadata` con cui una direttiva (es. @ignore-errors)
+/// disattiva `fail_on_error` per i comandi che intercetta
+pub const IGNORE_ERRORS_KEY: &str = "executor.ignore_errors";
+
+/// Numero massimo di nuovi tentativi accettato da `ExecutorConfig::retries`
+pub const MAX_COMMAND_RETRIES: u32 = 10;
+
+/// Configurazione per gli executor
+#[derive(Debug, Clone)]
 pub struct ExecutorConfig {
-    
-}
\ No newline at end of file
+    /// Tempo massimo di esecuzione di un comando, oltre il quale il processo viene terminato
+    pub timeout: Option<Duration>,
+    /// Se un exit code diverso da 0 interrompe l'esecuzione con un errore
+    pub fail_on_error: bool,
+    /// Se l'output dei comandi viene emesso riga per riga sul channel degli eventi durante l'esecuzione
+    pub stream_output: bool,
+    /// Se le occorrenze `${NAME}` nel comando vengono sostituite con le variabili d'ambiente dell'ExecutionContext.
+    /// L'espansione avviene dopo l'interpolazione delle variabili Loom, sulla stringa già risolta; i valori
+    /// vengono quotati per la shell e il testo tra apici singoli non viene espanso.
+    /// `$${` produce un `${` letterale e `$NAME` senza graffe resta alla shell. Disattivata di default
+    pub expand_env: bool,
+    /// Con `expand_env`, se un `${NAME}` non definito è un errore invece di essere lasciato alla shell
+    pub fail_on_undefined_env: bool,
+    /// Nuovi tentativi di un comando fallito, oltre a quelli richiesti dagli hook OnError
+    pub retries: u32,
+    /// Se stdout e stderr vengono catturati nel risultato; altrimenti il processo li eredita dal terminale
+    pub capture_output: bool,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            fail_on_error: true,
+            stream_output: false,
+            expand_env: false,
+            fail_on_undefined_env: false,
+            retries: 0,
+            capture_output: true,
+        }
+    }
+}
+
+impl ExecutorConfig {
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_fail_on_error(mut self, fail_on_error: bool) -> Self {
+        self.fail_on_error = fail_on_error;
+        self
+    }
+
+    pub fn with_stream_output(mut self, stream_output: bool) -> Self {
+        self.stream_output = stream_output;
+        self
+    }
+
+    pub fn with_expand_env(mut self, expand_env: bool) -> Self {
+        self.expand_env = expand_env;
+        self
+    }
+
+    pub fn with_fail_on_undefined_env(mut self, fail_on_undefined_env: bool) -> Self {
+        self.fail_on_undefined_env = fail_on_undefined_env;
+        self
+    }
+
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn with_capture_output(mut self, capture_output: bool) -> Self {
+        self.capture_output = capture_output;
+        self
+    }
+
+    /// Verifica che i valori siano coerenti tra loro e in un intervallo sensato
+    pub fn validate(&self) -> LoomResult<()> {
+        if self.timeout.is_some_and(|timeout| timeout.is_zero()) {
+            return Err(LoomError::config("Executor timeout must be greater than zero"));
+        }
+        if self.retries > MAX_COMMAND_RETRIES {
+            return Err(LoomError::config(format!(
+                "Executor retries must be at most {}, found {}", MAX_COMMAND_RETRIES, self.retries
+            )));
+        }
+        if self.fail_on_undefined_env && !self.expand_env {
+            return Err(LoomError::config("fail_on_undefined_env requires expand_env to be enabled"));
+        }
+        if self.stream_output && !self.capture_output {
+            return Err(LoomError::config("stream_output requires capture_output to be enabled"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+use crate::ast::{AssignmentTarget, Expression};
+use crate::context::LoomContext;
+use crate::error::{LoomError, LoomResult, UndefinedKind};
+use crate::interceptor::{InterceptorChain, InterceptorResult};
+use crate::interceptor::context::{ExecutionContext, InterceptorContext};
+use crate::interceptor::executor::config::ExecutorConfig;
+use crate::interceptor::executor::ExecutorInterceptor;
+use crate::interceptor::result::ExecutionResult;
+use crate::types::{LiteralValue, LoomValue, Position};
+
+/// Valuta il valore di un assegnamento e lo scrive nelle variabili dell'ExecutionContext.
+/// Con un indice sostituisce l'elemento di un array già esistente
+pub struct AssignmentExecutorInterceptor {
+    pub target: AssignmentTarget,
+    pub value: Arc<Expression>,
+    pub position: Position,
+}
+
+impl AssignmentExecutorInterceptor {
+    fn undefined(&self, name: &str, context: &ExecutionContext) -> LoomError {
+        LoomError::undefined_among(
+            name.to_string(),
+            UndefinedKind::Variable,
+            self.position.clone(),
+            context.variables.keys().map(|name| name.as_ref())
+        )
+    }
+
+    fn evaluate_index(&self, loom_context: &LoomContext, context: &ExecutionContext, index: &Expression) -> LoomResult<i64> {
+        match index.evaluate(loom_context, context, Some(self.position.clone()))? {
+            LoomValue::Literal(LiteralValue::Number(index)) => Ok(index),
+            LoomValue::Literal(literal) => Err(LoomError::type_error("number", literal.type_name(), self.position.clone())),
+            value => Err(LoomError::type_error("number", value.type_name(), self.position.clone())),
+        }
+    }
+
+    /// Sostituisce `items[index]` con `value`, verificando che `items` sia un array e l'indice sia nel range
+    fn assign_index(&self, context: &mut ExecutionContext, object: &Arc<str>, index: i64, value: LoomValue) -> LoomResult<()> {
+        let value = match value {
+            LoomValue::Literal(literal) => literal,
+            value => return Err(LoomError::type_error("literal", value.type_name(), self.position.clone())),
+        };
+        if !context.variables.contains_key(object) {
+            return Err(self.undefined(object, context));
+        }
+
+        let items = match context.variables.get_mut(object) {
+            Some(LoomValue::Literal(LiteralValue::Array(items))) => items,
+            Some(LoomValue::Literal(literal)) => return Err(LoomError::type_error("array", literal.type_name(), self.position.clone())),
+            Some(other) => return Err(LoomError::type_error("array", other.type_name(), self.position.clone())),
+            None => unreachable!("variable presence checked above"),
+        };
+
+        let length = items.len();
+        let slot = usize::try_from(index).ok()
+            .and_then(|index| items.get_mut(index))
+            .ok_or_else(|| LoomError::execution_at(
+                format!("Index {} out of range for '{}' (length {})", index, object, length),
+                self.position.clone()
+            ))?;
+        *slot = value;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ExecutorInterceptor for AssignmentExecutorInterceptor {
+    fn name(&self) -> &str {
+        "assignment"
+    }
+    fn description(&self) -> &str {
+        "Assegna un valore ad una variabile"
+    }
+    fn default_config(&self) -> ExecutorConfig {
+        ExecutorConfig::default()
+    }
+    async fn intercept<'a>(
+        &'a self,
+        context: InterceptorContext<'a>,
+        _config: &ExecutorConfig,
+        _next: Box<InterceptorChain<'a>>,
+    ) -> InterceptorResult {
+        let mut execution_context = context.write_execution_context("assign variable")?;
+        let value = self.value.evaluate(context.loom_context, &execution_context, Some(self.position.clone()))?;
+
+        match &self.target {
+            AssignmentTarget::Variable(name) => {
+                execution_context.variables.insert(name.clone(), value);
+            }
+            AssignmentTarget::IndexAccess { object, index } => {
+                let index = self.evaluate_index(context.loom_context, &execution_context, index)?;
+                self.assign_index(&mut execution_context, object, index, value)?;
+            }
+        }
+
+        Ok(ExecutionResult::new(None, None))
+    }
+
+    fn need_chain(&self) -> bool {
+        false
+    }
+
+    fn plan_detail(&self, _loom_context: &LoomContext, _context: &ExecutionContext) -> Option<String> {
+        Some(match &self.target {
+            AssignmentTarget::Variable(name) => format!("{} = ...", name),
+            AssignmentTarget::IndexAccess { object, .. } => format!("{}[...] = ...", object),
+        })
+    }
+}
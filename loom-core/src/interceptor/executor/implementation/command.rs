@@ -1,21 +1,41 @@
 use std::collections::HashMap;
 use std::ops::Deref;
-use std::process::Command;
+use std::process::{Output, Stdio};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
+use tokio::process::Command;
 use crate::ast::Expression;
 use crate::context::LoomContext;
-use crate::error::{LoomError, LoomResult};
+use crate::error::{InterceptorError, LoomError, LoomResult, UndefinedKind};
+use crate::event::channel::{ExecutionEventChannel, ExecutionEventKind};
+use crate::event::secret::SecretMasker;
+use crate::interceptor::cancellation::{CancellationToken, CancellationTokenExt};
 use crate::interceptor::context::{ExecutionContext, InterceptorContext};
-use crate::interceptor::executor::config::ExecutorConfig;
+use crate::interceptor::executor::config::{ExecutorConfig, IGNORE_ERRORS_KEY};
 use crate::interceptor::executor::ExecutorInterceptor;
-use crate::interceptor::hook::registry::HookRegistry;
+use crate::interceptor::hook::HookPayload;
+use crate::interceptor::hook::registry::{HookRegistry, RETRY_MAX_KEY};
 use crate::interceptor::{InterceptorChain, InterceptorResult};
-use crate::interceptor::result::ExecutionResult;
+use crate::interceptor::result::{ExecutionResult, ExecutionStatus};
+use crate::interceptor::scope::ExecutionHook;
 use crate::interceptor_result;
 use crate::loom_error;
-use crate::types::LoomValue;
+use crate::types::Position;
 
-pub struct CommandExecutorInterceptor(pub Arc<[Expression]>);
+/// Attesa prima del primo retry, raddoppiata ad ogni tentativo successivo fino a RETRY_MAX_DELAY
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Parti del comando da valutare e posizione dello statement, usata negli errori di valutazione
+pub struct CommandExecutorInterceptor(pub Arc<[Expression]>, pub Position);
+
+/// Motivo per cui un processo non ha prodotto un output
+enum CommandFailure {
+    Io(std::io::Error),
+    TimedOut(Duration),
+    Cancelled,
+}
 
 #[async_trait::async_trait]
 impl ExecutorInterceptor for CommandExecutorInterceptor {
@@ -31,57 +51,311 @@ impl ExecutorInterceptor for CommandExecutorInterceptor {
     async fn intercept<'a>(
         &'a self,
         context: InterceptorContext<'a>,
-        // TODO: Queste config mi potrebbero servie a qualcosa in questo livello
-        _config: &ExecutorConfig,
+        config: &ExecutorConfig,
         // TODO: Non dovrebbe esistere un NEXT perchè gli executor sono terminali e contengono altri interceptor
         _next: Box<InterceptorChain<'a>>,
     ) -> InterceptorResult {
         // TODO: Aggiungere hooks di "inizio", "fine", "success" e "error" definition
         // Esegue il comando
-        self.launch_interceptor(context)
+        self.launch_interceptor(context, config).await
     }
 
     fn need_chain(&self) -> bool {
         false
     }
 
+    fn plan_detail(&self, loom_context: &LoomContext, context: &ExecutionContext) -> Option<String> {
+        self.resolve_command(loom_context, context).ok()
+    }
+
 }
 
 
 impl CommandExecutorInterceptor {
     
-    fn launch_interceptor(
+    async fn launch_interceptor(
         &self,
         context: InterceptorContext<'_>,
+        config: &ExecutorConfig,
     ) -> LoomResult<ExecutionResult> {
-        let command =
+        let command = {
+            let execution_context = context.read_execution_context("resolve command")?;
+            let command = self.resolve_command(context.loom_context, execution_context.deref())?;
+            if config.expand_env {
+                self.expand_env(&command, &execution_context.env_vars, config.fail_on_undefined_env)?
+            } else {
+                command
+            }
+        };
+
+        context.run_hooks(ExecutionHook::PreCommand, &HookPayload::Command { command: vec![command.clone()] })?;
+        let mut max_attempts = Self::take_retry_request(&context)?.unwrap_or(1).max(config.retries + 1);
+
+        let mut attempt = 1;
+        let result = loop {
+            let result = self.run_attempt(&context, &command, config).await;
+            let Err(error) = &result else {
+                break result;
+            };
+            // Un comando annullato non va ritentato
+            if error.is_cancelled() {
+                break result;
+            }
+
+            // Un OnError hook può chiedere di ritentare il comando, un Block non sostituisce l'errore originale
+            let _ = context.run_hooks(ExecutionHook::OnError, &HookPayload::Error { error: error.to_string() });
+            if let Some(requested) = Self::take_retry_request(&context)? {
+                max_attempts = max_attempts.max(requested);
+            }
+            if attempt >= max_attempts {
+                break result;
+            }
+
+            let delay = RETRY_BASE_DELAY.saturating_mul(1 << (attempt - 1).min(6)).min(RETRY_MAX_DELAY);
+            let _ = context.channel.emit_with_context(
+                ExecutionEventKind::CommandRetry {
+                    command: command.clone(),
+                    attempt: attempt + 1,
+                    max_attempts,
+                    delay_ms: delay.as_millis() as u64,
+                },
+                HashMap::new(),
+            );
+            context.cancellation.sleep(delay, "waiting to retry the command").await?;
+            attempt += 1;
+        };
+
+        let payload = match &result {
+            Ok(result) => HookPayload::Result { result: result.clone() },
+            Err(error) => HookPayload::Error { error: error.to_string() },
+        };
+        let hook_result = context.run_hooks(ExecutionHook::PostCommand, &payload);
+
+        // Se il comando è fallito l'errore originale ha la precedenza su quello degli hook
+        let result = result?;
+        hook_result?;
+        Ok(result)
+    }
+
+    /// Singola esecuzione del comando, notificando inizio e fine sul channel
+    async fn run_attempt(
+        &self,
+        context: &InterceptorContext<'_>,
+        command: &str,
+        config: &ExecutorConfig,
+    ) -> LoomResult<ExecutionResult> {
+        // Il lock non può essere mantenuto durante l'esecuzione asincrona del processo,
+        // la copia viene presa dopo gli hook che potrebbero averlo modificato
+        let execution_context = context.read_execution_context("snapshot before command")?
+            .clone();
+
+        // Il permesso dell'engine resta acquisito fino alla fine del processo,
+        // così il limite vale per tutti i comandi in esecuzione, anche in branch paralleli diversi
+        let _permit = context.command_slots.acquire().await
+            .map_err(|_| LoomError::concurrency("command slots", "acquire", "the engine semaphore has been closed"))?;
+        // L'attesa del permesso può essere lunga, l'annullamento va ricontrollato prima di avviare il processo
+        context.cancellation.check("starting the command")?;
+
+        let channel = context.channel.clone();
+        let start_time = Instant::now();
+
+        // Gli eventi sono best-effort: un channel chiuso non deve bloccare l'esecuzione
+        let _ = channel.emit_with_context(
+            ExecutionEventKind::CommandStarted {
+                command: command.to_string(),
+                working_dir: execution_context.working_dir.clone(),
+            },
+            HashMap::new(),
+        );
+
+        let stream = config.stream_output.then(|| channel.clone());
+        let result = self.execute_command(command, &execution_context, config, stream, &context.cancellation).await;
+        let result = Self::mask_secrets(result, &channel.secrets);
+
+        let duration_ms = start_time.elapsed().as_millis() as u64;
+        let event = match &result {
+            Ok(result) if result.exit_code() == Some(0) => ExecutionEventKind::CommandCompleted {
+                command: command.to_string(),
+                exit_code: result.exit_code(),
+                duration_ms,
+                output_lines: result.output().map_or(0, |output| output.lines().count()),
+            },
+            // Exit code non zero con fail_on_error disattivato, o processo non avviato
+            Ok(result) => ExecutionEventKind::CommandFailed {
+                command: command.to_string(),
+                error: result.stderr()
+                    .or_else(|| result.metadata().get("system_error").map(String::as_str))
+                    .unwrap_or_default()
+                    .trim_end()
+                    .to_string(),
+                exit_code: result.exit_code(),
+                duration_ms,
+            },
+            Err(LoomError::InterceptorError {
+                error: InterceptorError::CommandExecution { message, exit_code, .. }, ..
+            }) => ExecutionEventKind::CommandFailed {
+                command: command.to_string(),
+                error: message.clone(),
+                exit_code: *exit_code,
+                duration_ms,
+            },
+            Err(error) => ExecutionEventKind::CommandFailed {
+                command: command.to_string(),
+                error: error.to_string(),
+                exit_code: None,
+                duration_ms,
+            },
+        };
+        let _ = channel.emit_with_context(event, HashMap::new());
+
+        result
+    }
+
+    /// Sostituisce i valori segreti nell'output e negli errori del comando, prima che vengano propagati
+    fn mask_secrets(result: LoomResult<ExecutionResult>, secrets: &SecretMasker) -> LoomResult<ExecutionResult> {
+        if secrets.is_empty() {
+            return result;
+        }
+
+        match result {
+            Ok(mut result) => {
+                result.output.iter_mut()
+                    .chain(result.stderr.iter_mut())
+                    .chain(result.metadata.values_mut())
+                    .for_each(|text| secrets.mask_in_place(text));
+                Ok(result)
+            }
+            Err(mut error) => {
+                match &mut error {
+                    LoomError::InterceptorError {
+                        error: InterceptorError::CommandExecution { command, message, .. }, ..
+                    } => {
+                        secrets.mask_in_place(command);
+                        secrets.mask_in_place(message);
+                    }
+                    LoomError::Cancelled { message } => secrets.mask_in_place(message),
+                    _ => {}
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Consuma la richiesta di retry lasciata nei metadata da un hook, così non si applica ai comandi successivi
+    fn take_retry_request(context: &InterceptorContext<'_>) -> LoomResult<Option<u32>> {
+        let mut execution_context = context.write_execution_context("take retry request")?;
+
+        Ok(
+            execution_context.metadata.remove(RETRY_MAX_KEY)
+                .and_then(|value| value.parse().ok())
+        )
+    }
+
+    /// Valuta le parti del comando e le concatena nella stringa da eseguire
+    fn resolve_command(&self, loom_context: &LoomContext, context: &ExecutionContext) -> LoomResult<String> {
+        Ok(
             self.0.iter()
                 .map(|it|
-                    it.evaluate(
-                        context.loom_context,
-                        context.execution_context.read().map_err(|_| LoomError::execution("Error while trying to read"))?.deref(),
-                        None
-                    ).map(|it|
-                            match it {
-                                LoomValue::Literal(lit) => lit.stringify(),
-                                _ => panic!("Unexpected")
-                            }
-                        )
+                    it.evaluate(loom_context, context, Some(self.1.clone()))
+                        .and_then(|it| it.stringify(loom_context, context))
                 )
                 .collect::<Result<Vec<_>, LoomError>>()?
-            .join("");
-        
-        self.execute_command(&command, context.execution_context.read().map_err(|_| LoomError::execution("Error while trying to read"))?.deref())
+                .join("")
+        )
     }
-    
+
+    /// Sostituisce `${NAME}` con il valore della variabile d'ambiente, quotato per la shell così che
+    /// `;`, backtick o `$(...)` nel valore non vengano eseguiti. Il testo tra apici singoli e i `\${`
+    /// restano invariati, `$${` viene reso come `${`; le variabili non definite restano invariate
+    /// per la shell o sono un errore con `strict`
+    fn expand_env(&self, command: &str, env_vars: &HashMap<String, String>, strict: bool) -> LoomResult<String> {
+        let mut result = String::with_capacity(command.len());
+        // Apice aperto: '\'' o '"'
+        let mut quote: Option<char> = None;
+        let mut index = 0;
+
+        while let Some(current) = command[index..].chars().next() {
+            let rest = &command[index..];
+            match current {
+                '\\' if quote != Some('\'') => {
+                    // Il carattere escapato passa alla shell senza essere interpretato
+                    let escaped = rest.chars().take(2).map(char::len_utf8).sum::<usize>();
+                    result.push_str(&rest[..escaped]);
+                    index += escaped;
+                    continue;
+                }
+                '\'' if quote != Some('"') => quote = if quote.is_some() { None } else { Some('\'') },
+                '"' if quote != Some('\'') => quote = if quote.is_some() { None } else { Some('"') },
+                // `$${` è l'escape di un `${` letterale
+                '$' if quote != Some('\'') && rest.starts_with("$${") => {
+                    result.push_str("${");
+                    index += 3;
+                    continue;
+                }
+                '$' if quote != Some('\'') && rest.starts_with("${") => {
+                    let Some(end) = rest[2..].find('}') else {
+                        // Graffa non chiusa: il resto viene lasciato alla shell
+                        result.push_str(rest);
+                        return Ok(result);
+                    };
+                    let name = &rest[2..2 + end];
+
+                    match env_vars.get(name) {
+                        Some(value) => result.push_str(&Self::shell_quote(value, quote == Some('"'))),
+                        None if strict => {
+                            return Err(LoomError::undefined(name, UndefinedKind::EnvVariable, self.1.clone()));
+                        }
+                        None => result.push_str(&rest[..3 + end]),
+                    }
+                    index += 3 + end;
+                    continue;
+                }
+                _ => {}
+            }
+            result.push(current);
+            index += current.len_utf8();
+        }
+
+        Ok(result)
+    }
+
+    /// Quota un valore perché la shell lo tratti come testo letterale.
+    /// Dentro doppi apici vengono escapati solo i caratteri che la shell interpreta ancora
+    fn shell_quote(value: &str, in_double_quotes: bool) -> String {
+        if cfg!(target_os = "windows") {
+            let mut quoted = String::with_capacity(value.len());
+            for c in value.chars() {
+                if matches!(c, '^' | '&' | '|' | '<' | '>' | '(' | ')' | '%' | '!' | '"') {
+                    quoted.push('^');
+                }
+                quoted.push(c);
+            }
+            quoted
+        } else if in_double_quotes {
+            let mut quoted = String::with_capacity(value.len());
+            for c in value.chars() {
+                if matches!(c, '\\' | '"' | '$' | '`') {
+                    quoted.push('\\');
+                }
+                quoted.push(c);
+            }
+            quoted
+        } else {
+            format!("'{}'", value.replace('\'', "'\\''"))
+        }
+    }
+
     /// Esegue un comando in modo cross-platform
-    fn execute_command(&self, command_string: &str, context: &ExecutionContext) -> LoomResult<ExecutionResult> {
+    async fn execute_command(
+        &self,
+        command_string: &str,
+        context: &ExecutionContext,
+        config: &ExecutorConfig,
+        stream: Option<ExecutionEventChannel>,
+        cancellation: &CancellationToken,
+    ) -> LoomResult<ExecutionResult> {
         if context.dry_run {
-            return Ok(ExecutionResult {
-                output: Some(format!("DRY RUN: Would execute: {}", command_string)),
-                exit_code: Some(0),
-                metadata: HashMap::new(),
-            });
+            return Ok(ExecutionResult::new(Some(format!("DRY RUN: Would execute: {}", command_string)), Some(0)));
         }
 
         // Parsing del comando per separare comando base e argomenti
@@ -92,8 +366,6 @@ impl CommandExecutorInterceptor {
 
         // let (cmd, args) = parts.split_first().unwrap();
 
-        // let start_time = std::time::Instant::now();
-
         // Costruisce il comando
         let mut command = if cfg!(target_os = "windows") {
             let mut cmd_builder = Command::new("cmd");
@@ -115,15 +387,24 @@ impl CommandExecutorInterceptor {
             command.env(key, value);
         }
 
+        command.stdin(Stdio::null()).kill_on_drop(true);
+        if config.capture_output {
+            command.stdout(Stdio::piped()).stderr(Stdio::piped());
+        } else {
+            command.stdout(Stdio::inherit()).stderr(Stdio::inherit());
+        }
+
+        let ignore_errors = context.metadata.get(IGNORE_ERRORS_KEY)
+            .is_some_and(|value| value == "true");
+
         // Esegue il comando
-        match command.output() {
+        match Self::wait_with_timeout(command, command_string, config.timeout, stream, cancellation).await {
             Ok(output) => {
-                // let execution_time = start_time.elapsed();
                 // let success = output.status.success();
                 let exit_code = output.status.code();
 
                 let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                // let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
 
                 let mut metadata = HashMap::new();
                 metadata.insert("command".to_string(), command_string.to_string());
@@ -131,27 +412,134 @@ impl CommandExecutorInterceptor {
                     metadata.insert("exit_code".to_string(), code.to_string());
                 }
 
+                if config.fail_on_error && !ignore_errors && !output.status.success() {
+                    return Err(LoomError::command_execution(command_string, stderr.trim_end(), exit_code));
+                }
+
                 Ok(ExecutionResult {
                     output: if stdout.is_empty() { None } else { Some(stdout) },
+                    stderr: if stderr.is_empty() { None } else { Some(stderr) },
                     exit_code,
                     metadata,
+                    status: ExecutionStatus::Executed,
                 })
             }
-            Err(e) => {
-                // let execution_time = start_time.elapsed();
+            Err(CommandFailure::TimedOut(timeout)) => {
+                Err(LoomError::command_execution(
+                    command_string,
+                    format!("Timed out after {:?}, the process has been killed", timeout),
+                    None
+                ))
+            }
+            Err(CommandFailure::Cancelled) => {
+                Err(LoomError::cancelled(format!("command '{}' has been killed", command_string)))
+            }
+            // Processo non avviato (es. shell mancante o working dir inesistente): con fail_on_error interrompe la sequenza
+            Err(CommandFailure::Io(e)) if config.fail_on_error && !ignore_errors => {
+                Err(LoomError::command_execution(command_string, format!("Failed to run the command: {}", e), None))
+            }
+            Err(CommandFailure::Io(e)) => {
                 let mut metadata = HashMap::new();
                 metadata.insert("command".to_string(), command_string.to_string());
                 metadata.insert("system_error".to_string(), e.to_string());
 
                 Ok(ExecutionResult {
                     output: None,
+                    stderr: None,
                     exit_code: None,
                     metadata,
+                    status: ExecutionStatus::Executed,
                 })
             }
         }
     }
 
+    /// Avvia il processo e ne attende la fine senza bloccare il runtime, terminandolo
+    /// se supera il timeout o se l'esecuzione viene annullata. stdout e stderr vengono letti in concorrenza
+    /// con l'attesa del processo, per non riempire le pipe, e se richiesto inoltrati riga per riga sul channel
+    /// degli eventi. Il timeout comprende la lettura delle pipe: un processo figlio rimasto in background
+    /// che le tiene aperte non blocca l'esecuzione oltre il limite
+    async fn wait_with_timeout(
+        mut command: Command,
+        command_string: &str,
+        timeout: Option<Duration>,
+        stream: Option<ExecutionEventChannel>,
+        cancellation: &CancellationToken,
+    ) -> Result<Output, CommandFailure> {
+        let mut child = command.spawn().map_err(CommandFailure::Io)?;
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        let failure = {
+            let run = async {
+                let (status, stdout, stderr) = tokio::join!(
+                    child.wait(),
+                    Self::read_pipe(stdout, command_string, stream.as_ref(), false),
+                    Self::read_pipe(stderr, command_string, stream.as_ref(), true),
+                );
+                Ok(Output { status: status?, stdout, stderr })
+            };
+            let limited = async {
+                match timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, run).await
+                        .map_err(|_| CommandFailure::TimedOut(timeout)),
+                    None => Ok(run.await),
+                }
+            };
+            tokio::select! {
+                result = limited => match result {
+                    Ok(output) => return output.map_err(CommandFailure::Io),
+                    Err(failure) => failure,
+                },
+                _ = cancellation.cancelled() => CommandFailure::Cancelled,
+            }
+        };
+
+        // Le pipe sono già state chiuse con la lettura interrotta, resta da terminare il processo
+        let _ = child.kill().await;
+        Err(failure)
+    }
+
+    /// Legge tutta la pipe; con un channel emette anche un evento per ogni riga letta
+    async fn read_pipe(
+        pipe: Option<impl AsyncRead + Unpin>,
+        command_string: &str,
+        stream: Option<&ExecutionEventChannel>,
+        is_stderr: bool,
+    ) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let Some(mut pipe) = pipe else {
+            return buffer;
+        };
+
+        match stream {
+            None => {
+                let _ = pipe.read_to_end(&mut buffer).await;
+            }
+            Some(channel) => {
+                let mut reader = BufReader::new(pipe);
+                let mut line = Vec::new();
+                while let Ok(read) = reader.read_until(b'\n', &mut line).await {
+                    if read == 0 {
+                        break;
+                    }
+                    let text = String::from_utf8_lossy(&line);
+                    // Gli eventi sono best-effort: un channel chiuso non deve bloccare l'esecuzione
+                    let _ = channel.emit_with_context(
+                        ExecutionEventKind::CommandOutputLine {
+                            command: command_string.to_string(),
+                            line: text.trim_end_matches(['\r', '\n']).to_string(),
+                            is_stderr,
+                        },
+                        HashMap::new(),
+                    );
+                    buffer.append(&mut line);
+                }
+            }
+        }
+        buffer
+    }
+
     // /// Parsing semplice del comando per separare comando e argomenti
     // /// Gestisce le virgolette per argomenti con spazi
     // fn parse_command(&self, command_string: &str) -> LoomResult<Vec<String>> {
@@ -190,4 +578,85 @@ impl CommandExecutorInterceptor {
     //
     //     Ok(parts)
     // }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{execution_context, string};
+    use crate::types::LoomValue;
+    use super::*;
+
+    fn expand(command: &str) -> LoomResult<String> {
+        let env_vars = HashMap::from([("NAME".to_string(), "a; rm -rf $(pwd) `id`'".to_string())]);
+        CommandExecutorInterceptor(Arc::from(vec![]), Position::default()).expand_env(command, &env_vars, false)
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn expand_env_quotes_the_substituted_value() {
+        assert_eq!(expand("echo ${NAME}").unwrap(), r#"echo 'a; rm -rf $(pwd) `id`'\'''"#);
+        assert_eq!(expand(r#"echo "${NAME}""#).unwrap(), r#"echo "a; rm -rf \$(pwd) \`id\`'""#);
+    }
+
+    #[test]
+    fn expand_env_leaves_single_quoted_spans_and_escapes_alone() {
+        assert_eq!(expand("echo '${NAME}'").unwrap(), "echo '${NAME}'");
+        assert_eq!(expand("echo $${NAME} ${OTHER}").unwrap(), "echo ${NAME} ${OTHER}");
+    }
+
+    #[test]
+    fn expand_env_is_disabled_by_default() {
+        assert!(!ExecutorConfig::default().expand_env);
+    }
+
+    #[test]
+    fn empty_and_unset_parts_do_not_panic() {
+        let loom_context = LoomContext::new();
+        let context = execution_context(vec![("empty", LoomValue::Empty)]);
+        let command = |name: &str| CommandExecutorInterceptor(
+            Arc::from(vec![string("echo ["), Expression::Variable(name.into()), string("]")]),
+            Position::default(),
+        );
+
+        assert_eq!(command("empty").resolve_command(&loom_context, &context).unwrap(), "echo []");
+        assert!(command("missing").resolve_command(&loom_context, &context).is_err());
+    }
+
+    #[tokio::test]
+    async fn capture_output_false_leaves_output_to_the_terminal() {
+        let command = CommandExecutorInterceptor(Arc::from(vec![]), Position::default());
+        let context = execution_context(vec![]);
+        let run = |config: ExecutorConfig| {
+            let command = &command;
+            let context = &context;
+            async move {
+                config.validate().unwrap();
+                command.execute_command("echo visible", context, &config, None, &CancellationToken::new()).await.unwrap()
+            }
+        };
+
+        let captured = run(ExecutorConfig::default()).await;
+        assert_eq!(captured.output().map(str::trim_end), Some("visible"));
+
+        let inherited = run(ExecutorConfig::default().with_capture_output(false).with_timeout(Duration::from_secs(10))).await;
+        assert_eq!(inherited.output(), None);
+        assert_eq!(inherited.exit_code(), Some(0));
+
+        assert!(ExecutorConfig::default().with_capture_output(false).with_stream_output(true).validate().is_err());
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn timeout_is_not_held_up_by_a_background_process_keeping_the_pipes_open() {
+        let command = CommandExecutorInterceptor(Arc::from(vec![]), Position::default());
+        let config = ExecutorConfig::default().with_timeout(Duration::from_millis(200));
+        let start = Instant::now();
+
+        // La shell termina subito, ma `sleep` eredita stdout e lo tiene aperto
+        let error = command.execute_command("sleep 5 & echo started", &execution_context(vec![]), &config, None, &CancellationToken::new())
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("Timed out"), "{}", error);
+        assert!(start.elapsed() < Duration::from_secs(2), "{:?}", start.elapsed());
+    }
+}
@@ -1,4 +1,5 @@
-use std::sync::Arc;
+use futures::future::join_all;
+use tokio::sync::Semaphore;
 use crate::error::LoomError;
 use crate::interceptor::{ActiveInterceptor, InterceptorChain, InterceptorResult};
 use crate::interceptor::context::InterceptorContext;
@@ -7,6 +8,7 @@ use crate::interceptor::executor::config::ExecutorConfig;
 use crate::interceptor::executor::ExecutorInterceptor;
 use crate::interceptor::executor::implementation::empty_execute_intercept_next;
 use crate::interceptor::result::ExecutionResult;
+use crate::types::ParallelizationKind;
 
 pub struct SequenceChainInterceptor(pub Vec<ActiveInterceptor>);
 
@@ -33,6 +35,10 @@ impl ExecutorInterceptor for SequenceChainInterceptor {
     fn need_chain(&self) -> bool {
         false
     }
+
+    fn children(&self) -> &[ActiveInterceptor] {
+        &self.0
+    }
 }
 
 pub struct SequentialExecutorInterceptor(pub Vec<ActiveInterceptor>, pub String);
@@ -51,26 +57,309 @@ impl ExecutorInterceptor for SequentialExecutorInterceptor {
     async fn intercept<'a>(
         &'a self,
         context: InterceptorContext<'a>,
-        config: &ExecutorConfig,
+        _config: &ExecutorConfig,
         _next: Box<InterceptorChain<'a>>,
     ) -> InterceptorResult {
-        let mut result: Option<ExecutionResult> = None;
-        for interceptor in &self.0 {
+        execute_children(context, &self.0).await
+        // context.execution_context.previous_result.take().ok_or("The result of a SequentialExecutor should not be None".to_string())
+        // InterceptorEngine::execute_chain(loom_context, context, hook_registry, &self.0)
+    }
+
+    fn need_chain(&self) -> bool {
+        false
+    }
+
+    fn children(&self) -> &[ActiveInterceptor] {
+        &self.0
+    }
+}
+
+/// Esegue in ordine gli executor figli, unendo i loro risultati con `ExecutionResult::combine`
+pub(crate) async fn execute_sequence<'a>(
+    context: InterceptorContext<'a>,
+    children: &'a [ActiveInterceptor],
+) -> InterceptorResult {
+    let mut results = Vec::with_capacity(children.len());
+    for interceptor in children {
+        match interceptor {
+            ActiveInterceptor::Executor(executor) => {
+                results.push(executor.interceptor.intercept(context.clone(), &executor.config, empty_execute_intercept_next()).await?);
+            }
+            _ => {
+                Err("SequentialExecutor should contain only executor Interceptor".to_string())?;
+            }
+        }
+    }
+    ExecutionResult::combine(results).ok_or(LoomError::execution("The result of a SequentialExecutor should not be None".to_string()))
+}
+
+/// Esegue gli executor figli in concorrenza, al massimo `max_thread` alla volta.
+/// I risultati sono raccolti nell'ordine di dichiarazione (non di completamento) e uniti,
+/// oppure viene ritornato un AggregateError con tutti i branch falliti e la loro posizione.
+/// Ogni branch lavora su uno snapshot dell'ExecutionContext: al termine vengono riportate nel contesto
+/// condiviso solo le variabili che il branch ha assegnato, nell'ordine di dichiarazione
+pub(crate) async fn execute_concurrently<'a>(
+    context: InterceptorContext<'a>,
+    children: &'a [ActiveInterceptor],
+    max_thread: u8,
+) -> InterceptorResult {
+    let semaphore = Semaphore::new(max_thread.max(1) as usize);
+    let base_variables = context.read_execution_context("snapshot parallel branches")?.variables.clone();
+    let snapshots = children.iter()
+        .map(|_| context.snapshot("snapshot parallel branch"))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let branches = children.iter().zip(snapshots.iter().cloned()).map(|(interceptor, context)| {
+        let semaphore = &semaphore;
+        async move {
             match interceptor {
                 ActiveInterceptor::Executor(executor) => {
-                    result = Some(executor.interceptor.intercept(context.clone(), config, empty_execute_intercept_next()).await?);
+                    let _permit = semaphore.acquire().await
+                        .map_err(|_| LoomError::execution("Parallel executor semaphore closed"))?;
+                    executor.interceptor.intercept(context, &executor.config, empty_execute_intercept_next()).await
                 }
-                _ => {
-                    Err("SequentialExecutor should contain only executor Interceptor".to_string())?;
+                _ => Err(LoomError::execution("ParallelExecutor should contain only executor Interceptor")),
+            }
+        }
+    });
+
+    let outcomes = join_all(branches).await;
+
+    {
+        let mut shared = context.write_execution_context("merge parallel branches")?;
+        for snapshot in &snapshots {
+            let branch = snapshot.read_execution_context("merge parallel branch")?;
+            for (name, value) in &branch.variables {
+                if base_variables.get(name) != Some(value) {
+                    shared.variables.insert(name.clone(), value.clone());
                 }
             }
         }
-        result.ok_or(LoomError::execution("The result of a SequentialExecutor should not be None".to_string()))
-        // context.execution_context.previous_result.take().ok_or("The result of a SequentialExecutor should not be None".to_string())
-        // InterceptorEngine::execute_chain(loom_context, context, hook_registry, &self.0)
     }
 
-    fn need_chain(&self) -> bool {
-        false
+    let mut results = Vec::with_capacity(children.len());
+    let mut errors = Vec::new();
+    for (index, result) in outcomes.into_iter().enumerate() {
+        match result {
+            Ok(result) => results.push(result),
+            Err(error) => errors.push((index, error)),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(LoomError::aggregate("Parallel execution failed", errors));
+    }
+
+    ExecutionResult::combine(results).ok_or(LoomError::execution("The result of a ParallelExecutor should not be None"))
+}
+
+/// Esegue gli executor figli secondo il ParallelizationKind presente nell'ExecutionContext.
+/// Il ParallelizationKind viene consumato da questo livello, così i figli tornano sequenziali;
+/// con un solo figlio viene invece lasciato al livello successivo (es. @parallel su una recipe con un solo block)
+pub(crate) async fn execute_children<'a>(
+    context: InterceptorContext<'a>,
+    children: &'a [ActiveInterceptor],
+) -> InterceptorResult {
+    if children.len() <= 1 {
+        return execute_sequence(context, children).await;
+    }
+
+    let parallelization_kind = std::mem::take(
+        &mut context.write_execution_context("take parallelization kind")?
+            .parallelization_kind
+    );
+
+    match parallelization_kind {
+        ParallelizationKind::Sequential => execute_sequence(context, children).await,
+        ParallelizationKind::Parallel { max_thread } =>
+            execute_concurrently(context, children, max_thread).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::context::LoomContext;
+    use crate::interceptor::executor::ActiveExecutorInterceptor;
+    use crate::interceptor::hook::registry::HookRegistry;
+    use crate::test_support::{execution_context, interceptor_context};
+    use crate::types::{LiteralValue, LoomValue};
+    use super::*;
+
+    /// Imposta la propria working dir, cede il controllo agli altri branch e poi la rilegge
+    struct WorkingDirProbe(&'static str);
+
+    #[async_trait::async_trait]
+    impl ExecutorInterceptor for WorkingDirProbe {
+        fn name(&self) -> &str {
+            "WorkingDirProbe"
+        }
+        fn description(&self) -> &str {
+            "Reads back the working dir it set"
+        }
+        fn default_config(&self) -> ExecutorConfig {
+            ExecutorConfig::default()
+        }
+        async fn intercept<'a>(
+            &'a self,
+            context: InterceptorContext<'a>,
+            _config: &ExecutorConfig,
+            _next: Box<InterceptorChain<'a>>,
+        ) -> InterceptorResult {
+            {
+                let mut execution_context = context.write_execution_context("set working dir")?;
+                execution_context.working_dir = Some(self.0.to_string());
+                execution_context.variables.insert(Arc::from(self.0), LoomValue::Literal(LiteralValue::String(self.0.to_string())));
+            }
+            tokio::task::yield_now().await;
+            let working_dir = context.read_execution_context("read working dir")?.working_dir.clone();
+            Ok(ExecutionResult::new(working_dir, Some(0)))
+        }
+        fn need_chain(&self) -> bool {
+            false
+        }
+    }
+
+    /// Fallisce sempre con il messaggio indicato
+    struct Failing(&'static str);
+
+    #[async_trait::async_trait]
+    impl ExecutorInterceptor for Failing {
+        fn name(&self) -> &str {
+            "Failing"
+        }
+        fn description(&self) -> &str {
+            "Always fails"
+        }
+        fn default_config(&self) -> ExecutorConfig {
+            ExecutorConfig::default()
+        }
+        async fn intercept<'a>(
+            &'a self,
+            _context: InterceptorContext<'a>,
+            _config: &ExecutorConfig,
+            _next: Box<InterceptorChain<'a>>,
+        ) -> InterceptorResult {
+            Err(LoomError::execution(self.0))
+        }
+        fn need_chain(&self) -> bool {
+            false
+        }
+    }
+
+    /// Ritorna sempre lo stesso risultato
+    struct Fixed(ExecutionResult);
+
+    #[async_trait::async_trait]
+    impl ExecutorInterceptor for Fixed {
+        fn name(&self) -> &str {
+            "Fixed"
+        }
+        fn description(&self) -> &str {
+            "Returns a fixed result"
+        }
+        fn default_config(&self) -> ExecutorConfig {
+            ExecutorConfig::default()
+        }
+        async fn intercept<'a>(
+            &'a self,
+            _context: InterceptorContext<'a>,
+            _config: &ExecutorConfig,
+            _next: Box<InterceptorChain<'a>>,
+        ) -> InterceptorResult {
+            Ok(self.0.clone())
+        }
+        fn need_chain(&self) -> bool {
+            false
+        }
     }
-}
\ No newline at end of file
+
+    fn executor(interceptor: Arc<dyn ExecutorInterceptor>) -> ActiveInterceptor {
+        ActiveInterceptor::Executor(ActiveExecutorInterceptor {
+            name: interceptor.name().to_string(),
+            interceptor,
+            config: ExecutorConfig::default(),
+        })
+    }
+
+    fn probe(name: &'static str) -> ActiveInterceptor {
+        executor(Arc::new(WorkingDirProbe(name)))
+    }
+
+    #[tokio::test]
+    async fn parallel_branches_do_not_share_execution_context() {
+        let loom_context = LoomContext::new();
+        let hook_registry = HookRegistry::new();
+        let mut root = execution_context(vec![]);
+        root.working_dir = Some("root".to_string());
+        let context = interceptor_context(&loom_context, &hook_registry, root);
+        let children = [probe("a"), probe("b")];
+
+        let result = execute_concurrently(context.clone(), &children, 2).await.unwrap();
+
+        assert_eq!(result.output.as_deref(), Some("a\nb"));
+        let shared = context.read_execution_context("check").unwrap();
+        assert_eq!(shared.working_dir.as_deref(), Some("root"));
+        assert!(shared.variables.contains_key("a") && shared.variables.contains_key("b"));
+    }
+
+    #[tokio::test]
+    async fn parallel_failures_are_aggregated_with_branch_positions() {
+        let loom_context = LoomContext::new();
+        let hook_registry = HookRegistry::new();
+        let context = interceptor_context(&loom_context, &hook_registry, execution_context(vec![]));
+        let children = [executor(Arc::new(Failing("first"))), probe("ok"), executor(Arc::new(Failing("third")))];
+
+        let error = execute_concurrently(context, &children, 3).await.unwrap_err();
+
+        let LoomError::AggregateError { errors, .. } = error else {
+            panic!("expected an aggregate error, got {:?}", error);
+        };
+        let failures: Vec<_> = errors.iter().map(|(index, error)| (*index, error.to_string())).collect();
+        assert_eq!(failures.len(), 2);
+        assert_eq!((failures[0].0, failures[1].0), (0, 2));
+        assert!(failures[0].1.contains("first") && failures[1].1.contains("third"));
+    }
+
+    #[tokio::test]
+    async fn sequential_results_are_combined_in_order() {
+        let loom_context = LoomContext::new();
+        let hook_registry = HookRegistry::new();
+        let context = interceptor_context(&loom_context, &hook_registry, execution_context(vec![]));
+        let fixed = |output: &str, exit_code: i32, step: &str| executor(Arc::new(Fixed(
+            ExecutionResult::new(Some(output.to_string()), Some(exit_code)).with_metadata("step", step).with_metadata(step, output)
+        )));
+        let sequence = SequentialExecutorInterceptor(vec![fixed("one", 0, "first"), fixed("two", 2, "second"), fixed("three", 0, "third")], "Block".to_string());
+
+        let result = sequence.intercept(context, &ExecutorConfig::default(), empty_execute_intercept_next()).await.unwrap();
+
+        assert_eq!(result.output(), Some("one\ntwo\nthree"));
+        assert_eq!(result.exit_code(), Some(2));
+        assert_eq!(result.metadata().get("step").map(String::as_str), Some("third"));
+        assert_eq!(result.metadata().len(), 4);
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn parallel_commands_respect_the_engine_slots() {
+        use crate::interceptor::executor::implementation::command::CommandExecutorInterceptor;
+        use crate::test_support::string;
+        use crate::types::Position;
+
+        // `mkdir` fallisce se la directory esiste già, cioè se due comandi sono in esecuzione insieme
+        let lock = std::env::temp_dir().join(format!("loom-slots-{}", std::process::id()));
+        let command = format!("mkdir {0} && sleep 0.05 && rmdir {0}", lock.display());
+        let loom_context = LoomContext::new();
+        let hook_registry = HookRegistry::new();
+        let mut context = interceptor_context(&loom_context, &hook_registry, execution_context(vec![]));
+        context.command_slots = Arc::new(Semaphore::new(1));
+        let children: Vec<_> = (0..3)
+            .map(|_| executor(Arc::new(CommandExecutorInterceptor(Arc::from(vec![string(&command)]), Position::default()))))
+            .collect();
+
+        let result = execute_concurrently(context, &children, 3).await;
+
+        let _ = std::fs::remove_dir(&lock);
+        assert_eq!(result.unwrap().exit_code(), Some(0));
+    }
+}
@@ -1,4 +1,4 @@
-use std::ops::{Deref, DerefMut};
+use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
 use log::log;
@@ -6,7 +6,7 @@ use crate::error::LoomError;
 use crate::InputArg;
 use crate::interceptor::{ActiveInterceptor, InterceptorChain, InterceptorResult};
 use crate::interceptor::context::{ExecutionContext, InterceptorContext};
-use crate::interceptor::engine::InterceptorEngine;
+use crate::interceptor::executor::implementation::composable::execute_children;
 use crate::interceptor::executor::config::ExecutorConfig;
 use crate::interceptor::executor::ExecutorInterceptor;
 use crate::types::Position;
@@ -26,7 +26,7 @@ impl ExecutorInterceptor for DefinitionExecutorInterceptor {
     }
     async fn intercept<'a>(
         &'a self,
-        mut context: InterceptorContext<'a>,
+        context: InterceptorContext<'a>,
         // TODO: Queste config mi potrebbero servie a qualcosa in questo livello
         _config: &ExecutorConfig,
         // TODO: Non dovrebbe esistere un NEXT perchè gli executor sono terminali e contengono altri interceptor
@@ -34,31 +34,41 @@ impl ExecutorInterceptor for DefinitionExecutorInterceptor {
     ) -> InterceptorResult {
         // TODO: Aggiungere hooks di "inizio", "fine", "success" e "error" definition
 
-        context.loom_context.find_definition(&self.0).as_ref().unwrap().signature
-            .args_into_variable(
-                context.loom_context,
-                context.execution_context.read()
-                    .map_err(|_| format!("Couldn't borrow"))?
-                    .deref(),
-                &self.2
-            )?.into_iter()
-            .try_for_each::<_, Result<(), String>>(|(variable_name, value)| {
-                // context.execution_context.variables.to_mut().insert(variable_name, value);
-                context.execution_context.write()
-                    .map_err(|_| format!("Couldn't borrow"))?
-                    .deref_mut()
-                    .variables
-                .insert(Arc::<str>::from(variable_name), value);
-                Ok(())
-                // context.execution_context.get_mut().variables.insert(variable_name, value);
-            })?;
+        bind_args(&context, &self.0, &self.2)?;
 
         // next(context, hook_registry)
-        InterceptorEngine::execute_chain(context, &self.1).await
+        execute_children(context, &self.1).await
     }
 
     fn need_chain(&self) -> bool {
         false
     }
 
+    fn children(&self) -> &[ActiveInterceptor] {
+        &self.1
+    }
+
+}
+
+/// Valuta gli argomenti passati alla definition e li inserisce come variabili nell'ExecutionContext
+pub(crate) fn bind_args(context: &InterceptorContext, definition_name: &str, args: &[InputArg]) -> Result<(), LoomError> {
+    let definition = context.loom_context.find_definition(definition_name)
+        .ok_or_else(|| LoomError::definition_resolution(definition_name, "Cannot find definition"))?;
+
+    // Il lock in lettura va rilasciato prima di scrivere le variabili
+    let variables = definition.signature
+        .args_into_variable(
+            context.loom_context,
+            context.read_execution_context("evaluate definition args")?
+                .deref(),
+            args,
+            Some(definition.position.clone())
+        )?;
+
+    let mut execution_context = context.write_execution_context("bind definition args")?;
+    for (variable_name, value) in variables {
+        execution_context.variables.insert(Arc::<str>::from(variable_name), value);
+    }
+
+    Ok(())
 }
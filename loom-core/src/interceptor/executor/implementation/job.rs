@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use crate::event::channel::ExecutionEventKind;
+use crate::InputArg;
+use crate::interceptor::{ActiveInterceptor, InterceptorChain, InterceptorResult};
+use crate::interceptor::context::InterceptorContext;
+use crate::interceptor::executor::config::ExecutorConfig;
+use crate::interceptor::executor::ExecutorInterceptor;
+use crate::interceptor::executor::implementation::composable::execute_children;
+use crate::interceptor::executor::implementation::definition::bind_args;
+
+/// Esegue i blocchi di un job, in sequenza o in parallelo in base al ParallelizationKind
+pub struct JobExecutorInterceptor {
+    pub name: Arc<str>,
+    pub stage: Option<Arc<str>>,
+    pub children: Vec<ActiveInterceptor>,
+    pub args: Vec<InputArg>,
+}
+
+#[async_trait::async_trait]
+impl ExecutorInterceptor for JobExecutorInterceptor {
+    fn name(&self) -> &str {
+        "job"
+    }
+    fn description(&self) -> &str {
+        "Esegue un job"
+    }
+    fn default_config(&self) -> ExecutorConfig {
+        ExecutorConfig::default()
+    }
+    async fn intercept<'a>(
+        &'a self,
+        context: InterceptorContext<'a>,
+        _config: &ExecutorConfig,
+        _next: Box<InterceptorChain<'a>>,
+    ) -> InterceptorResult {
+        let channel = context.channel.clone();
+        let start_time = Instant::now();
+
+        // Gli eventi sono best-effort: un channel chiuso non deve bloccare l'esecuzione
+        let _ = channel.emit_with_context(
+            ExecutionEventKind::JobStarted {
+                job_name: self.name.to_string(),
+                stage_name: self.stage.as_ref().map(|stage| stage.to_string()),
+            },
+            HashMap::new(),
+        );
+
+        let result = match bind_args(&context, &self.name, &self.args) {
+            Ok(()) => execute_children(context, &self.children).await,
+            Err(error) => Err(error),
+        };
+
+        let _ = channel.emit_with_context(
+            ExecutionEventKind::JobCompleted {
+                job_name: self.name.to_string(),
+                success: result.is_ok(),
+                duration_ms: start_time.elapsed().as_millis() as u64,
+            },
+            HashMap::new(),
+        );
+
+        result
+    }
+
+    fn need_chain(&self) -> bool {
+        false
+    }
+
+    fn children(&self) -> &[ActiveInterceptor] {
+        &self.children
+    }
+}
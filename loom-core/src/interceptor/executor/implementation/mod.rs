@@ -2,9 +2,12 @@ use crate::error::LoomError;
 use crate::interceptor::InterceptorChain;
 use crate::interceptor_result;
 
+pub mod assignment;
 pub mod command;
 pub mod composable;
 pub mod definition;
+pub mod job;
+pub mod pipeline;
 
 
 pub fn empty_execute_intercept_next<'a>() -> Box<InterceptorChain<'a>> {
@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use crate::event::channel::ExecutionEventKind;
+use crate::interceptor::{ActiveInterceptor, InterceptorChain, InterceptorResult};
+use crate::interceptor::context::InterceptorContext;
+use crate::interceptor::executor::config::ExecutorConfig;
+use crate::interceptor::executor::ExecutorInterceptor;
+use crate::interceptor::executor::implementation::composable::execute_children;
+
+/// Esegue uno stage di una pipeline, notificando inizio e fine sul channel
+pub struct StageExecutorInterceptor {
+    pub pipeline: Arc<str>,
+    pub name: Arc<str>,
+    pub children: Vec<ActiveInterceptor>,
+}
+
+#[async_trait::async_trait]
+impl ExecutorInterceptor for StageExecutorInterceptor {
+    fn name(&self) -> &str {
+        "stage"
+    }
+    fn description(&self) -> &str {
+        "Esegue uno stage di una pipeline"
+    }
+    fn default_config(&self) -> ExecutorConfig {
+        ExecutorConfig::default()
+    }
+    async fn intercept<'a>(
+        &'a self,
+        context: InterceptorContext<'a>,
+        _config: &ExecutorConfig,
+        _next: Box<InterceptorChain<'a>>,
+    ) -> InterceptorResult {
+        let channel = context.channel.clone();
+        let start_time = Instant::now();
+
+        // Gli eventi sono best-effort: un channel chiuso non deve bloccare l'esecuzione
+        let _ = channel.emit_with_context(
+            ExecutionEventKind::StageStarted {
+                stage_name: self.name.to_string(),
+                pipeline_name: self.pipeline.to_string(),
+            },
+            HashMap::new(),
+        );
+
+        let result = execute_children(context, &self.children).await;
+
+        let _ = channel.emit_with_context(
+            ExecutionEventKind::StageCompleted {
+                stage_name: self.name.to_string(),
+                pipeline_name: self.pipeline.to_string(),
+                success: result.is_ok(),
+                duration_ms: start_time.elapsed().as_millis() as u64,
+            },
+            HashMap::new(),
+        );
+
+        result
+    }
+
+    fn need_chain(&self) -> bool {
+        false
+    }
+
+    fn children(&self) -> &[ActiveInterceptor] {
+        &self.children
+    }
+}
@@ -1,7 +1,8 @@
 use std::sync::Arc;
-use crate::interceptor::context::InterceptorContext;
+use crate::context::LoomContext;
+use crate::interceptor::context::{ExecutionContext, InterceptorContext};
 use crate::interceptor::executor::config::ExecutorConfig;
-use crate::interceptor::{InterceptorChain, InterceptorResult};
+use crate::interceptor::{ActiveInterceptor, InterceptorChain, InterceptorResult};
 
 #[async_trait::async_trait]
 /// Trait per interceptor globali
@@ -25,4 +26,14 @@ pub trait ExecutorInterceptor: Send + Sync {
     
     fn need_chain(&self) -> bool;
 
+    /// Interceptor figli eseguiti da questo executor, usati per ispezionare la chain senza eseguirla
+    fn children(&self) -> &[ActiveInterceptor] {
+        &[]
+    }
+
+    /// Dettaglio leggibile mostrato nel piano di esecuzione (es. il comando risolto)
+    fn plan_detail(&self, _loom_context: &LoomContext, _context: &ExecutionContext) -> Option<String> {
+        None
+    }
+
 }
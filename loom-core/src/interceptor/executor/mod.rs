@@ -1,11 +1,16 @@
 pub mod interceptor;
-mod config;
+pub mod config;
 pub mod implementation;
 
 use std::sync::Arc;
+use crate::ast::Expression;
 use crate::interceptor::executor::config::ExecutorConfig;
 use crate::interceptor::executor::interceptor::ExecutorInterceptor;
 
+/// Crea l'executor terminale di un comando a partire dalle sue parti, già private del prefisso
+/// con cui l'executor è stato registrato (es. `docker:run ubuntu` -> `run ubuntu`)
+pub type ExecutorFactory = Arc<dyn Fn(Arc<[Expression]>) -> Arc<dyn ExecutorInterceptor> + Send + Sync>;
+
 /// Interceptor globale attivo con la sua configurazione
 #[derive(Clone)]
 pub struct ActiveExecutorInterceptor {
@@ -22,4 +27,11 @@ impl ActiveExecutorInterceptor {
             interceptor: executor,
         }
     }
+
+    pub fn with_config(executor: Arc<dyn ExecutorInterceptor>, config: ExecutorConfig) -> Self {
+        Self {
+            config,
+            ..Self::new(executor)
+        }
+    }
 }
\ No newline at end of file
@@ -1,4 +1,8 @@
 use std::collections::HashMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use serde_json::{Map, Value};
+use crate::error::{LoomError, LoomResult};
 use crate::interceptor::global::interceptor::ActivationCondition;
 
 /// Configurazione per interceptor globali
@@ -14,4 +18,135 @@ pub struct GlobalInterceptorConfig {
     pub parameters: HashMap<String, serde_json::Value>,
     /// Se può essere disabilitato dall'utente
     pub user_overridable: bool,
-}
\ No newline at end of file
+}
+
+impl GlobalInterceptorConfig {
+    /// Rappresentazione JSON, con gli stessi nomi dei campi della struct
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "enabled": self.enabled,
+            "priority": self.priority,
+            "conditions": self.conditions.iter().map(ActivationCondition::to_json).collect::<Vec<_>>(),
+            "parameters": self.parameters,
+            "user_overridable": self.user_overridable,
+        })
+    }
+
+    /// Sovrascrive solo i campi presenti nell'oggetto JSON, gli altri restano invariati
+    pub fn merge_json(&mut self, value: &Value) -> LoomResult<()> {
+        let fields = value.as_object()
+            .ok_or_else(|| LoomError::config("Global interceptor config must be a JSON object"))?;
+
+        for (key, value) in fields {
+            match key.as_str() {
+                "enabled" => self.enabled = Self::field(key, value.as_bool())?,
+                "priority" => self.priority = Self::field(key, value.as_i64().and_then(|it| i32::try_from(it).ok()))?,
+                "conditions" => {
+                    self.conditions = Self::field(key, value.as_array())?
+                        .iter()
+                        .map(ActivationCondition::from_json)
+                        .collect::<LoomResult<_>>()?;
+                }
+                "parameters" => {
+                    self.parameters = Self::field(key, value.as_object())?
+                        .iter()
+                        .map(|(name, value)| (name.clone(), value.clone()))
+                        .collect();
+                }
+                "user_overridable" => self.user_overridable = Self::field(key, value.as_bool())?,
+                other => return Err(LoomError::config(format!("Unknown global interceptor config field '{}'", other))),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn field<T>(name: &str, value: Option<T>) -> LoomResult<T> {
+        value.ok_or_else(|| LoomError::config(format!("Invalid value for global interceptor config field '{}'", name)))
+    }
+}
+
+// Serializzazione tramite serde_json::Value: i campi mancanti prendono il valore di default
+impl Serialize for GlobalInterceptorConfig {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GlobalInterceptorConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let mut config = Self::default();
+        config.merge_json(&value).map_err(D::Error::custom)?;
+        Ok(config)
+    }
+}
+
+impl ActivationCondition {
+    /// Rappresentazione JSON come enum esternamente taggato, es. `{"Environment": ["production"]}`
+    pub fn to_json(&self) -> Value {
+        let (tag, value) = match self {
+            ActivationCondition::TargetType(types) => ("TargetType", Value::from(types.clone())),
+            ActivationCondition::Environment(envs) => ("Environment", Value::from(envs.clone())),
+            ActivationCondition::CommandPattern(regex) => ("CommandPattern", Value::from(regex.as_str())),
+            ActivationCondition::Workspace(workspaces) => ("Workspace", Value::from(workspaces.clone())),
+            ActivationCondition::TimeWindow { start, end } => (
+                "TimeWindow",
+                serde_json::json!({ "start": start, "end": end }),
+            ),
+            ActivationCondition::Custom(expression) => ("Custom", Value::from(expression.as_str())),
+        };
+
+        let mut object = Map::new();
+        object.insert(tag.to_string(), value);
+        Value::Object(object)
+    }
+
+    pub fn from_json(value: &Value) -> LoomResult<Self> {
+        let invalid = || LoomError::config(format!("Invalid activation condition: {}", value));
+        let strings = |value: &Value| -> LoomResult<Vec<String>> {
+            value.as_array()
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|item| item.as_str().map(str::to_string).ok_or_else(invalid))
+                .collect()
+        };
+        let string = |value: &Value| value.as_str().map(str::to_string).ok_or_else(invalid);
+
+        let (tag, value) = value.as_object()
+            .filter(|object| object.len() == 1)
+            .and_then(|object| object.iter().next())
+            .ok_or_else(invalid)?;
+
+        match tag.as_str() {
+            "TargetType" => Ok(ActivationCondition::TargetType(strings(value)?)),
+            "Environment" => Ok(ActivationCondition::Environment(strings(value)?)),
+            "CommandPattern" => {
+                let pattern = string(value)?;
+                regex::Regex::new(&pattern)
+                    .map(ActivationCondition::CommandPattern)
+                    .map_err(|e| LoomError::config(format!("Invalid CommandPattern '{}': {}", pattern, e)))
+            }
+            "Workspace" => Ok(ActivationCondition::Workspace(strings(value)?)),
+            "TimeWindow" => Ok(ActivationCondition::TimeWindow {
+                start: string(value.get("start").ok_or_else(invalid)?)?,
+                end: string(value.get("end").ok_or_else(invalid)?)?,
+            }),
+            "Custom" => Ok(ActivationCondition::Custom(string(value)?)),
+            other => Err(LoomError::config(format!("Unknown activation condition '{}'", other))),
+        }
+    }
+}
+
+impl Serialize for ActivationCondition {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ActivationCondition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        Self::from_json(&value).map_err(D::Error::custom)
+    }
+}
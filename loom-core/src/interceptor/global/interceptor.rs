@@ -35,11 +35,11 @@ pub trait GlobalInterceptor: Send + Sync {
     }
 
     /// Intercetta l'esecuzione (stesso pattern degli interceptor normali)
-    async fn intercept(
-        &self,
-        context: InterceptorContext<'_>,
+    async fn intercept<'a>(
+        &'a self,
+        context: InterceptorContext<'a>,
         config: &GlobalInterceptorConfig,
-        next: Box<InterceptorChain<'_>>,
+        next: Box<InterceptorChain<'a>>,
     ) -> InterceptorResult;
 
     /// Valuta una condizione di attivazione
@@ -62,12 +62,8 @@ pub trait GlobalInterceptor: Send + Sync {
                 types.contains(&target_type.to_string())
             }
             ActivationCondition::Environment(envs) => {
-                const DEVELOPMENT: &str = "development";
-                let current_env = context.env_vars.get("LOOM_ENV").map(|it| it.as_str())
-                    .or_else(|| context.env_vars.get("ENVIRONMENT").map(|it| it.as_str()))
-                .unwrap_or(DEVELOPMENT)
-                    .to_string();
-                envs.contains(&current_env)
+                let current_env = context.current_environment();
+                envs.iter().any(|env| env == current_env)
             }
             ActivationCondition::CommandPattern(regex) => {
                 // if let ExecutionActivity::Command (c) = &context.target {
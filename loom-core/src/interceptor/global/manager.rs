@@ -1,8 +1,9 @@
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 use crate::error::{LoomError, LoomResult};
 use crate::interceptor::context::ExecutionContext;
-use crate::interceptor::global::ActiveGlobalInterceptor;
+use crate::interceptor::global::{ActiveGlobalInterceptor, GlobalInterceptorCategory};
 use crate::interceptor::global::config::GlobalInterceptorConfig;
 use crate::interceptor::global::interceptor::GlobalInterceptor;
 use crate::interceptor::priority::PriorityRanges;
@@ -36,6 +37,31 @@ impl GlobalInterceptorManager {
         Ok(())
     }
 
+    /// Rimuove un interceptor insieme alla sua configurazione e all'eventuale override utente
+    pub fn unregister(&mut self, name: &str) -> LoomResult<()> {
+        if self.interceptors.remove(name).is_none() {
+            return loom_error!("Global interceptor '{}' not found", name);
+        }
+
+        self.configs.remove(name);
+        self.user_overrides.remove(name);
+        Ok(())
+    }
+
+    /// Interceptor registrati con categoria e priorità configurata, ordinati per priorità decrescente
+    pub fn list(&self) -> Vec<(String, GlobalInterceptorCategory, i32)> {
+        let mut result: Vec<_> = self.interceptors.iter()
+            .map(|(name, interceptor)| (
+                name.clone(),
+                interceptor.category(),
+                self.configs.get(name).map_or(0, |config| config.priority),
+            ))
+            .collect();
+
+        result.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        result
+    }
+
     pub fn configure(&mut self, name: &str, config: GlobalInterceptorConfig) -> LoomResult<()> {
         if !self.interceptors.contains_key(name) {
             return loom_error!("Global interceptor '{}' not found", name);
@@ -46,6 +72,37 @@ impl GlobalInterceptorManager {
         Ok(())
     }
 
+    /// Applica le configurazioni lette da un file JSON nella forma `{ "nome_interceptor": { ...config } }`.
+    /// Ogni config sovrascrive solo i campi indicati; se un interceptor non esiste o una priorità
+    /// non è valida non viene applicata nessuna configurazione
+    pub fn apply_config_file(&mut self, path: impl AsRef<Path>) -> LoomResult<()> {
+        let path = path.as_ref();
+        let display_path = path.display().to_string();
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| LoomError::io_with_path(e.to_string(), display_path.clone()))?;
+        let document: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| LoomError::config_with_path(e.to_string(), display_path.clone()))?;
+        let entries = document.as_object()
+            .ok_or_else(|| LoomError::config_with_path("Expected a map of interceptor name to config", display_path.clone()))?;
+
+        let mut updated = Vec::with_capacity(entries.len());
+        for (name, value) in entries {
+            let mut config = self.configs.get(name)
+                .cloned()
+                .ok_or_else(|| LoomError::config_with_path(format!("Global interceptor '{}' not found", name), display_path.clone()))?;
+
+            config.merge_json(value)
+                .and_then(|_| self.validate_global_priority(config.priority))
+                .map_err(|e| LoomError::config_with_path(format!("Invalid config for '{}': {}", name, e), display_path.clone()))?;
+
+            updated.push((name.clone(), config));
+        }
+
+        self.configs.extend(updated);
+        Ok(())
+    }
+
     pub fn set_user_override(&mut self, name: &str, enabled: bool) -> LoomResult<()> {
         let config = self.configs.get(name)
             .ok_or_else(|| LoomError::execution(format!("Global interceptor '{}' not found", name)))?;
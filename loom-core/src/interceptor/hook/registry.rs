@@ -5,6 +5,9 @@ use crate::interceptor::hook::{HookHandler, HookPayload};
 use crate::interceptor::result::HookResult;
 use crate::interceptor::scope::ExecutionHook;
 
+/// Chiave dei metadata in cui viene registrato il numero massimo di tentativi richiesto da `HookResult::Retry`
+pub const RETRY_MAX_KEY: &str = "retry_max";
+
 // TODO: Aggiungere meglio hook a interceptor e vedere se necessario una interazione finale una volta finita la chain.
 
 /// Registry per hook handlers
@@ -51,7 +54,7 @@ impl HookRegistry {
                         return Err(reason);
                     }
                     HookResult::Retry { max_attempts } => {
-                        context.metadata.insert("retry_max".to_string(), max_attempts.to_string());
+                        context.metadata.insert(RETRY_MAX_KEY.to_string(), max_attempts.to_string());
                     }
                 }
             }
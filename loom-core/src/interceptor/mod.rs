@@ -12,6 +12,8 @@ use crate::interceptor::hook::registry::HookRegistry;
 use crate::interceptor::result::ExecutionResult;
 
 pub mod context;
+pub mod cancellation;
+pub mod env_file;
 pub mod result;
 pub mod directive;
 pub mod global;
@@ -20,12 +22,16 @@ pub mod engine;
 pub mod hook;
 pub mod executor;
 pub mod priority;
+pub mod plan;
+pub mod schedule;
 
 /// **LoomContext**:        The general context with every, enum, definition, variable...
 /// **ExecutionContext**:   The context for the current execution, it's mutable.
 /// **HookRegistry**:       The registry with all the hooks.
-pub type InterceptorChain<'a> = dyn FnOnce(InterceptorContext<'a>)
-    -> Pin<Box<dyn Future<Output = InterceptorResult> + Send + 'a>> + Send + 'a;
+///
+/// The chain can be invoked more than once (e.g. by loop directives like `@for`).
+pub type InterceptorChain<'a> = dyn Fn(InterceptorContext<'a>)
+    -> Pin<Box<dyn Future<Output = InterceptorResult> + Send + 'a>> + Send + Sync + 'a;
 
 pub type InterceptorResult = LoomResult<ExecutionResult>;
 
@@ -0,0 +1,128 @@
+use serde_json::{json, Value};
+use crate::context::LoomContext;
+use crate::interceptor::ActiveInterceptor;
+use crate::interceptor::context::ExecutionContext;
+use crate::interceptor::scope::ExecutionScope;
+
+/// Piano di esecuzione di una definition: la chain che verrebbe eseguita, senza eseguirla
+#[derive(Debug, Clone)]
+pub struct ExecutionPlan {
+    pub definition: String,
+    pub scope: ExecutionScope,
+    pub chain: Vec<PlanNode>,
+}
+
+/// Singolo interceptor della chain, con gli eventuali interceptor figli dell'executor
+#[derive(Debug, Clone)]
+pub struct PlanNode {
+    pub name: String,
+    pub interceptor_type: String,
+    pub priority: i32,
+    /// Dettaglio leggibile (es. il comando risolto), se l'interceptor lo espone
+    pub detail: Option<String>,
+    pub children: Vec<PlanNode>,
+}
+
+impl ExecutionPlan {
+    pub(crate) fn from_chain(
+        loom_context: &LoomContext,
+        context: &ExecutionContext,
+        definition: &str,
+        chain: &[ActiveInterceptor],
+    ) -> Self {
+        Self {
+            definition: definition.to_string(),
+            scope: context.scope,
+            chain: PlanNode::from_chain(loom_context, context, chain),
+        }
+    }
+
+    /// Albero JSON del piano, con nomi di campo stabili per visualizzazione o diff esterni
+    pub fn to_json(&self) -> Value {
+        json!({
+            "definition": self.definition,
+            "scope": format!("{:?}", self.scope),
+            "chain": self.chain.iter().map(PlanNode::to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Tutti i nodi del piano in pre-ordine
+    pub fn nodes(&self) -> Vec<&PlanNode> {
+        let mut nodes = Vec::new();
+        let mut stack: Vec<&PlanNode> = self.chain.iter().rev().collect();
+        while let Some(node) = stack.pop() {
+            nodes.push(node);
+            stack.extend(node.children.iter().rev());
+        }
+        nodes
+    }
+}
+
+impl PlanNode {
+    fn from_chain(
+        loom_context: &LoomContext,
+        context: &ExecutionContext,
+        chain: &[ActiveInterceptor],
+    ) -> Vec<PlanNode> {
+        chain.iter()
+            .map(|interceptor| {
+                let (detail, children) = match interceptor {
+                    ActiveInterceptor::Executor(executor) => (
+                        executor.interceptor.plan_detail(loom_context, context),
+                        Self::from_chain(loom_context, context, executor.interceptor.children()),
+                    ),
+                    _ => (None, Vec::new()),
+                };
+
+                PlanNode {
+                    name: interceptor.name().to_string(),
+                    interceptor_type: interceptor.interceptor_type().to_string(),
+                    priority: interceptor.priority(),
+                    detail,
+                    children,
+                }
+            })
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "type": self.interceptor_type,
+            "priority": self.priority,
+            "detail": self.detail,
+            "children": self.children.iter().map(PlanNode::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interceptor::engine::InterceptorEngine;
+    use crate::test_support::{echo, recipe};
+    use super::*;
+
+    #[test]
+    fn plan_json_lists_commands_and_executors() {
+        let loom_context = LoomContext::with_definitions(vec![recipe("build", vec![echo("a", vec![]), echo("b", vec![])])]);
+
+        let json = InterceptorEngine::new().plan(&loom_context, "build", &[]).unwrap().to_json();
+
+        // Foglie dell'albero: gli executor terminali, in ordine di esecuzione
+        let mut leaves = Vec::new();
+        let mut stack: Vec<&Value> = json["chain"].as_array().unwrap().iter().rev().collect();
+        while let Some(node) = stack.pop() {
+            let children = node["children"].as_array().unwrap();
+            if children.is_empty() {
+                leaves.push((node["name"].as_str(), node["type"].as_str(), node["detail"].as_str()));
+            }
+            stack.extend(children.iter().rev());
+        }
+
+        assert_eq!(json["definition"], "build");
+        assert_eq!(leaves, [
+            (Some("command"), Some("executor"), Some("echo a")),
+            (Some("command"), Some("executor"), Some("echo b")),
+        ]);
+    }
+}
@@ -1,10 +1,152 @@
 use std::collections::HashMap;
 
+/// Esito di un'esecuzione, distinto dal risultato del comando
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ExecutionStatus {
+    #[default]
+    Executed,
+    /// Il target non è stato eseguito per scelta (es. condizione di @if falsa)
+    Skipped { reason: String },
+    /// Il target è stato fermato da un hook o da una direttiva bloccante
+    Blocked { reason: String },
+}
+
 #[derive(Debug, Clone)]
 pub struct ExecutionResult {
     pub(crate) output: Option<String>,
+    pub(crate) stderr: Option<String>,
     pub(crate) exit_code: Option<i32>,
     pub(crate) metadata: HashMap<String, String>,
+    pub(crate) status: ExecutionStatus,
+}
+
+impl ExecutionResult {
+    /// Crea un risultato con output ed exit code, senza stderr né metadata
+    pub fn new(output: Option<String>, exit_code: Option<i32>) -> Self {
+        Self {
+            output,
+            stderr: None,
+            exit_code,
+            metadata: HashMap::new(),
+            status: ExecutionStatus::Executed,
+        }
+    }
+
+    pub fn with_stderr(mut self, stderr: Option<String>) -> Self {
+        self.stderr = stderr;
+        self
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Risultato di un'esecuzione saltata (es. condizione di @if falsa)
+    pub fn skipped(reason: impl Into<String>) -> Self {
+        Self {
+            status: ExecutionStatus::Skipped { reason: reason.into() },
+            ..Self::new(None, None)
+        }
+    }
+
+    /// Risultato di un'esecuzione bloccata (es. hook che risponde `HookResult::Block`)
+    pub fn blocked(reason: impl Into<String>) -> Self {
+        Self {
+            status: ExecutionStatus::Blocked { reason: reason.into() },
+            ..Self::new(None, None)
+        }
+    }
+
+    /// Standard output del comando, se presente
+    pub fn output(&self) -> Option<&str> {
+        self.output.as_deref()
+    }
+
+    /// Standard error del comando, se presente
+    pub fn stderr(&self) -> Option<&str> {
+        self.stderr.as_deref()
+    }
+
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    pub fn metadata(&self) -> &HashMap<String, String> {
+        &self.metadata
+    }
+
+    pub fn status(&self) -> &ExecutionStatus {
+        &self.status
+    }
+
+    /// Indica se l'esecuzione è stata saltata
+    pub fn is_skipped(&self) -> bool {
+        matches!(self.status, ExecutionStatus::Skipped { .. })
+    }
+
+    /// Indica se l'esecuzione è stata bloccata
+    pub fn is_blocked(&self) -> bool {
+        matches!(self.status, ExecutionStatus::Blocked { .. })
+    }
+
+    /// Unisce i risultati di più executor mantenendo l'ordine ricevuto:
+    /// - output e stderr sono concatenati, separati da un a capo se il precedente non termina già con uno
+    /// - i metadata sono uniti in ordine, quindi a parità di chiave vince l'ultimo
+    /// - l'exit code è l'ultimo diverso da 0, altrimenti 0
+    ///
+    /// I risultati saltati sono no-op: non contribuiscono ai metadata né allo status, a meno che non lo siano tutti.
+    /// Se un risultato è bloccato, lo status unito è il primo blocco
+    pub fn combine(results: Vec<ExecutionResult>) -> Option<ExecutionResult> {
+        if results.is_empty() {
+            return None;
+        }
+
+        let concat = |select: fn(&ExecutionResult) -> Option<&String>| {
+            let mut joined: Option<String> = None;
+            for part in results.iter().filter_map(select) {
+                match joined.as_mut() {
+                    Some(text) => {
+                        if !text.is_empty() && !text.ends_with('\n') {
+                            text.push('\n');
+                        }
+                        text.push_str(part);
+                    }
+                    None => joined = Some(part.clone()),
+                }
+            }
+            joined
+        };
+        let output = concat(|result| result.output.as_ref());
+        let stderr = concat(|result| result.stderr.as_ref());
+
+        let exit_code = results.iter()
+            .filter_map(|result| result.exit_code)
+            .fold(None, |last, code| match (last, code) {
+                (Some(last), 0) => Some(last),
+                _ => Some(code),
+            });
+
+        let all_skipped = results.iter().all(ExecutionResult::is_skipped);
+        let mut metadata = HashMap::new();
+        for result in results.iter().filter(|result| all_skipped || !result.is_skipped()) {
+            metadata.extend(result.metadata.iter().map(|(key, value)| (key.clone(), value.clone())));
+        }
+
+        let status = results.iter()
+            .find(|result| result.is_blocked())
+            .or_else(|| results.first().filter(|_| all_skipped))
+            .map(|result| result.status.clone())
+            .unwrap_or_default();
+
+        Some(ExecutionResult {
+            output,
+            stderr,
+            exit_code,
+            metadata,
+            status,
+        })
+    }
 }
 
 /// Risultato di un hook
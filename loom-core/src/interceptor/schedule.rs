@@ -0,0 +1,137 @@
+use std::time::SystemTime;
+use chrono::{DateTime, Local};
+use croner::Cron;
+use croner::parser::{CronParser, Seconds, Year};
+use crate::ast::DirectiveCall;
+use crate::context::LoomContext;
+use crate::definition::ArgDefinition;
+use crate::error::{LoomError, LoomResult};
+use crate::interceptor::context::ExecutionContext;
+
+/// Direttiva che definisce un'espressione cron: `@cron("0 2 * * *")`
+pub const CRON_DIRECTIVE: &str = "cron";
+/// Direttiva che definisce un'esecuzione singola: `@at("2025-01-01T02:00:00+01:00")`
+pub const AT_DIRECTIVE: &str = "at";
+
+/// Quando deve scattare uno schedule
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleTrigger {
+    Cron(CronExpression),
+    At(DateTime<Local>),
+}
+
+impl ScheduleTrigger {
+    /// Indica se la direttiva è una di quelle che definiscono il trigger (e non un interceptor)
+    pub fn is_trigger_directive(name: &str) -> bool {
+        name == CRON_DIRECTIVE || name == AT_DIRECTIVE
+    }
+
+    /// Legge il trigger dalle direttive dello schedule; None se non è presente né `@cron` né `@at`
+    pub fn from_directives(
+        loom_context: &LoomContext,
+        context: &ExecutionContext,
+        directives: &[DirectiveCall],
+    ) -> LoomResult<Option<Self>> {
+        let mut triggers = directives.iter()
+            .filter(|directive| Self::is_trigger_directive(&directive.name));
+
+        let Some(directive) = triggers.next() else {
+            return Ok(None);
+        };
+        if triggers.next().is_some() {
+            return Err(LoomError::config("A schedule accepts only one @cron or @at directive"));
+        }
+
+        let value = Self::directive_value(loom_context, context, directive)?;
+        match directive.name.as_ref() {
+            CRON_DIRECTIVE => Ok(Some(ScheduleTrigger::Cron(CronExpression::parse(&value)?))),
+            _ => DateTime::parse_from_rfc3339(&value)
+                .map(|date| Some(ScheduleTrigger::At(date.with_timezone(&Local))))
+                .map_err(|e| LoomError::config(format!("Invalid @at date '{}': {}", value, e))),
+        }
+    }
+
+    /// Prossima esecuzione successiva a `after`
+    pub fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        let after = DateTime::<Local>::from(after);
+        match self {
+            ScheduleTrigger::Cron(cron) => cron.next_after(&after).map(SystemTime::from),
+            ScheduleTrigger::At(date) => (*date > after).then(|| SystemTime::from(*date)),
+        }
+    }
+
+    fn directive_value(
+        loom_context: &LoomContext,
+        context: &ExecutionContext,
+        directive: &DirectiveCall,
+    ) -> LoomResult<String> {
+        let expression = match directive.args.as_ref() {
+            [ArgDefinition::Positional(expression)] => expression,
+            [ArgDefinition::Named { value, .. }] => value,
+            _ => return Err(LoomError::config(format!("@{} expects a single argument", directive.name))),
+        };
+
+        expression.evaluate(loom_context, context, None)?
+            .stringify(loom_context, context)
+    }
+}
+
+/// Espressione cron standard a 5 campi: minuto, ora, giorno del mese, mese, giorno della settimana.
+/// Il parsing e il calcolo delle esecuzioni sono delegati a `croner`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpression {
+    source: String,
+    // In un Box: `Cron` è molto più grande delle altre varianti di ScheduleTrigger
+    cron: Box<Cron>,
+}
+
+impl CronExpression {
+    pub fn parse(source: &str) -> LoomResult<Self> {
+        let parser = CronParser::builder()
+            .seconds(Seconds::Disallowed)
+            .year(Year::Disallowed)
+            .build();
+        let cron = parser.parse(source)
+            .map_err(|e| LoomError::config(format!("Invalid cron expression '{}': {}", source, e)))?;
+
+        Ok(Self { source: source.to_string(), cron: Box::new(cron) })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Primo istante strettamente successivo a `after` che soddisfa l'espressione
+    pub fn next_after(&self, after: &DateTime<Local>) -> Option<DateTime<Local>> {
+        self.cron.find_next_occurrence(after, false).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, TimeZone, Timelike, Weekday};
+    use super::*;
+
+    #[test]
+    fn restricted_day_of_month_and_day_of_week_match_either() {
+        // Giorni dispari del mese oppure lunedì
+        let cron = CronExpression::parse("30 2 */2 * 1").unwrap();
+        // Sabato 10 ottobre 2026: la prossima è domenica 11 alle 02:30, giorno dispari
+        let after = Local.with_ymd_and_hms(2026, 10, 10, 12, 0, 0).unwrap();
+        let next = cron.next_after(&after).unwrap();
+        assert_eq!((next.day(), next.hour(), next.minute()), (11, 2, 30));
+
+        // Lunedì 12 è un giorno pari ma corrisponde il giorno della settimana
+        let next = cron.next_after(&next).unwrap();
+        assert_eq!((next.day(), next.weekday()), (12, Weekday::Mon));
+        assert_eq!(cron.next_after(&next).unwrap().day(), 13);
+    }
+
+    #[test]
+    fn invalid_expressions_are_config_errors() {
+        for source in ["* * * *", "0 0 * * * *", "61 * * * *", "*/0 * * * *", "a b c d e"] {
+            let error = CronExpression::parse(source).unwrap_err();
+            assert!(matches!(error, LoomError::ConfigError { .. }), "{}: {:?}", source, error);
+        }
+    }
+}
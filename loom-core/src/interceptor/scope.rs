@@ -1,8 +1,10 @@
 use crate::ast::{Definition, DirectiveCall, Expression, Statement};
 use crate::context::LoomContext;
+use crate::definition::ArgDefinition;
 use crate::error::{LoomError, LoomResult};
 use crate::interceptor::context::ExecutionContext;
-use crate::types::DefinitionKind;
+use crate::InputArg;
+use crate::types::{DefinitionKind, Position};
 use std::sync::Arc;
 
 /// Quando una direttiva viene eseguita nel ciclo di vita
@@ -42,8 +44,9 @@ pub enum DirectiveScope {
 /// ExecutionActivity ottimizzata con Arc per evitare clone pesanti
 #[derive(Debug, Clone)]
 pub enum ExecutionActivity {
-    // Terminale - usa Arc per Statement condiviso
-    Command(Arc<Statement>),
+    // Terminale - usa Arc per Statement condiviso, insieme alle direttive del block padre
+    // (il motore applica al comando solo quelle a cascata)
+    Command(Arc<Statement>, Arc<[DirectiveCall]>),
 
     // Block con Arc per evitare clone
     Block(Arc<BlockTarget>),
@@ -55,19 +58,28 @@ pub enum ExecutionActivity {
         stages: Arc<[BlockTarget]>,
     },
 
-    Stage(Arc<BlockTarget>),
+    Stage {
+        pipeline: Arc<str>,
+        name: Arc<str>,
+        stage: Arc<BlockTarget>,
+    },
 
     // Job ottimizzato
     Job {
         name: Arc<str>,
         directives: Arc<[DirectiveCall]>,
         blocks: Arc<[BlockTarget]>,
+        // Stage di appartenenza, se il job è eseguito all'interno di una pipeline
+        stage: Option<Arc<str>>,
+        // Argomenti passati dallo stage al job
+        args: Arc<[InputArg]>,
     },
 
     // Schedule ottimizzato
     Schedule {
         name: Arc<str>,
-        directives: Arc<[DirectiveCall]>
+        directives: Arc<[DirectiveCall]>,
+        blocks: Arc<[BlockTarget]>,
     },
 
     // Definition ottimizzata
@@ -112,6 +124,7 @@ impl From<&Definition> for ExecutionActivity {
                 directives: block.directives.clone(),
                 commands: block.statements.clone(),
                 label: block.label.clone(),
+                position: value.position.clone(),
             })
             .collect::<Vec<_>>()
             .into();
@@ -129,6 +142,8 @@ impl From<&Definition> for ExecutionActivity {
                     name: name_arc,
                     directives: directives_arc,
                     blocks: blocks_arc,
+                    stage: None,
+                    args: Arc::new([]),
                 }
             }
             DefinitionKind::Pipeline => {
@@ -142,6 +157,7 @@ impl From<&Definition> for ExecutionActivity {
                 ExecutionActivity::Schedule {
                     name: name_arc,
                     directives: directives_arc,
+                    blocks: blocks_arc,
                 }
             }
         }
@@ -149,67 +165,100 @@ impl From<&Definition> for ExecutionActivity {
 }
 
 impl ExecutionActivity {
+    /// Risolve il job referenziato da uno stage, convertendo gli argomenti posizionali
+    fn stage_job(
+        loom_context: &LoomContext,
+        name: &str,
+        stage_name: &Arc<str>,
+        args: &[ArgDefinition],
+    ) -> LoomResult<ExecutionActivity> {
+        let job_definition = loom_context.find_definition(name)
+            .filter(|definition| definition.kind == DefinitionKind::Job)
+            .ok_or_else(|| LoomError::definition_resolution(name, "Cannot find Job"))?;
+
+        // Usa Arc per evitare clone delle parti pesanti
+        let blocks: Arc<[BlockTarget]> = job_definition.body.iter()
+            .map(|block| BlockTarget {
+                directives: block.directives.clone(),
+                commands: block.statements.clone(),
+                label: block.label.clone(),
+                position: job_definition.position.clone(),
+            })
+            .collect::<Vec<_>>()
+            .into();
+
+        Ok(ExecutionActivity::Job {
+            name: name.into(),
+            directives: job_definition.directives.clone(),
+            blocks,
+            stage: Some(stage_name.clone()),
+            args: job_definition.signature.bind_call_args(args)?.into(),
+        })
+    }
+
     /// Build child activities - DRASTICAMENTE ottimizzato per evitare clone
     pub fn build_child(&self, loom_context: &LoomContext, context: &ExecutionContext) -> LoomResult<Vec<ExecutionActivity>> {
         match self {
-            ExecutionActivity::Command(_) => Ok(Vec::new()),
+            ExecutionActivity::Command(..) => Ok(Vec::new()),
 
             ExecutionActivity::Block(block) => {
                 // Usa iterator e map invece di collect + clone
                 let activities: Vec<ExecutionActivity> = block.commands.iter()
-                    .map(|stmt| ExecutionActivity::Command(Arc::new(stmt.clone()))) // Solo questo clone è inevitabile per ora
+                    .map(|stmt| ExecutionActivity::Command(Arc::new(stmt.clone()), block.directives.clone())) // Solo questo clone è inevitabile per ora
                     .collect();
                 Ok(activities)
             }
 
-            ExecutionActivity::Stage(stage) => {
+            ExecutionActivity::Stage { name: stage_name, stage, .. } => {
                 // Pre-alloca con capacità nota
                 let mut activities = Vec::with_capacity(stage.commands.len());
 
                 for statement in stage.commands.iter() {
-                    match statement {
-                        Statement::Command { parts, .. } => {
+                    let activity = match statement {
+                        // Un job referenziato per nome come comando
+                        Statement::Command { parts, position, .. } => {
                             // Evaluation efficace evitando cloni temporanei
                             let name = parts.iter()
                                 .map(|expr| {
-                                    expr.evaluate(loom_context, context, Default::default())
+                                    expr.evaluate(loom_context, context, Some(position.clone()))
                                         .and_then(|val| val.stringify(loom_context, context))
                                 })
                                 .collect::<LoomResult<Vec<_>>>()?
                                 .join("");
 
-                            let job_definition = loom_context.find_definition(&name)
-                                .ok_or_else(|| LoomError::definition_resolution(&name, "Cannot find Job"))?;
-
-                            // Usa Arc per evitare clone delle parti pesanti
-                            let blocks: Arc<[BlockTarget]> = job_definition.body.iter()
-                                .map(|block| BlockTarget {
-                                    directives: block.directives.clone(),
-                                    commands: block.statements.clone(),
-                                    label: block.label.clone(),
-                                })
-                                .collect::<Vec<_>>()
-                                .into();
-
-                            activities.push(ExecutionActivity::Job {
-                                name: name.into(),
-                                directives: job_definition.directives.clone(),
-                                blocks,
-                            });
+                            Self::stage_job(loom_context, &name, stage_name, &[])?
+                        }
+                        // Un job chiamato con i suoi argomenti
+                        Statement::Call { name, args, .. } => {
+                            Self::stage_job(loom_context, name, stage_name, args)?
+                        }
+                        Statement::Assignment { position, .. } => {
+                            return Err(LoomError::validation_at(
+                                format!("Assignments are not allowed in stage '{}', only jobs can be referenced", stage_name),
+                                position.clone()
+                            ));
                         }
-                        _ => return Err(LoomError::execution("Tipo di statement non previsto per uno stage!"))
-                    }
+                    };
+                    activities.push(activity);
                 }
 
                 Ok(activities)
             }
 
-            ExecutionActivity::Pipeline { stages, .. } => {
-                // Map diretto senza clone intermedio
-                let activities: Vec<ExecutionActivity> = stages.iter()
-                    .map(|stage| ExecutionActivity::Stage(Arc::new(stage.clone()))) // Clone minimale
-                    .collect();
-                Ok(activities)
+            ExecutionActivity::Pipeline { name, stages, .. } => {
+                // Il nome dello stage è la sua label, altrimenti la posizione nella pipeline
+                stages.iter()
+                    .enumerate()
+                    .map(|(index, stage)| {
+                        let stage_name = stage.label_name(loom_context, context)?
+                            .unwrap_or_else(|| format!("stage-{}", index + 1));
+                        Ok(ExecutionActivity::Stage {
+                            pipeline: name.clone(),
+                            name: stage_name.into(),
+                            stage: Arc::new(stage.clone()), // Clone minimale
+                        })
+                    })
+                    .collect()
             }
 
             ExecutionActivity::Job { blocks, .. } => {
@@ -220,7 +269,13 @@ impl ExecutionActivity {
                 Ok(activities)
             }
 
-            ExecutionActivity::Schedule { .. } => Ok(Vec::new()),
+            ExecutionActivity::Schedule { blocks, .. } => {
+                // Il corpo dello schedule viene eseguito come quello di una definition
+                let activities: Vec<ExecutionActivity> = blocks.iter()
+                    .map(|block| ExecutionActivity::Block(Arc::new(block.clone())))
+                    .collect();
+                Ok(activities)
+            }
 
             ExecutionActivity::Definition { blocks, .. } => {
                 // Map diretto
@@ -239,6 +294,8 @@ pub struct BlockTarget {
     pub directives: Arc<[DirectiveCall]>,
     pub commands: Arc<[Statement]>,
     pub label: Arc<[Expression]>,
+    // Posizione della definition che contiene il block
+    pub position: Position,
 }
 
 impl Default for BlockTarget {
@@ -247,6 +304,7 @@ impl Default for BlockTarget {
             directives: Arc::new([]),
             commands: Arc::new([]),
             label: Arc::new([]),
+            position: Position::default(),
         }
     }
 }
@@ -294,9 +352,15 @@ impl BlockTarget {
             directives: directives.into(),
             commands: commands.into(),
             label: label.into(),
+            position: Position::default(),
         }
     }
 
+    pub fn with_position(mut self, position: Position) -> Self {
+        self.position = position;
+        self
+    }
+
     pub fn empty() -> Self {
         Self::default()
     }
@@ -306,7 +370,25 @@ impl BlockTarget {
             directives: Arc::new([]),
             commands: commands.into(),
             label: Arc::new([]),
+            position: Position::default(),
+        }
+    }
+
+    /// Valuta la label del block, None se il block non ha label
+    pub fn label_name(&self, loom_context: &LoomContext, context: &ExecutionContext) -> LoomResult<Option<String>> {
+        if self.label.is_empty() {
+            return Ok(None);
         }
+
+        let name = self.label.iter()
+            .map(|expr| {
+                expr.evaluate(loom_context, context, Some(self.position.clone()))
+                    .and_then(|val| val.stringify(loom_context, context))
+            })
+            .collect::<LoomResult<Vec<_>>>()?
+            .join("");
+
+        Ok(Some(name))
     }
 }
 
@@ -328,6 +410,7 @@ impl ExecutionActivity {
     pub fn name(&self) -> Option<&str> {
         match self {
             ExecutionActivity::Pipeline { name, .. } => Some(name.as_ref()),
+            ExecutionActivity::Stage { name, .. } => Some(name.as_ref()),
             ExecutionActivity::Job { name, .. } => Some(name.as_ref()),
             ExecutionActivity::Schedule { name, .. } => Some(name.as_ref()),
             ExecutionActivity::Definition { name, .. } => Some(name.as_ref()),
@@ -342,23 +425,23 @@ impl ExecutionActivity {
             ExecutionActivity::Schedule { directives, .. } => Some(directives.as_ref()),
             ExecutionActivity::Definition { directives, .. } => Some(directives.as_ref()),
             ExecutionActivity::Block(block) => Some(block.directives.as_ref()),
-            ExecutionActivity::Stage(stage) => Some(stage.directives.as_ref()),
+            ExecutionActivity::Stage { stage, .. } => Some(stage.directives.as_ref()),
             _ => None,
         }
     }
 
     pub fn is_terminal(&self) -> bool {
-        matches!(self, ExecutionActivity::Command(_))
+        matches!(self, ExecutionActivity::Command(..))
     }
 
     pub fn children_count(&self) -> usize {
         match self {
-            ExecutionActivity::Command(_) => 0,
+            ExecutionActivity::Command(..) => 0,
             ExecutionActivity::Block(block) => block.commands.len(),
             ExecutionActivity::Pipeline { stages, .. } => stages.len(),
-            ExecutionActivity::Stage(stage) => stage.commands.len(),
+            ExecutionActivity::Stage { stage, .. } => stage.commands.len(),
             ExecutionActivity::Job { blocks, .. } => blocks.len(),
-            ExecutionActivity::Schedule { .. } => 0,
+            ExecutionActivity::Schedule { blocks, .. } => blocks.len(),
             ExecutionActivity::Definition { blocks, .. } => blocks.len(),
         }
     }
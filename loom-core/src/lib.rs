@@ -4,12 +4,19 @@ pub mod types;
 pub mod ast;
 pub mod context;
 pub mod error;
+pub mod diagnostic;
 pub mod definition;
 pub mod interceptor;
 pub mod event;
+pub mod workflow;
 
-#[derive(Clone)]
+#[cfg(test)]
+mod test_support;
+
+#[derive(Debug, Clone)]
 pub struct InputArg {
     name: String,
     value: Option<Expression>,
+    /// Argomenti in eccesso raccolti dal parametro varargs
+    varargs: Vec<Expression>,
 }
\ No newline at end of file
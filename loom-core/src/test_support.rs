@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::Semaphore;
+use crate::ast::{Block, Definition, DirectiveCall, Expression, Statement};
+use crate::definition::ArgDefinition;
+use crate::context::LoomContext;
+use crate::event::channel::ExecutionEventChannel;
+use crate::interceptor::cancellation::CancellationToken;
+use crate::interceptor::context::{ExecutionContext, InterceptorContext};
+use crate::interceptor::hook::registry::HookRegistry;
+use crate::interceptor::scope::ExecutionScope;
+use crate::types::{DefinitionKind, LiteralValue, LoomValue, ParallelizationKind, Position, Signature};
+
+pub(crate) fn string(value: &str) -> Expression {
+    Expression::Literal(LiteralValue::String(value.to_string()))
+}
+
+pub(crate) fn directive(name: &str, args: Vec<ArgDefinition>) -> DirectiveCall {
+    DirectiveCall { name: name.into(), args: args.into(), position: Position::default() }
+}
+
+/// Definition senza parametri né direttive, con un block per ogni lista di statement
+pub(crate) fn definition(kind: DefinitionKind, name: &str, blocks: Vec<Vec<Statement>>) -> Definition {
+    Definition {
+        kind,
+        signature: Signature { name: name.into(), parameters: Vec::new().into() },
+        body: blocks.into_iter().map(|statements| Block::new(statements, vec![], vec![])).collect::<Vec<_>>().into(),
+        directives: Vec::new().into(),
+        position: Position::default(),
+        module_index: 0,
+    }
+}
+
+pub(crate) fn recipe(name: &str, statements: Vec<Statement>) -> Definition {
+    definition(DefinitionKind::Recipe, name, vec![statements])
+}
+
+/// Comando `echo <text>`
+pub(crate) fn echo(text: &str, directives: Vec<DirectiveCall>) -> Statement {
+    Statement::Command {
+        parts: vec![string(&format!("echo {}", text))].into(),
+        directives: directives.into(),
+        position: Position::default(),
+    }
+}
+
+pub(crate) fn call(name: &str, args: Vec<ArgDefinition>, directives: Vec<DirectiveCall>) -> Statement {
+    Statement::Call { name: name.into(), args: args.into(), directives: directives.into(), position: Position::default() }
+}
+
+/// ExecutionContext vuoto con le sole variabili indicate
+pub(crate) fn execution_context(variables: Vec<(&str, LoomValue)>) -> ExecutionContext {
+    ExecutionContext {
+        variables: variables.into_iter().map(|(name, value)| (Arc::from(name), value)).collect(),
+        env_vars: HashMap::new(),
+        working_dir: None,
+        dry_run: false,
+        scope: ExecutionScope::Command,
+        parallelization_kind: ParallelizationKind::Sequential,
+        metadata: HashMap::new(),
+        trace: None,
+    }
+}
+
+pub(crate) fn interceptor_context<'a>(
+    loom_context: &'a LoomContext,
+    hook_registry: &'a HookRegistry,
+    execution_context: ExecutionContext,
+) -> InterceptorContext<'a> {
+    InterceptorContext {
+        loom_context,
+        execution_context: Arc::new(RwLock::new(execution_context)),
+        hook_registry,
+        channel: ExecutionEventChannel::new().0,
+        command_slots: Arc::new(Semaphore::new(4)),
+        cancellation: CancellationToken::new(),
+    }
+}
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use serde_json::Value;
 use crate::ast::Expression;
 use crate::context::LoomContext;
+use crate::definition::{format_help_parameters, ArgDefinition};
 use crate::error::{LoomError, LoomResult};
 use crate::InputArg;
 use crate::interceptor::context::ExecutionContext;
@@ -22,61 +24,134 @@ impl LoomValue {
             LoomValue::Empty => "empty",
         }
     }
-}
 
-impl TryInto<bool> for LoomValue {
-    type Error = LoomError;
-    fn try_into(self) -> LoomResult<bool> {
-        match self {
-            LoomValue::Literal(LiteralValue::Boolean(b)) => Ok(b),
-            other => Err(LoomError::execution(format!("Cannot convert {:?} to bool", other)))
+    /// Converts a JSON value, mapping scalars (including `null`) to the matching literal.
+    /// Objects and arrays stay `Json` so they can be indexed further
+    pub fn from_json(value: Value) -> Self {
+        match value {
+            Value::Null => LoomValue::Literal(LiteralValue::Null),
+            Value::Bool(b) => LoomValue::Literal(LiteralValue::Boolean(b)),
+            Value::Number(n) => match n.as_i64() {
+                Some(n) => LoomValue::Literal(LiteralValue::Number(n)),
+                None => LoomValue::Literal(LiteralValue::Float(n.as_f64().unwrap_or(f64::NAN))),
+            },
+            Value::String(s) => LoomValue::Literal(LiteralValue::String(s)),
+            other => LoomValue::Literal(LiteralValue::Json(other)),
         }
     }
-}
-impl TryInto<String> for LoomValue {
-    type Error = LoomError;
-    fn try_into(self) -> LoomResult<String> {
+
+    /// Loose truthiness for conditions: `true`, a non-empty string, a non-zero number,
+    /// a non-empty array and a non-null, non-empty JSON value are truthy.
+    /// `false`, `null`, `Empty` and unevaluated expressions are falsy
+    pub fn is_truthy(&self) -> bool {
         match self {
-            LoomValue::Literal(LiteralValue::String(b)) => Ok(b),
-            other => Err(LoomError::execution(format!("Cannot convert {:?} to String", other)))
+            LoomValue::Literal(literal) => literal.is_truthy(),
+            LoomValue::Expression(_) | LoomValue::Empty => false,
         }
     }
-}
 
-impl TryInto<f64> for LoomValue {
-    type Error = LoomError;
-    fn try_into(self) -> LoomResult<f64> {
+    /// Strict boolean: only `LiteralValue::Boolean` is accepted, any other value is an error
+    pub fn as_bool(&self) -> LoomResult<bool> {
+        bool::try_from(self)
+    }
+
+    /// Converts a whole JSON tree: arrays become `LiteralValue::Array` (recursively),
+    /// objects stay `Json` since there is no native object literal
+    pub fn from_json_value(value: Value) -> Self {
+        LoomValue::Literal(LiteralValue::from_json(value))
+    }
+
+    /// Converts the value to JSON. `Empty` and unevaluated expressions have no value and become `null`;
+    /// evaluate expressions first to serialize their result
+    pub fn to_json_value(&self) -> Value {
         match self {
-            LoomValue::Literal(LiteralValue::Float(b)) => Ok(b),
-            other => Err(LoomError::execution(format!("Cannot convert {:?} to float", other)))
+            LoomValue::Literal(literal) => literal.to_json(),
+            LoomValue::Expression(_) | LoomValue::Empty => Value::Null,
         }
     }
 }
-impl TryInto<i64> for LoomValue {
-    type Error = LoomError;
-    fn try_into(self) -> LoomResult<i64> {
-        match self {
-            LoomValue::Literal(LiteralValue::Number(b)) => Ok(b),
-            other => Err(LoomError::execution(format!("Cannot convert {:?} to integer", other)))
-        }
+
+/// Generates `TryFrom<LoomValue>` and the borrowing `TryFrom<&LoomValue>` for a literal variant.
+/// The borrowing variant clones only the inner value, not the whole `LoomValue`
+macro_rules! impl_try_from_loom_value {
+    ($($target:ty => $variant:ident, $name:literal),* $(,)?) => {
+        $(
+            impl TryFrom<LoomValue> for $target {
+                type Error = LoomError;
+                fn try_from(value: LoomValue) -> LoomResult<$target> {
+                    match value {
+                        LoomValue::Literal(LiteralValue::$variant(v)) => Ok(v),
+                        other => Err(LoomError::execution(format!("Cannot convert '{}' to {}", other, $name)))
+                    }
+                }
+            }
+
+            impl TryFrom<&LoomValue> for $target {
+                type Error = LoomError;
+                fn try_from(value: &LoomValue) -> LoomResult<$target> {
+                    match value {
+                        LoomValue::Literal(LiteralValue::$variant(v)) => Ok(v.clone()),
+                        other => Err(LoomError::execution(format!("Cannot convert '{}' to {}", other, $name)))
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_loom_value! {
+    bool => Boolean, "bool",
+    String => String, "String",
+    f64 => Float, "float",
+    i64 => Number, "integer",
+    Vec<LiteralValue> => Array, "Array",
+    Value => Json, "Json",
+}
+
+/// Generates `From<$source>` for both `LiteralValue` and `LoomValue`
+macro_rules! impl_literal_from {
+    ($($source:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$source> for LiteralValue {
+                fn from(value: $source) -> Self {
+                    LiteralValue::$variant(value.into())
+                }
+            }
+
+            impl From<$source> for LoomValue {
+                fn from(value: $source) -> Self {
+                    LoomValue::Literal(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_literal_from! {
+    String => String,
+    &str => String,
+    i64 => Number,
+    i32 => Number,
+    f64 => Float,
+    bool => Boolean,
+    Value => Json,
+}
+
+impl<T: Into<LiteralValue>> From<Vec<T>> for LiteralValue {
+    fn from(values: Vec<T>) -> Self {
+        LiteralValue::Array(values.into_iter().map(Into::into).collect())
     }
 }
-impl TryInto<Vec<LiteralValue>> for LoomValue {
-    type Error = LoomError;
-    fn try_into(self) -> LoomResult<Vec<LiteralValue>> {
-        match self {
-            LoomValue::Literal(LiteralValue::Array(b)) => Ok(b),
-            other => Err(LoomError::execution(format!("Cannot convert {:?} to Array", other)))
-        }
+
+impl<T: Into<LiteralValue>> From<Vec<T>> for LoomValue {
+    fn from(values: Vec<T>) -> Self {
+        LoomValue::Literal(values.into())
     }
 }
-impl TryInto<Value> for LoomValue {
-    type Error = LoomError;
-    fn try_into(self) -> LoomResult<Value> {
-        match self {
-            LoomValue::Literal(LiteralValue::Json(b)) => Ok(b),
-            other => Err(LoomError::execution(format!("Cannot convert {:?} to Json", other)))
-        }
+
+impl From<LiteralValue> for LoomValue {
+    fn from(value: LiteralValue) -> Self {
+        LoomValue::Literal(value)
     }
 }
 
@@ -96,6 +171,18 @@ pub struct EnumDef {
     pub variants: Arc<HashMap<String, String>>,
 }
 
+impl EnumDef {
+    /// Reverse lookup: the variant whose value is `value`, e.g. to show the symbolic name of a command output.
+    /// When several variants share the value, the alphabetically first one is returned,
+    /// since the order of `variants` is not deterministic
+    pub fn variant_for_value(&self, value: &str) -> Option<&str> {
+        self.variants.iter()
+            .filter(|(_, variant_value)| variant_value.as_str() == value)
+            .map(|(variant, _)| variant.as_str())
+            .min()
+    }
+}
+
 /// Variable assignment
 #[derive(Debug, Clone, PartialEq)]
 pub struct VariableAssignment {
@@ -110,6 +197,8 @@ pub struct ParameterDefinition {
     pub param_type: Option<Arc<str>>,
     pub default_value: Option<Arc<Expression>>, // Unevaluated expression
     pub required: bool,
+    /// Collects all remaining positional arguments (only valid as the last parameter)
+    pub varargs: bool,
 }
 
 /// Function signature with parameter definitions
@@ -136,6 +225,16 @@ pub struct Position {
     pub file: Option<String>,
 }
 
+/// Renders `file:line:column`, or `line:column` when the file is unknown
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}:{}", file, self.line, self.column),
+            None => write!(f, "{}:{}", self.line, self.column),
+        }
+    }
+}
+
 impl Default for Position {
     fn default() -> Self {
         Self {
@@ -148,11 +247,46 @@ impl Default for Position {
 
 impl Signature {
 
+    /// Firma compatta, es. `build(target: string = "debug", ...files)`
+    pub fn usage(&self) -> String {
+        let parameters = self.parameters.iter()
+            .map(|param| {
+                let param_type = param.param_type.as_deref().map(|it| format!(": {}", it)).unwrap_or_default();
+                match &param.default_value {
+                    _ if param.varargs => format!("...{}{}", param.name, param_type),
+                    Some(default) => format!("{}{} = {}", param.name, param_type, help_default(default)),
+                    None => format!("{}{}", param.name, param_type),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!("{}({})", self.name, parameters)
+    }
+
+    /// Help della firma: uso e sezione PARAMETERS con tipo, default e obbligatorietà
+    pub fn help(&self) -> String {
+        let rows = self.parameters.iter()
+            .map(|param| {
+                let flags = match &param.default_value {
+                    _ if param.varargs => "varargs".to_string(),
+                    Some(default) => format!("optional, default: {}", help_default(default)),
+                    None if param.required => "required".to_string(),
+                    None => "optional".to_string(),
+                };
+                (param.name.to_string(), param.param_type.as_deref().unwrap_or("any").to_string(), flags, String::new())
+            })
+            .collect::<Vec<_>>();
+
+        format!("USAGE:\n    {}\n\n{}", self.usage(), format_help_parameters(&rows))
+    }
+
     pub fn args_into_variable(
         &self,
         loom_context: &LoomContext,
         context: &ExecutionContext,
-        args: &Vec<InputArg>,
+        args: &[InputArg],
+        position: Option<Position>,
     ) -> LoomResult<Vec<(String, LoomValue)>> {
         args.iter()
             .map(|arg|
@@ -160,37 +294,148 @@ impl Signature {
             ).filter(|(_, p)| p.is_some())
             .map(|(v1, v2)| (v1, v2.unwrap()))
             .map(|(v1, v2)|
-                v2.value_from_arg(v1.value.as_ref(), loom_context, context)
+                if v2.varargs {
+                    v2.values_from_varargs(&v1.varargs, loom_context, context, position.clone())
+                } else {
+                    v2.value_from_arg(v1.value.as_ref(), loom_context, context, position.clone())
+                }
                     .map(|it| (v2.name.to_string(), it))
             )
         .collect::<Result<Vec<_>, _>>()
     }
 
+    /// Indice del parametro varargs, che è valido solo come ultimo parametro
+    fn varargs_index(&self) -> Option<usize> {
+        self.parameters.last()
+            .filter(|param| param.varargs)
+            .map(|_| self.parameters.len() - 1)
+    }
+
     pub fn positional_arg_from_expression(
         &self,
         args: &[Expression] // Reference invece di owned Vec
     ) -> LoomResult<Vec<InputArg>> {
-        if args.len() > self.parameters.len() {
+        let fixed = self.varargs_index().unwrap_or(self.parameters.len());
+        if args.len() > fixed && self.varargs_index().is_none() {
             return Err(LoomError::execution(format!(
                 "La definition '{}' ha {} parametri e non {}",
                 self.name, self.parameters.len(), args.len()
             )));
         }
 
-        Ok(
-            self.parameters.iter()
-                .take(args.len())
-                .zip(args.iter())
-                .map(|(param, expr)| InputArg {
+        // I parametri oltre gli argomenti forniti ricevono il default
+        let bound = (0..self.parameters.len())
+            .map(|index| args.get(index).filter(|_| index < fixed))
+            .collect();
+        // Gli argomenti in eccesso finiscono nel parametro varargs
+        let rest = args.iter().skip(fixed).collect();
+
+        self.complete_bound_args(bound, rest)
+    }
+
+    /// Associa argomenti posizionali e nominali ai parametri della signature.
+    /// I posizionali vengono legati in ordine, i nominali per nome; ai parametri omessi
+    /// viene assegnato il valore di default, se presente
+    pub fn bind_call_args(
+        &self,
+        args: &[ArgDefinition]
+    ) -> LoomResult<Vec<InputArg>> {
+        let varargs_index = self.varargs_index();
+        let fixed = varargs_index.unwrap_or(self.parameters.len());
+        let mut bound: Vec<Option<&Expression>> = vec![None; self.parameters.len()];
+        let mut rest = Vec::new();
+        let mut next_positional = 0;
+
+        for arg in args {
+            let (index, value) = match arg {
+                ArgDefinition::Positional(value) => {
+                    let index = next_positional;
+                    next_positional += 1;
+                    if index >= fixed && varargs_index.is_none() {
+                        return Err(LoomError::execution(format!(
+                            "La definition '{}' ha {} parametri e non {}",
+                            self.name, self.parameters.len(), args.len()
+                        )));
+                    }
+                    (index.min(fixed), value)
+                }
+                ArgDefinition::Named { name, value } => {
+                    let index = self.parameters.iter()
+                        .position(|param| param.name.as_ref() == name)
+                        .ok_or_else(|| LoomError::execution(format!(
+                            "La definition '{}' non ha un parametro '{}'",
+                            self.name, name
+                        )))?;
+                    (index, value)
+                }
+            };
+
+            if Some(index) == varargs_index {
+                rest.push(value);
+            } else if bound[index].replace(value).is_some() {
+                return Err(LoomError::execution(format!(
+                    "Il parametro '{}' della definition '{}' è valorizzato più volte",
+                    self.parameters[index].name, self.name
+                )));
+            }
+        }
+
+        self.complete_bound_args(bound, rest)
+    }
+
+    /// Converte i valori legati ai parametri in InputArg, usando il default per quelli omessi
+    /// ed errore se un parametro obbligatorio non ha valore.
+    /// Il parametro varargs riceve sempre gli argomenti in eccesso, anche se nessuno
+    fn complete_bound_args(
+        &self,
+        bound: Vec<Option<&Expression>>,
+        rest: Vec<&Expression>,
+    ) -> LoomResult<Vec<InputArg>> {
+        let varargs_index = self.varargs_index();
+        let mut rest = Some(rest);
+        let mut input_args = Vec::with_capacity(self.parameters.len());
+        for (index, (param, value)) in self.parameters.iter().zip(bound).enumerate() {
+            if Some(index) == varargs_index {
+                input_args.push(InputArg {
                     name: param.name.to_string(),
-                    value: Some(expr.clone()), // Solo questo clone necessario
-                })
-                .collect()
-        )
+                    value: None,
+                    varargs: rest.take().unwrap_or_default().into_iter().cloned().collect(),
+                });
+                continue;
+            }
+
+            let value = match (value, &param.default_value) {
+                (Some(value), _) => value.clone(), // Solo questo clone necessario
+                (None, Some(default)) => default.as_ref().clone(),
+                (None, None) if param.required => {
+                    return Err(LoomError::execution(format!(
+                        "Il parametro obbligatorio '{}' della definition '{}' non è stato valorizzato",
+                        param.name, self.name
+                    )));
+                }
+                (None, None) => continue,
+            };
+            input_args.push(InputArg {
+                name: param.name.to_string(),
+                value: Some(value),
+                varargs: Vec::new(),
+            });
+        }
+
+        Ok(input_args)
     }
 
 }
 
+/// Rappresentazione di un valore di default nell'help: i letterali per esteso, le espressioni abbreviate
+fn help_default(default: &Expression) -> String {
+    match default {
+        Expression::Literal(LiteralValue::String(value)) => format!("\"{}\"", value),
+        Expression::Literal(literal) => literal.to_string(),
+        _ => "<expr>".to_string(),
+    }
+}
+
 impl ParameterDefinition {
 
     // TODO: Potrebbe essere il caso di convertire queste stringhe in costanti!
@@ -199,22 +444,29 @@ impl ParameterDefinition {
         value: Option<&Expression>,
         loom_context: &LoomContext,
         context: &ExecutionContext,
+        position: Option<Position>,
     ) -> LoomResult<LoomValue> {
         match value {
             Some(value) => {
                 if let Some(param_type) = &self.param_type {
-                    let evaluated = value.evaluate(loom_context, context, None)?;
+                    let evaluated = value.evaluate(loom_context, context, position.clone())?;
 
                     Ok(LoomValue::Literal(match param_type.as_ref() {
-                        "bool" => LiteralValue::Boolean((&evaluated).clone().try_into()?),
-                        "number" => LiteralValue::Number((&evaluated).clone().try_into()?),
-                        "float" => LiteralValue::Float((&evaluated).clone().try_into()?),
-                        "string" => LiteralValue::String((&evaluated).clone().try_into()?),
+                        "bool" => LiteralValue::Boolean(bool::try_from(&evaluated)?),
+                        "number" => LiteralValue::Number(i64::try_from(&evaluated)?),
+                        "float" => LiteralValue::Float(f64::try_from(&evaluated)?),
+                        "string" => LiteralValue::String(String::try_from(evaluated)?),
+                        // La durata resta una stringa, validata qui per fallire prima dell'esecuzione
+                        "duration" => {
+                            let duration = String::try_from(evaluated)?;
+                            parse_duration(&duration)?;
+                            LiteralValue::String(duration)
+                        }
                         // Enumerator type
                         other => {
                             let en = loom_context.find_enum(other)
-                                .ok_or_else(|| LoomError::execution(format!("Enum '{}' not found", other)))?;
-                            let str_val: String = (&evaluated).clone().try_into()?;
+                                .ok_or_else(|| loom_context.missing_enum(other, position.clone().unwrap_or_default()))?;
+                            let str_val = String::try_from(evaluated)?;
 
                             en.variants.get(&str_val)
                                 .cloned()
@@ -228,7 +480,7 @@ impl ParameterDefinition {
                         }
                     }))
                 } else {
-                    let evaluated = value.evaluate(loom_context, context, None)?;
+                    let evaluated = value.evaluate(loom_context, context, position.clone())?;
                     let stringified = evaluated.stringify(loom_context, context)?;
                     Ok(LoomValue::Literal(LiteralValue::String(stringified)))
                 }
@@ -246,7 +498,7 @@ impl ParameterDefinition {
                                     "No default value for parameter {} and no value provided",
                                     self.name
                                 )))?
-                                .evaluate(loom_context, context, None)
+                                .evaluate(loom_context, context, position.clone())
                         }
                     }
                 }
@@ -254,6 +506,23 @@ impl ParameterDefinition {
         }
     }
 
+    /// Valuta gli argomenti raccolti dal parametro varargs e li restituisce come array
+    pub fn values_from_varargs(
+        &self,
+        values: &[Expression],
+        loom_context: &LoomContext,
+        context: &ExecutionContext,
+        position: Option<Position>,
+    ) -> LoomResult<LoomValue> {
+        values.iter()
+            .map(|value| match self.value_from_arg(Some(value), loom_context, context, position.clone())? {
+                LoomValue::Literal(literal) => Ok(literal),
+                other => other.stringify(loom_context, context).map(LiteralValue::String),
+            })
+            .collect::<LoomResult<Vec<_>>>()
+            .map(|values| LoomValue::Literal(LiteralValue::Array(values)))
+    }
+
     /// Evaluates the parameter definition and returns (param_name, Option<LoomValue>)
     /// Returns None when:
     /// - No default value is provided and parameter is not required
@@ -291,70 +560,208 @@ impl ParameterDefinition {
         (self.name.to_string(), value)
     }
 
-    /// Helper to evaluate function calls
-    fn evaluate_function_call(
-        &self,
-        name: &str,
-        args: &[Expression],
-        loom_context: &LoomContext,
-        context: &ExecutionContext,
-    ) -> LoomResult<LoomValue> {
-        // Evaluate all arguments first
-        let mut evaluated_args = Vec::new();
-        for arg in args {
-            evaluated_args.push(arg.evaluate(loom_context, context, None)?);
-        }
+}
 
-        // TODO: Prendere da modulo esterno...
+/// Converte una durata leggibile (es. `500ms`, `30s`, `2h30m`, `1d`) in `Duration`.
+/// La stringa è una sequenza di `<numero><unità>` con unità `ms`, `s`, `m`, `h` o `d`
+pub fn parse_duration(value: &str) -> LoomResult<Duration> {
+    let invalid = || LoomError::validation(format!(
+        "Invalid duration '{}', expected e.g. 500ms, 30s, 5m, 2h30m or 1d", value
+    ));
 
-        // Call the function with evaluated arguments
-        match name {
-            "env" => {
-                // Example: env("VAR_NAME") - get environment variable
-                if evaluated_args.len() != 1 {
-                    return Err(LoomError::execution("env() requires exactly one argument"));
-                }
-                if let LoomValue::Literal(LiteralValue::String(var_name)) = &evaluated_args[0] {
-                    match std::env::var(var_name) {
-                        Ok(value) => Ok(LoomValue::Literal(LiteralValue::String(value))),
-                        Err(_) => Ok(LoomValue::Empty),
-                    }
-                } else {
-                    Err(LoomError::execution("env() argument must be a string"))
-                }
-            }
-            "concat" => {
-                // Example: concat("a", "b") - concatenate strings
-                let mut result = String::new();
-                for arg in evaluated_args {
-                    match arg {
-                        LoomValue::Literal(LiteralValue::String(s)) => result.push_str(&s),
-                        other => result.push_str(&format!("{:?}", other)), // Convert to string representation
-                    }
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut total = Duration::ZERO;
+    while !rest.is_empty() {
+        let digits = rest.find(|char: char| !char.is_ascii_digit()).unwrap_or(rest.len());
+        let amount: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+
+        let unit_len = rest.find(|char: char| !char.is_ascii_alphabetic()).unwrap_or(rest.len());
+        let millis_per_unit = match &rest[..unit_len] {
+            "ms" => 1,
+            "s" => 1_000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            "d" => 86_400_000,
+            _ => return Err(invalid()),
+        };
+        rest = &rest[unit_len..];
+
+        let millis = amount.checked_mul(millis_per_unit).ok_or_else(invalid)?;
+        total = total.checked_add(Duration::from_millis(millis)).ok_or_else(invalid)?;
+    }
+    Ok(total)
+}
+
+/// Tipi dei parametri di una signature gestiti da `value_from_arg`; ogni altro tipo è il nome di un enum
+pub(crate) const BUILTIN_PARAMETER_TYPES: &[&str] = &["bool", "number", "float", "string", "duration"];
+
+/// Nomi delle funzioni builtin gestite da `call_builtin`
+pub(crate) const BUILTIN_FUNCTIONS: &[&str] = &[
+    "env", "concat", "default", "len", "upper", "lower", "trim", "split", "join", "json", "to_json",
+];
+
+/// Esegue una funzione builtin sugli argomenti già valutati, None se `name` non è una builtin
+pub(crate) fn call_builtin(name: &str, args: &[LoomValue], context: &ExecutionContext) -> Option<LoomResult<LoomValue>> {
+    let result = match name {
+        "env" => {
+            // Example: env("VAR_NAME") - variabile d'ambiente dell'esecuzione (incluse @env ed env file), Empty se non definita
+            builtin_arity(name, args, 1).and_then(|_| {
+                let var_name = builtin_string(name, &args[0])?;
+                Ok(match context.env_vars.get(var_name) {
+                    Some(value) => LoomValue::Literal(LiteralValue::String(value.clone())),
+                    None => LoomValue::Empty,
+                })
+            })
+        }
+        "concat" => {
+            // Example: concat("a", "b") - concatenate strings
+            let mut result = String::new();
+            for arg in args {
+                match arg {
+                    LoomValue::Literal(LiteralValue::String(s)) => result.push_str(s),
+                    other => result.push_str(&other.to_string()),
                 }
-                Ok(LoomValue::Literal(LiteralValue::String(result)))
             }
-            "default" => {
-                // Example: default(var, "fallback") - return first non-empty value
-                for arg in evaluated_args {
-                    match &arg {
-                        LoomValue::Empty => continue,
-                        LoomValue::Literal(LiteralValue::String(s)) if s.is_empty() => continue,
-                        _ => return Ok(arg),
-                    }
+            Ok(LoomValue::Literal(LiteralValue::String(result)))
+        }
+        "default" => {
+            // Example: default(var, "fallback") - return first non-empty value
+            Ok(
+                args.iter()
+                    .find(|arg| match arg {
+                        LoomValue::Empty | LoomValue::Literal(LiteralValue::Null) => false,
+                        LoomValue::Literal(LiteralValue::String(s)) => !s.is_empty(),
+                        _ => true,
+                    })
+                    .cloned()
+                    .unwrap_or(LoomValue::Empty)
+            )
+        }
+        "len" => {
+            // Example: len("abc") / len(items) - caratteri di una stringa o elementi di un array
+            builtin_arity(name, args, 1).and_then(|_| {
+                let len = match &args[0] {
+                    LoomValue::Literal(LiteralValue::String(s)) => s.chars().count(),
+                    LoomValue::Literal(LiteralValue::Array(items)) => items.len(),
+                    LoomValue::Literal(LiteralValue::Json(Value::Array(items))) => items.len(),
+                    LoomValue::Literal(LiteralValue::Json(Value::Object(object))) => object.len(),
+                    LoomValue::Empty => 0,
+                    other => return Err(builtin_type_error(name, "string or array", other)),
+                };
+                Ok(LoomValue::Literal(LiteralValue::Number(len as i64)))
+            })
+        }
+        "upper" | "lower" | "trim" => {
+            builtin_arity(name, args, 1).and_then(|_| {
+                let value = builtin_string(name, &args[0])?;
+                Ok(LoomValue::Literal(LiteralValue::String(match name {
+                    "upper" => value.to_uppercase(),
+                    "lower" => value.to_lowercase(),
+                    _ => value.trim().to_string(),
+                })))
+            })
+        }
+        "split" => {
+            // Example: split("a,b", ",") - array di stringhe
+            builtin_arity(name, args, 2).and_then(|_| {
+                let value = builtin_string(name, &args[0])?;
+                let separator = builtin_string(name, &args[1])?;
+                if separator.is_empty() {
+                    return Err(LoomError::execution("split() separator must not be empty"));
                 }
-                Ok(LoomValue::Empty)
-            }
-            // Add more built-in functions as needed
-            _ => {
-                // Try to call user-defined function from context
-                loom_context.call_function(name, evaluated_args)
-                    // .or_else(|| context.call_function(name, evaluated_args))
-                    // .ok_or_else(|| format!("Unknown function '{}'", name))
-            }
+                Ok(LoomValue::Literal(LiteralValue::Array(
+                    value.split(separator)
+                        .map(|part| LiteralValue::String(part.to_string()))
+                        .collect()
+                )))
+            })
+        }
+        "join" => {
+            // Example: join(items, ",") - elementi dell'array uniti dal separatore
+            builtin_arity(name, args, 2).and_then(|_| {
+                let items = match &args[0] {
+                    LoomValue::Literal(LiteralValue::Array(items)) => items,
+                    other => return Err(builtin_type_error(name, "array", other)),
+                };
+                let separator = builtin_string(name, &args[1])?;
+                Ok(LoomValue::Literal(LiteralValue::String(
+                    items.iter()
+                        .map(LiteralValue::stringify)
+                        .collect::<Vec<_>>()
+                        .join(separator)
+                )))
+            })
+        }
+        "json" => {
+            // Example: json(env("CONFIG")) - parsing di una stringa JSON
+            builtin_arity(name, args, 1).and_then(|_| {
+                let text = builtin_string(name, &args[0])?;
+                serde_json::from_str::<Value>(text)
+                    .map(|json| LoomValue::Literal(LiteralValue::Json(json)))
+                    .map_err(|error| LoomError::execution(format!(
+                        "json() cannot parse '{}': {}",
+                        json_error_excerpt(text, &error), error
+                    )))
+            })
         }
+        "to_json" => {
+            // Example: to_json(items) - serializzazione in una stringa JSON
+            builtin_arity(name, args, 1).and_then(|_| {
+                let json = match &args[0] {
+                    LoomValue::Literal(literal) => literal.to_json(),
+                    LoomValue::Empty => Value::Null,
+                    other => return Err(builtin_type_error(name, "literal", other)),
+                };
+                Ok(LoomValue::Literal(LiteralValue::String(json.to_string())))
+            })
+        }
+        // Add more built-in functions as needed
+        _ => return None,
+    };
+
+    Some(result)
+}
+
+/// Porzione del testo attorno al punto in cui il parsing JSON è fallito
+fn json_error_excerpt(text: &str, error: &serde_json::Error) -> String {
+    const EXCERPT_RADIUS: usize = 10;
+
+    let line = text.lines().nth(error.line().saturating_sub(1)).unwrap_or(text);
+    let chars: Vec<char> = line.chars().collect();
+    let column = error.column().saturating_sub(1).min(chars.len());
+    chars[column.saturating_sub(EXCERPT_RADIUS)..(column + EXCERPT_RADIUS).min(chars.len())]
+        .iter()
+        .collect()
+}
+
+fn builtin_arity(name: &str, args: &[LoomValue], expected: usize) -> LoomResult<()> {
+    if args.len() != expected {
+        return Err(LoomError::execution(format!(
+            "{}() requires exactly {} argument{}, found {}",
+            name, expected, if expected == 1 { "" } else { "s" }, args.len()
+        )));
+    }
+    Ok(())
+}
+
+fn builtin_string<'a>(name: &str, value: &'a LoomValue) -> LoomResult<&'a str> {
+    match value {
+        LoomValue::Literal(LiteralValue::String(s)) => Ok(s),
+        other => Err(builtin_type_error(name, "string", other)),
     }
+}
 
+fn builtin_type_error(name: &str, expected: &str, found: &LoomValue) -> LoomError {
+    let found = match found {
+        LoomValue::Literal(literal) => literal.type_name(),
+        other => other.type_name(),
+    };
+    LoomError::execution(format!("{}() expects a {} argument, found {}", name, expected, found))
 }
 
 // Esempio di utilizzo con il nuovo metodo evaluate
@@ -397,6 +804,8 @@ pub enum LiteralValue {
     Boolean(bool),
     Array(Vec<LiteralValue>),
     Json(Value),
+    /// Explicit null, e.g. from JSON. Unlike `LoomValue::Empty` it is a value, not the absence of one
+    Null,
 }
 
 impl LoomValue {
@@ -413,16 +822,342 @@ impl LoomValue {
 
 impl LiteralValue {
 
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            LiteralValue::String(_) => "string",
+            LiteralValue::Number(_) => "number",
+            LiteralValue::Float(_) => "float",
+            LiteralValue::Boolean(_) => "boolean",
+            LiteralValue::Array(_) => "array",
+            LiteralValue::Json(_) => "json",
+            LiteralValue::Null => "null",
+        }
+    }
+
     pub fn stringify(&self) -> String {
+        self.to_string()
+    }
+
+    /// Regole di `LoomValue::is_truthy` applicate al singolo literal
+    pub fn is_truthy(&self) -> bool {
         match self {
-            LiteralValue::String(v) => v.to_string(),
-            LiteralValue::Number(v) => v.to_string(),
-            LiteralValue::Float(v) => v.to_string(),
-            LiteralValue::Boolean(v) => v.to_string(),
-            LiteralValue::Array(v) =>
-                format!("[{}]", v.iter().map(|it| it.stringify()).collect::<Vec<_>>().join(", ")),
-            LiteralValue::Json(v) => v.to_string(),
+            LiteralValue::Boolean(b) => *b,
+            LiteralValue::String(s) => !s.is_empty(),
+            LiteralValue::Number(n) => *n != 0,
+            LiteralValue::Float(f) => *f != 0.0 && !f.is_nan(),
+            LiteralValue::Array(items) => !items.is_empty(),
+            LiteralValue::Json(json) => match json {
+                Value::Null => false,
+                Value::Bool(b) => *b,
+                Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+                Value::String(s) => !s.is_empty(),
+                Value::Array(items) => !items.is_empty(),
+                Value::Object(fields) => !fields.is_empty(),
+            },
+            LiteralValue::Null => false,
+        }
+    }
+
+    /// Converte un valore JSON nel literal equivalente: gli array diventano `Array` ricorsivamente,
+    /// gli oggetti restano `Json`
+    pub fn from_json(value: Value) -> Self {
+        match value {
+            Value::Null => LiteralValue::Null,
+            Value::Bool(b) => LiteralValue::Boolean(b),
+            Value::Number(n) => match n.as_i64() {
+                Some(n) => LiteralValue::Number(n),
+                None => LiteralValue::Float(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            Value::String(s) => LiteralValue::String(s),
+            Value::Array(items) => LiteralValue::Array(items.into_iter().map(LiteralValue::from_json).collect()),
+            object @ Value::Object(_) => LiteralValue::Json(object),
         }
     }
 
-}
\ No newline at end of file
+    /// Converte il literal nel valore JSON equivalente
+    pub fn to_json(&self) -> Value {
+        match self {
+            LiteralValue::String(v) => Value::String(v.clone()),
+            LiteralValue::Number(v) => Value::from(*v),
+            LiteralValue::Float(v) => Value::from(*v),
+            LiteralValue::Boolean(v) => Value::Bool(*v),
+            LiteralValue::Array(v) => Value::Array(v.iter().map(LiteralValue::to_json).collect()),
+            LiteralValue::Json(v) => v.clone(),
+            LiteralValue::Null => Value::Null,
+        }
+    }
+
+}
+
+impl std::fmt::Display for LiteralValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiteralValue::String(v) => write!(f, "{}", v),
+            LiteralValue::Number(v) => write!(f, "{}", v),
+            LiteralValue::Float(v) => write!(f, "{}", v),
+            LiteralValue::Boolean(v) => write!(f, "{}", v),
+            LiteralValue::Array(v) => {
+                write!(f, "[")?;
+                for (index, item) in v.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            LiteralValue::Json(v) => write!(f, "{}", v),
+            LiteralValue::Null => write!(f, "null"),
+        }
+    }
+}
+
+/// Rendering senza contesto: le espressioni non valutate sono mostrate come `<expr>`,
+/// per il valore effettivo usare `LoomValue::stringify`
+impl std::fmt::Display for LoomValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoomValue::Literal(literal) => write!(f, "{}", literal),
+            LoomValue::Expression(_) => write!(f, "<expr>"),
+            LoomValue::Empty => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::execution_context;
+    use super::*;
+
+    fn string(value: &str) -> Expression {
+        Expression::Literal(LiteralValue::String(value.to_string()))
+    }
+
+    /// `build(target, mode = "debug")`
+    fn build_signature() -> Signature {
+        let parameter = |name: &str, default_value: Option<Expression>| ParameterDefinition {
+            name: name.into(),
+            param_type: None,
+            required: default_value.is_none(),
+            default_value: default_value.map(Arc::new),
+            varargs: false,
+        };
+        Signature {
+            name: "build".into(),
+            parameters: vec![parameter("target", None), parameter("mode", Some(string("debug")))].into(),
+        }
+    }
+
+    fn bound(args: &[InputArg]) -> Vec<(&str, Option<&Expression>)> {
+        args.iter().map(|arg| (arg.name.as_str(), arg.value.as_ref())).collect()
+    }
+
+    #[test]
+    fn omitted_positional_args_use_their_default() {
+        let args = build_signature().positional_arg_from_expression(&[string("prod")]).unwrap();
+        assert_eq!(bound(&args), vec![("target", Some(&string("prod"))), ("mode", Some(&string("debug")))]);
+
+        assert!(build_signature().positional_arg_from_expression(&[]).is_err());
+    }
+
+    #[test]
+    fn bind_call_args_with_named_args_only() {
+        let args = build_signature().bind_call_args(&[
+            ArgDefinition::Named { name: "target".to_string(), value: string("x") },
+        ]).unwrap();
+        assert_eq!(bound(&args), vec![("target", Some(&string("x"))), ("mode", Some(&string("debug")))]);
+    }
+
+    #[test]
+    fn bind_call_args_with_positional_and_named_args() {
+        let args = build_signature().bind_call_args(&[
+            ArgDefinition::Positional(string("x")),
+            ArgDefinition::Named { name: "mode".to_string(), value: string("release") },
+        ]).unwrap();
+        assert_eq!(bound(&args), vec![("target", Some(&string("x"))), ("mode", Some(&string("release")))]);
+
+        let twice = build_signature().bind_call_args(&[
+            ArgDefinition::Positional(string("x")),
+            ArgDefinition::Named { name: "target".to_string(), value: string("y") },
+        ]);
+        assert!(twice.is_err());
+    }
+
+    fn text(value: &str) -> LoomValue {
+        LoomValue::Literal(LiteralValue::String(value.to_string()))
+    }
+
+    fn builtin(name: &str, args: &[LoomValue]) -> LoomResult<LoomValue> {
+        call_builtin(name, args, &execution_context(vec![])).unwrap()
+    }
+
+    #[test]
+    fn len_counts_characters_and_items() {
+        let items = LoomValue::Literal(LiteralValue::Array(vec![LiteralValue::Number(1), LiteralValue::Number(2)]));
+        assert_eq!(builtin("len", &[text("héllo")]).unwrap(), LoomValue::Literal(LiteralValue::Number(5)));
+        assert_eq!(builtin("len", &[items]).unwrap(), LoomValue::Literal(LiteralValue::Number(2)));
+        assert!(builtin("len", &[LoomValue::Literal(LiteralValue::Boolean(true))]).is_err());
+    }
+
+    #[test]
+    fn upper_lower_and_trim() {
+        assert_eq!(builtin("upper", &[text("Loom")]).unwrap(), text("LOOM"));
+        assert_eq!(builtin("lower", &[text("Loom")]).unwrap(), text("loom"));
+        assert_eq!(builtin("trim", &[text("  loom \n")]).unwrap(), text("loom"));
+        assert!(builtin("upper", &[text("a"), text("b")]).is_err());
+    }
+
+    #[test]
+    fn split_and_join() {
+        let parts = LoomValue::Literal(LiteralValue::Array(vec![
+            LiteralValue::String("api".to_string()),
+            LiteralValue::String("web".to_string()),
+        ]));
+        assert_eq!(builtin("split", &[text("api,web"), text(",")]).unwrap(), parts);
+        assert_eq!(builtin("join", &[parts, text(" ")]).unwrap(), text("api web"));
+        assert!(builtin("split", &[text("api"), text("")]).is_err());
+        assert!(builtin("join", &[text("api"), text(",")]).is_err());
+    }
+
+    #[test]
+    fn json_parses_objects_and_arrays() {
+        assert_eq!(
+            builtin("json", &[text(r#"{"port": 8080, "hosts": ["a", "b"]}"#)]).unwrap(),
+            LoomValue::Literal(LiteralValue::Json(serde_json::json!({"port": 8080, "hosts": ["a", "b"]})))
+        );
+        assert_eq!(
+            builtin("json", &[text("[1, 2]")]).unwrap(),
+            LoomValue::Literal(LiteralValue::Json(serde_json::json!([1, 2])))
+        );
+    }
+
+    #[test]
+    fn json_reports_the_invalid_input() {
+        let error = builtin("json", &[text(r#"{"port": 80,, "x": 1}"#)]).unwrap_err();
+        assert!(error.to_string().contains(",,"), "{}", error);
+    }
+
+    #[test]
+    fn to_json_serializes_literals() {
+        let items = LoomValue::Literal(LiteralValue::Array(vec![LiteralValue::Number(1), LiteralValue::String("a".to_string())]));
+        assert_eq!(builtin("to_json", &[items]).unwrap(), text(r#"[1,"a"]"#));
+
+        let object = builtin("json", &[text(r#"{"a": true}"#)]).unwrap();
+        assert_eq!(builtin("to_json", &[object]).unwrap(), text(r#"{"a":true}"#));
+    }
+
+    #[test]
+    fn env_reads_the_execution_context() {
+        let mut context = execution_context(vec![]);
+        context.env_vars.insert("LOOM_TEST_TARGET".to_string(), "release".to_string());
+
+        let env = |name: &str| call_builtin("env", &[text(name)], &context).unwrap().unwrap();
+        assert_eq!(env("LOOM_TEST_TARGET"), text("release"));
+        assert_eq!(env("LOOM_TEST_UNSET"), LoomValue::Empty);
+    }
+
+    #[test]
+    fn position_display_includes_file_when_present() {
+        let position = Position { line: 3, column: 5, file: Some("build.wf".to_string()) };
+        assert_eq!(position.to_string(), "build.wf:3:5");
+    }
+
+    #[test]
+    fn position_display_without_file() {
+        let position = Position { line: 3, column: 5, file: None };
+        assert_eq!(position.to_string(), "3:5");
+    }
+
+    #[test]
+    fn parse_duration_accepts_units_and_sequences() {
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("2h30m").unwrap(), Duration::from_secs(2 * 3600 + 30 * 60));
+        assert_eq!(parse_duration(" 1d ").unwrap(), Duration::from_secs(86_400));
+    }
+
+    #[test]
+    fn parse_duration_rejects_invalid_input() {
+        for value in ["", "30", "s", "5x", "1.5s", "-1s", "99999999999999999999d"] {
+            assert!(parse_duration(value).is_err(), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn json_value_round_trips_nested_structures() {
+        let json = serde_json::json!({
+            "name": "deploy",
+            "targets": [{"host": "a", "port": 22}, {"host": "b", "port": 2222}],
+            "ratio": 0.5,
+            "enabled": true,
+            "parent": null
+        });
+        assert_eq!(LoomValue::from_json_value(json.clone()).to_json_value(), json);
+
+        for json in [serde_json::json!("text"), serde_json::json!(42), serde_json::json!(1.5), serde_json::json!(false), serde_json::json!(null)] {
+            assert_eq!(LoomValue::from_json_value(json.clone()).to_json_value(), json);
+        }
+    }
+
+    #[test]
+    fn from_json_value_builds_native_arrays() {
+        let value = LoomValue::from_json_value(serde_json::json!([1, ["a", true]]));
+        assert_eq!(value, LoomValue::Literal(LiteralValue::Array(vec![
+            LiteralValue::Number(1),
+            LiteralValue::Array(vec![LiteralValue::String("a".to_string()), LiteralValue::Boolean(true)]),
+        ])));
+        assert_eq!(LoomValue::Empty.to_json_value(), Value::Null);
+    }
+
+    #[test]
+    fn truthiness_per_variant() {
+        let literal = |literal: LiteralValue| LoomValue::Literal(literal);
+        let truthy = [
+            literal(LiteralValue::Boolean(true)),
+            literal(LiteralValue::String("no".to_string())),
+            literal(LiteralValue::Number(-1)),
+            literal(LiteralValue::Float(0.1)),
+            literal(LiteralValue::Array(vec![LiteralValue::Null])),
+            literal(LiteralValue::Json(serde_json::json!({"a": 1}))),
+        ];
+        let falsy = [
+            literal(LiteralValue::Boolean(false)),
+            literal(LiteralValue::String(String::new())),
+            literal(LiteralValue::Number(0)),
+            literal(LiteralValue::Float(f64::NAN)),
+            literal(LiteralValue::Array(vec![])),
+            literal(LiteralValue::Json(serde_json::json!({}))),
+            literal(LiteralValue::Null),
+            LoomValue::Empty,
+            LoomValue::Expression(Arc::new(Expression::Variable("flag".into()))),
+        ];
+
+        for value in truthy {
+            assert!(value.is_truthy(), "{:?}", value);
+        }
+        for value in falsy {
+            assert!(!value.is_truthy(), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn as_bool_accepts_only_booleans() {
+        assert!(LoomValue::Literal(LiteralValue::Boolean(true)).as_bool().unwrap());
+        assert!(!LoomValue::Literal(LiteralValue::Boolean(false)).as_bool().unwrap());
+        assert!(LoomValue::Literal(LiteralValue::String("true".to_string())).as_bool().is_err());
+        assert!(LoomValue::Literal(LiteralValue::Number(1)).as_bool().is_err());
+    }
+
+    #[test]
+    fn variant_for_value_finds_the_first_variant_by_name() {
+        let variants = HashMap::from([
+            ("Production".to_string(), "prod".to_string()),
+            ("Prod".to_string(), "prod".to_string()),
+            ("Staging".to_string(), "staging".to_string()),
+        ]);
+        let environment = EnumDef { name: "Environment".into(), variants: Arc::new(variants) };
+
+        assert_eq!(environment.variant_for_value("staging"), Some("Staging"));
+        assert_eq!(environment.variant_for_value("prod"), Some("Prod"));
+        assert_eq!(environment.variant_for_value("dev"), None);
+    }
+}
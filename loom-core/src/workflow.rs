@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use crate::context::LoomContext;
+use crate::error::LoomResult;
+use crate::event::channel::ExecutionEventChannel;
+use crate::InputArg;
+use crate::interceptor::context::ExecutionOptions;
+use crate::interceptor::directive::interceptor::DirectiveInterceptor;
+use crate::interceptor::engine::InterceptorEngine;
+use crate::interceptor::global::interceptor::GlobalInterceptor;
+use crate::interceptor::result::ExecutionResult;
+
+/// Punto di ingresso semplificato: unisce il `LoomContext` con le definition e
+/// l'`InterceptorEngine` che le esegue. Per usi avanzati entrambi restano accessibili
+pub struct Workflow {
+    context: LoomContext,
+    engine: InterceptorEngine,
+}
+
+impl Workflow {
+    /// Workflow con un engine senza interceptor registrati
+    pub fn new(context: LoomContext) -> Self {
+        Self::with_engine(context, InterceptorEngine::new())
+    }
+
+    /// Workflow con un engine già configurato
+    pub fn with_engine(context: LoomContext, engine: InterceptorEngine) -> Self {
+        Self { context, engine }
+    }
+
+    pub fn context(&self) -> &LoomContext {
+        &self.context
+    }
+
+    pub fn engine(&self) -> &InterceptorEngine {
+        &self.engine
+    }
+
+    pub fn engine_mut(&mut self) -> &mut InterceptorEngine {
+        &mut self.engine
+    }
+
+    pub fn into_parts(self) -> (LoomContext, InterceptorEngine) {
+        (self.context, self.engine)
+    }
+
+    pub fn register_directive(&mut self, interceptor: Arc<dyn DirectiveInterceptor>) -> LoomResult<()> {
+        self.engine.register_directive(interceptor)
+    }
+
+    /// Registra più direttive insieme (es. quelle standard di `loom_directives::standard_directives`)
+    pub fn register_directives(
+        &mut self,
+        interceptors: impl IntoIterator<Item = Arc<dyn DirectiveInterceptor>>
+    ) -> LoomResult<()> {
+        self.engine.register_directives(interceptors)
+    }
+
+    pub fn register_global(&mut self, interceptor: Arc<dyn GlobalInterceptor>) -> LoomResult<()> {
+        self.engine.register_global(interceptor)
+    }
+
+    /// Esegue una definition con i suoi argomenti
+    pub async fn run(&self, name: &str, args: &[InputArg]) -> LoomResult<ExecutionResult> {
+        self.engine.execute(&self.context, name, args).await
+    }
+
+    /// Come `run`, con le opzioni di esecuzione indicate
+    pub async fn run_with_options(
+        &self,
+        name: &str,
+        args: &[InputArg],
+        options: &ExecutionOptions,
+    ) -> LoomResult<ExecutionResult> {
+        self.engine.execute_with_options(&self.context, name, args, options).await
+    }
+
+    /// Come `run`, emettendo gli eventi di esecuzione sul channel indicato
+    pub async fn run_with_channel(
+        &self,
+        name: &str,
+        args: &[InputArg],
+        channel: ExecutionEventChannel,
+    ) -> LoomResult<ExecutionResult> {
+        self.engine.execute_with_channel(&self.context, name, args, channel).await
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use loom_core::ast::DirectiveCall;
+use loom_core::context::LoomContext;
+use loom_core::definition::ArgDefinition;
+use loom_core::error::{LoomError, LoomResult};
+use loom_core::interceptor::context::{ExecutionContext, InterceptorContext};
+use loom_core::interceptor::directive::interceptor::DirectiveInterceptor;
+use loom_core::interceptor::{InterceptorChain, InterceptorResult};
+use loom_core::types::LoomValue;
+
+const PATH_PARAM: &str = "path";
+
+/// Interceptor di direttiva @cwd (priorità DIRECTIVE_NORMAL): `@cwd("frontend")` esegue i comandi del target
+/// nella directory indicata. Un percorso relativo parte dalla working directory corrente, e quella
+/// precedente viene ripristinata all'uscita
+#[derive(Default)]
+pub struct CwdDirectiveInterceptor;
+
+impl CwdDirectiveInterceptor {
+    pub fn new() -> Self { Self }
+
+    /// Risolve `path` rispetto alla working directory corrente, verificando che la directory esista
+    fn resolve(path: &str, current: Option<&str>) -> LoomResult<String> {
+        let resolved = match current {
+            Some(current) => Path::new(current).join(path),
+            None => PathBuf::from(path),
+        };
+
+        if !resolved.is_dir() {
+            return Err(LoomError::io_with_path(
+                "@cwd working directory does not exist or is not a directory",
+                resolved.display().to_string(),
+            ));
+        }
+        Ok(resolved.display().to_string())
+    }
+
+    fn set_working_dir(context: &InterceptorContext, working_dir: Option<String>) -> LoomResult<Option<String>> {
+        let mut execution_context = context.write_execution_context("set @cwd working directory")?;
+        Ok(std::mem::replace(&mut execution_context.working_dir, working_dir))
+    }
+}
+
+#[async_trait::async_trait]
+impl DirectiveInterceptor for CwdDirectiveInterceptor {
+    fn directive_name(&self) -> &str {
+        "cwd"
+    }
+
+    async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+        let Some(path) = params.get(PATH_PARAM) else {
+            return Err(LoomError::directive_interceptor(self.directive_name(), "Missing working directory"));
+        };
+
+        // Il percorso è valutato qui, così può usare le variabili legate da @for
+        let working_dir = {
+            let execution_context = context.read_execution_context("evaluate @cwd path")?;
+            let path = path.stringify(context.loom_context, &execution_context)?;
+            Self::resolve(&path, execution_context.working_dir.as_deref())?
+        };
+
+        // La working directory precedente viene ripristinata anche in caso di errore
+        let previous = Self::set_working_dir(&context, Some(working_dir))?;
+        let result = next(context.clone()).await;
+        Self::set_working_dir(&context, previous)?;
+
+        result
+    }
+
+    fn parse_parameters(&self, _loom_context: &LoomContext, _execution_context: &ExecutionContext, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+        let path = match call.args.as_ref() {
+            [ArgDefinition::Positional(path)] => path,
+            [ArgDefinition::Named { name, value }] if name == PATH_PARAM => value,
+            _ => return Err(LoomError::parameter_validation(
+                self.directive_name(),
+                "@cwd expects a single directory, e.g. @cwd(\"frontend\")"
+            )),
+        };
+
+        Ok(HashMap::from([
+            (PATH_PARAM.to_string(), LoomValue::Expression(Arc::new(path.clone()))),
+        ]))
+    }
+
+    fn priority(&self) -> i32 { 4100 } // DIRECTIVE_NORMAL range
+
+    fn need_chain(&self) -> bool {
+        true
+    }
+}
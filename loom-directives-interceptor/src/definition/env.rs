@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use loom_core::ast::DirectiveCall;
+use loom_core::context::LoomContext;
+use loom_core::definition::ArgDefinition;
+use loom_core::error::{LoomError, LoomResult};
+use loom_core::interceptor::context::{ExecutionContext, InterceptorContext};
+use loom_core::interceptor::directive::interceptor::DirectiveInterceptor;
+use loom_core::interceptor::{InterceptorChain, InterceptorResult};
+use loom_core::types::{LiteralValue, LoomValue};
+
+/// Interceptor di direttiva @env (priorità DIRECTIVE_NORMAL): `@env(AWS_REGION: "eu-west-1")` o
+/// `@env({"AWS_REGION": "eu-west-1"})` imposta le variabili d'ambiente per i comandi del target.
+/// I valori precedenti vengono ripristinati all'uscita, così i blocchi fratelli non le vedono
+#[derive(Default)]
+pub struct EnvDirectiveInterceptor;
+
+impl EnvDirectiveInterceptor {
+    pub fn new() -> Self { Self }
+
+    fn is_valid_name(name: &str) -> bool {
+        let mut chars = name.chars();
+        chars.next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+            && chars.all(|char| char.is_ascii_alphanumeric() || char == '_')
+    }
+
+    fn set_env(context: &InterceptorContext, values: Vec<(String, Option<String>)>) -> LoomResult<Vec<(String, Option<String>)>> {
+        let mut execution_context = context.write_execution_context("set @env variables")?;
+
+        Ok(values.into_iter()
+            .map(|(name, value)| {
+                let previous = match value {
+                    Some(value) => execution_context.env_vars.insert(name.clone(), value),
+                    None => execution_context.env_vars.remove(&name),
+                };
+                (name, previous)
+            })
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl DirectiveInterceptor for EnvDirectiveInterceptor {
+    fn directive_name(&self) -> &str {
+        "env"
+    }
+
+    async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+        // I valori sono valutati qui e non in parse_parameters, così vedono le variabili legate da @for
+        let values = {
+            let execution_context = context.read_execution_context("evaluate @env values")?;
+            params.iter()
+                .map(|(name, value)| Ok((name.clone(), Some(value.stringify(context.loom_context, &execution_context)?))))
+                .collect::<LoomResult<Vec<_>>>()?
+        };
+
+        // I valori precedenti vengono ripristinati anche in caso di errore
+        let previous = Self::set_env(&context, values)?;
+        let result = next(context.clone()).await;
+        Self::set_env(&context, previous)?;
+
+        result
+    }
+
+    fn parse_parameters(&self, loom_context: &LoomContext, execution_context: &ExecutionContext, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+        let mut values = Vec::new();
+        for arg in call.args.iter() {
+            match arg {
+                ArgDefinition::Named { name, value } => {
+                    values.push((name.clone(), LoomValue::Expression(Arc::new(value.clone()))));
+                }
+                ArgDefinition::Positional(value) if call.args.len() == 1 => {
+                    match value.evaluate(loom_context, execution_context, Some(call.position.clone()))? {
+                        LoomValue::Literal(LiteralValue::Json(serde_json::Value::Object(map))) => {
+                            values.extend(map.into_iter().map(|(name, value)| {
+                                let value = match value {
+                                    serde_json::Value::String(value) => value,
+                                    value => value.to_string(),
+                                };
+                                (name, LoomValue::Literal(LiteralValue::String(value)))
+                            }));
+                        }
+                        LoomValue::Literal(literal) => return Err(LoomError::type_error("object", literal.type_name(), call.position.clone())),
+                        value => return Err(LoomError::type_error("object", value.type_name(), call.position.clone())),
+                    }
+                }
+                _ => return Err(LoomError::parameter_validation(
+                    self.directive_name(),
+                    "@env expects named values or a single object, e.g. @env(AWS_REGION: \"eu-west-1\")"
+                )),
+            }
+        }
+
+        if values.is_empty() {
+            return Err(LoomError::parameter_validation(self.directive_name(), "@env requires at least one variable"));
+        }
+        if let Some((name, _)) = values.iter().find(|(name, _)| !Self::is_valid_name(name)) {
+            return Err(LoomError::parameter_validation(
+                name.clone(),
+                format!("'{}' is not a valid environment variable name", name)
+            ));
+        }
+
+        Ok(values.into_iter().collect())
+    }
+
+    fn priority(&self) -> i32 { 4200 } // DIRECTIVE_NORMAL range
+
+    fn need_chain(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use loom_core::ast::Expression;
+    use loom_core::interceptor::hook::registry::HookRegistry;
+    use loom_core::interceptor::result::ExecutionResult;
+    use crate::definition::test_support::{directive_call, execution_context, interceptor_context};
+    use super::*;
+
+    /// Target che ritorna come output le variabili d'ambiente viste al momento dell'esecuzione
+    fn env_next<'a>() -> Box<InterceptorChain<'a>> {
+        Box::new(|context: InterceptorContext<'a>| Box::pin(async move {
+            let env_vars = &context.read_execution_context("read env")?.env_vars;
+            let output = ["AWS_REGION", "STAGE"].map(|name| env_vars.get(name).cloned().unwrap_or_default());
+            Ok(ExecutionResult::new(Some(output.join(",")), Some(0)))
+        }))
+    }
+
+    #[tokio::test]
+    async fn env_is_visible_inside_and_restored_outside() {
+        let loom_context = LoomContext::new();
+        let hook_registry = HookRegistry::new();
+        let directive = EnvDirectiveInterceptor::new();
+        let params = directive.parse_parameters(
+            &loom_context,
+            &execution_context(vec![]),
+            &directive_call("env", vec![
+                ArgDefinition::Named { name: "AWS_REGION".to_string(), value: Expression::Variable("region".into()) },
+                ArgDefinition::Named { name: "STAGE".to_string(), value: Expression::Literal(LiteralValue::String("prod".to_string())) },
+            ]),
+        ).unwrap();
+
+        // `region` è una variabile Loom valutata all'esecuzione; STAGE aveva già un valore
+        let mut base = execution_context(vec![("region", LoomValue::Literal(LiteralValue::String("eu-west-1".to_string())))]);
+        base.env_vars.insert("STAGE".to_string(), "dev".to_string());
+        let context = interceptor_context(&loom_context, &hook_registry, base);
+
+        let result = directive.intercept(context.clone(), &params, env_next()).await.unwrap();
+        assert_eq!(result.output(), Some("eu-west-1,prod"));
+
+        let env_vars = context.read_execution_context("check").unwrap().env_vars.clone();
+        assert_eq!(env_vars.get("AWS_REGION"), None);
+        assert_eq!(env_vars.get("STAGE").map(String::as_str), Some("dev"));
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use loom_core::ast::{DirectiveCall, Expression};
+use loom_core::context::LoomContext;
+use loom_core::definition::ArgDefinition;
+use loom_core::error::{LoomError, LoomResult};
+use loom_core::interceptor::context::{ExecutionContext, InterceptorContext};
+use loom_core::interceptor::directive::interceptor::DirectiveInterceptor;
+use loom_core::interceptor::{InterceptorChain, InterceptorResult};
+use loom_core::interceptor::result::ExecutionResult;
+use loom_core::types::{LiteralValue, LoomValue};
+use crate::definition::{position_from_params, position_param};
+
+const VARIABLE_PARAM: &str = "variable";
+const ITEMS_PARAM: &str = "items";
+
+/// Interceptor di direttiva @for (priorità DIRECTIVE_NORMAL): `@for(item, [1, 2, 3])`
+/// esegue il target una volta per elemento, esponendolo nella variabile del ciclo
+#[derive(Default)]
+pub struct ForDirectiveInterceptor;
+
+impl ForDirectiveInterceptor {
+    pub fn new() -> Self { Self }
+
+    /// Imposta (o rimuove) il valore della variabile del ciclo, ritornando quello precedente
+    fn bind(context: &InterceptorContext, variable: &Arc<str>, value: Option<LoomValue>) -> LoomResult<Option<LoomValue>> {
+        let mut execution_context = context.write_execution_context("bind @for variable")?;
+
+        Ok(match value {
+            Some(value) => execution_context.variables.insert(variable.clone(), value),
+            None => execution_context.variables.remove(variable),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl DirectiveInterceptor for ForDirectiveInterceptor {
+    fn directive_name(&self) -> &str {
+        "for"
+    }
+
+    async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+        let (Some(LoomValue::Literal(LiteralValue::String(variable))), Some(items)) =
+            (params.get(VARIABLE_PARAM), params.get(ITEMS_PARAM)) else {
+            return Err(LoomError::directive_interceptor(self.directive_name(), "Missing loop variable or items"));
+        };
+        let variable: Arc<str> = variable.as_str().into();
+        let position = position_from_params(params);
+
+        // Gli elementi sono valutati qui, così possono usare gli argomenti e le variabili assegnate in precedenza
+        let items = {
+            let execution_context = context.read_execution_context("evaluate @for items")?;
+            let value = match items {
+                LoomValue::Expression(expression) => expression.evaluate(context.loom_context, &execution_context, Some(position.clone()))?,
+                value => value.clone(),
+            };
+            match value {
+                LoomValue::Literal(LiteralValue::Array(items)) => items,
+                LoomValue::Literal(literal) => return Err(LoomError::type_error("array", literal.type_name(), position)),
+                value => return Err(LoomError::type_error("array", value.type_name(), position)),
+            }
+        };
+
+        // Il binding precedente viene ripristinato a fine ciclo, anche in caso di errore
+        let previous = Self::bind(&context, &variable, None)?;
+
+        let mut results = Vec::with_capacity(items.len());
+        let mut failure = None;
+        for item in items {
+            Self::bind(&context, &variable, Some(LoomValue::Literal(item.clone())))?;
+            match next(context.clone()).await {
+                Ok(result) => results.push(result),
+                Err(error) => {
+                    failure = Some(error);
+                    break;
+                }
+            }
+        }
+
+        Self::bind(&context, &variable, previous)?;
+
+        match failure {
+            Some(error) => Err(error),
+            None => Ok(
+                ExecutionResult::combine(results)
+                    .unwrap_or_else(|| ExecutionResult::skipped("@for over an empty array"))
+            ),
+        }
+    }
+
+    fn parse_parameters(&self, _loom_context: &LoomContext, _execution_context: &ExecutionContext, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+        let [ArgDefinition::Positional(variable), ArgDefinition::Positional(items)] = call.args.as_ref() else {
+            return Err(LoomError::parameter_validation(
+                self.directive_name(),
+                "@for expects a loop variable and an array, e.g. @for(item, [1, 2, 3])"
+            ));
+        };
+
+        let variable = match variable {
+            Expression::Variable(name) => name.to_string(),
+            Expression::Literal(LiteralValue::String(name)) => name.clone(),
+            _ => return Err(LoomError::parameter_validation(self.directive_name(), "The loop variable must be a name")),
+        };
+
+        Ok(HashMap::from([
+            (VARIABLE_PARAM.to_string(), LoomValue::Literal(LiteralValue::String(variable))),
+            (ITEMS_PARAM.to_string(), LoomValue::Expression(Arc::new(items.clone()))),
+            position_param(&call.position),
+        ]))
+    }
+
+    fn priority(&self) -> i32 { 4500 } // DIRECTIVE_NORMAL range
+
+    fn need_chain(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use loom_core::interceptor::hook::registry::HookRegistry;
+    use loom_core::types::Position;
+    use crate::definition::test_support::{directive_call, execution_context, interceptor_context, probe_next};
+    use super::*;
+
+    #[tokio::test]
+    async fn for_items_are_evaluated_against_the_runtime_context() {
+        let loom_context = LoomContext::new();
+        let hook_registry = HookRegistry::new();
+        let directive = ForDirectiveInterceptor::new();
+        // `names` viene assegnata solo dopo la costruzione della chain
+        let params = directive.parse_parameters(
+            &loom_context,
+            &execution_context(vec![]),
+            &directive_call("for", vec![
+                ArgDefinition::Positional(Expression::Variable("name".into())),
+                ArgDefinition::Positional(Expression::Variable("names".into())),
+            ]),
+        ).unwrap();
+
+        let names = LoomValue::Literal(LiteralValue::Array(vec![
+            LiteralValue::String("api".to_string()),
+            LiteralValue::String("web".to_string()),
+        ]));
+        let context = interceptor_context(&loom_context, &hook_registry, execution_context(vec![("names", names)]));
+        let result = directive.intercept(context.clone(), &params, probe_next("name")).await.unwrap();
+
+        assert_eq!(result.output(), Some("api\nweb"));
+        assert!(context.read_execution_context("check").unwrap().get_variable("name").is_none());
+    }
+
+    #[tokio::test]
+    async fn non_array_items_are_a_type_error_at_the_directive_position() {
+        let loom_context = LoomContext::new();
+        let hook_registry = HookRegistry::new();
+        let directive = ForDirectiveInterceptor::new();
+        let position = Position { line: 7, column: 3, file: Some("loom.loom".to_string()) };
+        let mut call = directive_call("for", vec![
+            ArgDefinition::Positional(Expression::Variable("name".into())),
+            ArgDefinition::Positional(Expression::Variable("names".into())),
+        ]);
+        call.position = position.clone();
+        let params = directive.parse_parameters(&loom_context, &execution_context(vec![]), &call).unwrap();
+
+        let names = LoomValue::Literal(LiteralValue::String("api".to_string()));
+        let context = interceptor_context(&loom_context, &hook_registry, execution_context(vec![("names", names)]));
+        let error = directive.intercept(context, &params, probe_next("name")).await.unwrap_err();
+
+        assert!(matches!(&error, LoomError::TypeError { expected, found, .. } if expected == "array" && found == "string"), "{:?}", error);
+        assert_eq!(error.position(), Some(&position));
+    }
+}
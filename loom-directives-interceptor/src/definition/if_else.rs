@@ -2,30 +2,289 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use loom_core::ast::DirectiveCall;
 use loom_core::context::LoomContext;
-use loom_core::error::LoomResult;
+use loom_core::definition::ArgDefinition;
+use loom_core::error::{LoomError, LoomResult};
 use loom_core::interceptor::context::{ExecutionContext, InterceptorContext};
+use loom_core::interceptor::directive::PAIR_PARAM;
 use loom_core::interceptor::directive::interceptor::DirectiveInterceptor;
-use loom_core::interceptor::hook::registry::HookRegistry;
 use loom_core::interceptor::{InterceptorChain, InterceptorResult};
 use loom_core::interceptor::result::ExecutionResult;
-use loom_core::types::LoomValue;
+use loom_core::types::{LiteralValue, LoomValue};
+use crate::definition::{position_from_params, position_param};
 
-struct IfElseDirectiveInterceptor;
+const CONDITION_PARAM: &str = "condition";
+/// Con `strict: false` la condizione può essere qualsiasi valore, valutato con `LoomValue::is_truthy`
+const STRICT_PARAM: &str = "strict";
+
+/// Prefisso della chiave di `ExecutionContext::metadata` con l'esito di un @if, consumata dal @else accoppiato
+pub const IF_OUTCOME_KEY: &str = "if.outcome";
+
+/// Chiave dell'esito per la coppia @if/@else, None se il motore non ha accoppiato la direttiva.
+/// L'identificativo è diverso per ogni coppia, così un @if di una definition chiamata non lo sovrascrive
+fn outcome_key(params: &HashMap<String, LoomValue>) -> Option<String> {
+    match params.get(PAIR_PARAM) {
+        Some(LoomValue::Literal(LiteralValue::String(pair))) => Some(format!("{}.{}", IF_OUTCOME_KEY, pair)),
+        _ => None,
+    }
+}
+
+/// Interceptor di direttiva @if (priorità DIRECTIVE_HIGH): esegue il target solo se la condizione è vera
+#[derive(Default)]
+pub struct IfDirectiveInterceptor;
+
+impl IfDirectiveInterceptor {
+    pub fn new() -> Self { Self }
+}
+
+#[async_trait::async_trait]
+impl DirectiveInterceptor for IfDirectiveInterceptor {
+    fn directive_name(&self) -> &str {
+        "if"
+    }
+
+    async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+        let Some(condition) = params.get(CONDITION_PARAM) else {
+            return Err(LoomError::directive_interceptor(self.directive_name(), "Missing condition"));
+        };
+        let strict = !matches!(params.get(STRICT_PARAM), Some(LoomValue::Literal(LiteralValue::Boolean(false))));
+        let position = position_from_params(params);
+
+        // La condizione è valutata qui, così può usare gli argomenti, le variabili di @for e le assegnazioni precedenti
+        let condition = {
+            let execution_context = context.read_execution_context("evaluate @if condition")?;
+            let value = match condition {
+                LoomValue::Expression(expression) => expression.evaluate(context.loom_context, &execution_context, Some(position.clone()))?,
+                value => value.clone(),
+            };
+            match (value, strict) {
+                (LoomValue::Literal(LiteralValue::Boolean(condition)), _) => condition,
+                // Con strict: false vale la truthiness di LoomValue::is_truthy
+                (value, false) => value.is_truthy(),
+                (LoomValue::Literal(literal), true) => return Err(LoomError::type_error("boolean", literal.type_name(), position)),
+                (value, true) => return Err(LoomError::type_error("boolean", value.type_name(), position)),
+            }
+        };
+
+        // Serve solo se un @else segue questo elemento
+        if let Some(key) = outcome_key(params) {
+            context.write_execution_context("store @if outcome")?
+                .metadata
+                .insert(key, condition.to_string());
+        }
+
+        if condition {
+            next(context).await
+        } else {
+            Ok(ExecutionResult::skipped("@if condition is false"))
+        }
+    }
+
+    fn parse_parameters(&self, loom_context: &LoomContext, execution_context: &ExecutionContext, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+        let mut condition = None;
+        let mut strict = true;
+        for arg in call.args.iter() {
+            match arg {
+                ArgDefinition::Positional(value) if condition.is_none() => condition = Some(value),
+                ArgDefinition::Named { name, value } if name == CONDITION_PARAM && condition.is_none() => condition = Some(value),
+                ArgDefinition::Named { name, value } if name == STRICT_PARAM => {
+                    strict = value.evaluate(loom_context, execution_context, Some(call.position.clone()))?
+                        .as_bool()
+                        .map_err(|_| LoomError::parameter_validation(STRICT_PARAM, "@if strict must be a boolean"))?;
+                }
+                _ => return Err(LoomError::parameter_validation(
+                    self.directive_name(),
+                    "@if expects a single condition and an optional 'strict' flag"
+                )),
+            }
+        }
+        let condition = condition.ok_or_else(|| LoomError::parameter_validation(
+            self.directive_name(),
+            "@if expects a single condition"
+        ))?;
+
+        Ok(HashMap::from([
+            (CONDITION_PARAM.to_string(), LoomValue::Expression(Arc::new(condition.clone()))),
+            (STRICT_PARAM.to_string(), LoomValue::Literal(LiteralValue::Boolean(strict))),
+            position_param(&call.position),
+        ]))
+    }
+
+    fn priority(&self) -> i32 { 7800 } // DIRECTIVE_HIGH range
+
+    fn need_chain(&self) -> bool {
+        true
+    }
+}
+
+/// Interceptor di direttiva @else: esegue il target solo se il @if precedente era falso
+#[derive(Default)]
+pub struct ElseDirectiveInterceptor;
+
+impl ElseDirectiveInterceptor {
+    pub fn new() -> Self { Self }
+}
 
 #[async_trait::async_trait]
-impl DirectiveInterceptor for IfElseDirectiveInterceptor {
+impl DirectiveInterceptor for ElseDirectiveInterceptor {
     fn directive_name(&self) -> &str {
-        "if_else"
+        "else"
     }
 
-    async fn intercept<'a>(&'a self, _context: InterceptorContext<'a>, _next: Box<InterceptorChain<'a>>) -> InterceptorResult {
-        todo!()
+    async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+        let Some(key) = outcome_key(params) else {
+            return Err(LoomError::validation("@else must directly follow an element with @if"));
+        };
+        // L'esito viene consumato, così non resta nel contesto condiviso
+        let outcome = context.write_execution_context("consume @if outcome")?
+            .metadata
+            .remove(&key);
+
+        match outcome.as_deref() {
+            Some("false") => next(context).await,
+            Some(_) => Ok(ExecutionResult::skipped("@if condition is true")),
+            // Il @if accoppiato non è stato eseguito, ad esempio perché saltato da una direttiva più esterna
+            None => Ok(ExecutionResult::skipped("@if was not evaluated")),
+        }
     }
 
-    fn parse_parameters(&self, _loom_context: &LoomContext, _execution_context: &ExecutionContext, _call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
-        todo!()
+    fn parse_parameters(&self, _loom_context: &LoomContext, _execution_context: &ExecutionContext, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+        if !call.args.is_empty() {
+            return Err(LoomError::parameter_validation(self.directive_name(), "@else does not accept arguments"));
+        }
+        Ok(HashMap::new())
     }
+
+    fn priority(&self) -> i32 { 7790 } // DIRECTIVE_HIGH range, subito dopo @if
+
+    fn follows(&self) -> Option<&str> { Some("if") }
+
     fn need_chain(&self) -> bool {
-        false
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use loom_core::ast::Expression;
+    use loom_core::interceptor::hook::registry::HookRegistry;
+    use loom_core::types::Position;
+    use crate::definition::test_support::{directive_call, execution_context, interceptor_context, probe_next};
+    use super::*;
+
+    fn flag(value: bool) -> Vec<(&'static str, LoomValue)> {
+        vec![("flag", LoomValue::Literal(LiteralValue::Boolean(value)))]
+    }
+
+    /// Parametri di una direttiva come accoppiati dal motore con l'identificativo `pair`
+    fn paired(mut params: HashMap<String, LoomValue>, pair: &str) -> HashMap<String, LoomValue> {
+        params.insert(PAIR_PARAM.to_string(), LoomValue::Literal(LiteralValue::String(pair.to_string())));
+        params
+    }
+
+    fn if_params(loom_context: &LoomContext, condition: Expression, pair: &str) -> HashMap<String, LoomValue> {
+        let params = IfDirectiveInterceptor::new().parse_parameters(
+            loom_context,
+            &execution_context(vec![]),
+            &directive_call("if", vec![ArgDefinition::Positional(condition)]),
+        ).unwrap();
+        paired(params, pair)
+    }
+
+    fn else_params(pair: &str) -> HashMap<String, LoomValue> {
+        paired(HashMap::new(), pair)
+    }
+
+    #[tokio::test]
+    async fn if_condition_is_evaluated_against_the_runtime_context() {
+        let loom_context = LoomContext::new();
+        let hook_registry = HookRegistry::new();
+        let directive = IfDirectiveInterceptor::new();
+        // Al momento della costruzione della chain `flag` non esiste ancora
+        let params = if_params(&loom_context, Expression::Variable("flag".into()), "a");
+
+        // Gli stessi parametri, riusati come da cache, seguono il valore di ogni chiamata
+        let enabled = interceptor_context(&loom_context, &hook_registry, execution_context(flag(true)));
+        let result = directive.intercept(enabled, &params, probe_next("flag")).await.unwrap();
+        assert!(!result.is_skipped());
+
+        let disabled = interceptor_context(&loom_context, &hook_registry, execution_context(flag(false)));
+        let result = directive.intercept(disabled.clone(), &params, probe_next("flag")).await.unwrap();
+        assert!(result.is_skipped());
+        assert_eq!(
+            disabled.read_execution_context("check").unwrap().metadata.get("if.outcome.a").map(String::as_str),
+            Some("false")
+        );
+    }
+
+    #[tokio::test]
+    async fn strict_condition_errors_point_to_the_directive() {
+        let loom_context = LoomContext::new();
+        let hook_registry = HookRegistry::new();
+        let directive = IfDirectiveInterceptor::new();
+        let position = Position { line: 3, column: 5, file: Some("loom.loom".to_string()) };
+        let params = |condition: Expression| {
+            let mut call = directive_call("if", vec![ArgDefinition::Positional(condition)]);
+            call.position = position.clone();
+            directive.parse_parameters(&loom_context, &execution_context(vec![]), &call).unwrap()
+        };
+
+        let context = interceptor_context(&loom_context, &hook_registry, execution_context(vec![]));
+        let yes = params(Expression::Literal(LiteralValue::String("yes".to_string())));
+        let error = directive.intercept(context.clone(), &yes, probe_next("flag"))
+            .await
+            .unwrap_err();
+        assert!(matches!(&error, LoomError::TypeError { expected, found, .. } if expected == "boolean" && found == "string"), "{:?}", error);
+        assert_eq!(error.position(), Some(&position));
+
+        let error = directive.intercept(context, &params(Expression::Variable("missing".into())), probe_next("flag"))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, LoomError::UndefinedError { .. }), "{:?}", error);
+        assert_eq!(error.position(), Some(&position));
+    }
+
+    #[tokio::test]
+    async fn else_runs_only_after_a_false_if() {
+        let loom_context = LoomContext::new();
+        let hook_registry = HookRegistry::new();
+        let (if_directive, else_directive) = (IfDirectiveInterceptor::new(), ElseDirectiveInterceptor::new());
+        let if_params = if_params(&loom_context, Expression::Literal(LiteralValue::Boolean(false)), "a");
+        let else_params = else_params("a");
+
+        let context = interceptor_context(&loom_context, &hook_registry, execution_context(flag(true)));
+        let if_result = if_directive.intercept(context.clone(), &if_params, probe_next("flag")).await.unwrap();
+        let else_result = else_directive.intercept(context.clone(), &else_params, probe_next("flag")).await.unwrap();
+        assert!(if_result.is_skipped());
+        assert_eq!(else_result.output(), Some("true"));
+
+        // L'esito è stato consumato: rieseguito senza il suo @if, il @else viene saltato
+        assert!(else_directive.intercept(context.clone(), &else_params, probe_next("flag")).await.unwrap().is_skipped());
+        // Un @else che il motore non ha accoppiato ad un @if è un errore
+        assert!(else_directive.intercept(context, &HashMap::new(), probe_next("flag")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn nested_if_in_the_called_definition_does_not_affect_the_outer_else() {
+        let loom_context = LoomContext::new();
+        let hook_registry = HookRegistry::new();
+        let (if_directive, else_directive) = (IfDirectiveInterceptor::new(), ElseDirectiveInterceptor::new());
+        let outer_if = if_params(&loom_context, Expression::Literal(LiteralValue::Boolean(true)), "outer");
+        let inner_if = if_params(&loom_context, Expression::Literal(LiteralValue::Boolean(false)), "inner");
+
+        // `@if(true) call inner` seguito da `@else`: la definition chiamata usa lo stesso ExecutionContext
+        // ed esegue a sua volta un `@if(false)`, accoppiato al proprio @else
+        let context = interceptor_context(&loom_context, &hook_registry, execution_context(flag(true)));
+        let if_directive = &if_directive;
+        let inner_if = &inner_if;
+        let call_inner: Box<InterceptorChain> = Box::new(move |context: InterceptorContext| Box::pin(async move {
+            if_directive.intercept(context, inner_if, probe_next("flag")).await
+        }));
+        let outer_result = if_directive.intercept(context.clone(), &outer_if, call_inner).await.unwrap();
+        assert!(outer_result.is_skipped(), "the inner @if(false) skips its own target");
+
+        let outer_else = else_directive.intercept(context.clone(), &else_params("outer"), probe_next("flag")).await.unwrap();
+        assert!(outer_else.is_skipped(), "the outer @if was true");
+        let inner_else = else_directive.intercept(context, &else_params("inner"), probe_next("flag")).await.unwrap();
+        assert_eq!(inner_else.output(), Some("true"));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use loom_core::ast::DirectiveCall;
+use loom_core::context::LoomContext;
+use loom_core::error::{LoomError, LoomResult};
+use loom_core::interceptor::context::{ExecutionContext, InterceptorContext};
+use loom_core::interceptor::directive::interceptor::DirectiveInterceptor;
+use loom_core::interceptor::executor::config::IGNORE_ERRORS_KEY;
+use loom_core::interceptor::{InterceptorChain, InterceptorResult};
+use loom_core::interceptor::result::ExecutionResult;
+use loom_core::types::LoomValue;
+
+/// Chiave dei metadata del risultato in cui @ignore-errors conserva l'errore ignorato
+pub const IGNORED_ERROR_KEY: &str = "ignored_error";
+
+/// Interceptor di direttiva @ignore-errors (priorità DIRECTIVE_NORMAL): i comandi del target che falliscono
+/// non interrompono l'esecuzione, come con `fail_on_error` disattivato. Il risultato resta con l'exit code
+/// originale e l'errore viene riportato nei metadata sotto `IGNORED_ERROR_KEY`
+#[derive(Default)]
+pub struct IgnoreErrorsDirectiveInterceptor;
+
+impl IgnoreErrorsDirectiveInterceptor {
+    pub fn new() -> Self { Self }
+
+    fn set_ignore_errors(context: &InterceptorContext, value: Option<String>) -> LoomResult<Option<String>> {
+        let mut execution_context = context.write_execution_context("set @ignore-errors flag")?;
+
+        Ok(match value {
+            Some(value) => execution_context.metadata.insert(IGNORE_ERRORS_KEY.to_string(), value),
+            None => execution_context.metadata.remove(IGNORE_ERRORS_KEY),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl DirectiveInterceptor for IgnoreErrorsDirectiveInterceptor {
+    fn directive_name(&self) -> &str {
+        "ignore-errors"
+    }
+
+    async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, _params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+        // Il valore precedente viene ripristinato all'uscita, anche in caso di errore
+        let previous = Self::set_ignore_errors(&context, Some("true".to_string()))?;
+        let result = next(context.clone()).await;
+        Self::set_ignore_errors(&context, previous)?;
+
+        match result {
+            Ok(result) => match result.exit_code() {
+                Some(code) if code != 0 => {
+                    let error = result.stderr()
+                        .map(str::trim_end)
+                        .filter(|stderr| !stderr.is_empty())
+                        .map(str::to_string)
+                        .unwrap_or_else(|| format!("exit code {}", code));
+                    Ok(result.with_metadata(IGNORED_ERROR_KEY, error))
+                }
+                _ => Ok(result),
+            },
+            // Es. un comando terminato per timeout, che fallisce anche senza fail_on_error
+            Err(error) if error.is_command_execution() => {
+                Ok(ExecutionResult::new(None, None).with_metadata(IGNORED_ERROR_KEY, error.to_string()))
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn parse_parameters(&self, _loom_context: &LoomContext, _execution_context: &ExecutionContext, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+        if !call.args.is_empty() {
+            return Err(LoomError::parameter_validation(self.directive_name(), "@ignore-errors does not accept parameters"));
+        }
+        Ok(HashMap::new())
+    }
+
+    fn priority(&self) -> i32 { 3500 } // DIRECTIVE_NORMAL range
+
+    fn need_chain(&self) -> bool {
+        true
+    }
+}
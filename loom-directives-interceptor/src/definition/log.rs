@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use log::Level;
+use loom_core::ast::DirectiveCall;
+use loom_core::context::LoomContext;
+use loom_core::definition::{ArgDefinition, ParameterDefinition, ParameterType};
+use loom_core::error::{LoomError, LoomResult};
+use loom_core::event::channel::ExecutionEventKind;
+use loom_core::interceptor::context::{ExecutionContext, InterceptorContext};
+use loom_core::interceptor::directive::interceptor::DirectiveInterceptor;
+use loom_core::interceptor::{InterceptorChain, InterceptorResult};
+use loom_core::types::{LiteralValue, LoomValue};
+
+const MESSAGE_PARAM: &str = "message";
+const LEVEL_PARAM: &str = "level";
+const LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+
+/// `event_type` degli eventi `Custom` emessi da @log
+pub const LOG_EVENT_TYPE: &str = "log";
+
+/// Interceptor di direttiva @log (priorità DIRECTIVE_SUPPORT): `@log("deploy {env}", level: "debug")` registra
+/// il messaggio tramite il crate `log` prima e dopo il target, con esito e durata, ed emette un evento
+/// `Custom` per ciascuna fase. Il messaggio viene valutato all'esecuzione, così può interpolare le variabili
+#[derive(Default)]
+pub struct LogDirectiveInterceptor;
+
+impl LogDirectiveInterceptor {
+    pub fn new() -> Self { Self }
+
+    fn parse_level(level: &str) -> Option<Level> {
+        match level {
+            "trace" => Some(Level::Trace),
+            "debug" => Some(Level::Debug),
+            "info" => Some(Level::Info),
+            "warn" => Some(Level::Warn),
+            "error" => Some(Level::Error),
+            _ => None,
+        }
+    }
+
+    fn emit(context: &InterceptorContext, level: Level, message: &str, phase: &str, extra: serde_json::Value) {
+        let mut data = serde_json::json!({
+            "level": level.as_str().to_lowercase(),
+            "message": message,
+            "phase": phase,
+        });
+        if let (Some(data), serde_json::Value::Object(extra)) = (data.as_object_mut(), extra) {
+            data.extend(extra);
+        }
+
+        let _ = context.channel.emit_with_context(
+            ExecutionEventKind::Custom { event_type: LOG_EVENT_TYPE.to_string(), data },
+            HashMap::new(),
+        );
+    }
+}
+
+#[async_trait::async_trait]
+impl DirectiveInterceptor for LogDirectiveInterceptor {
+    fn directive_name(&self) -> &str {
+        "log"
+    }
+
+    async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+        let (Some(message), Some(LoomValue::Literal(LiteralValue::String(level)))) =
+            (params.get(MESSAGE_PARAM), params.get(LEVEL_PARAM)) else {
+            return Err(LoomError::directive_interceptor(self.directive_name(), "Missing message or level"));
+        };
+        let level = Self::parse_level(level).unwrap_or(Level::Info);
+
+        let message = {
+            let execution_context = context.read_execution_context("evaluate @log message")?;
+            message.stringify(context.loom_context, &execution_context)?
+        };
+
+        log::log!(level, "{} - started", message);
+        Self::emit(&context, level, &message, "started", serde_json::Value::Null);
+
+        let start = Instant::now();
+        let result = next(context.clone()).await;
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        match &result {
+            Ok(_) => log::log!(level, "{} - completed in {}ms", message, duration_ms),
+            Err(error) => log::log!(level.min(Level::Warn), "{} - failed after {}ms: {}", message, duration_ms, error),
+        }
+        Self::emit(&context, level, &message, "finished", serde_json::json!({
+            "success": result.is_ok(),
+            "duration_ms": duration_ms,
+        }));
+
+        result
+    }
+
+    fn parse_parameters(&self, loom_context: &LoomContext, execution_context: &ExecutionContext, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+        let mut message = None;
+        let mut level = None;
+        for (index, arg) in call.args.iter().enumerate() {
+            match arg {
+                ArgDefinition::Positional(value) if index == 0 => message = Some(value),
+                ArgDefinition::Named { name, value } if name == MESSAGE_PARAM => message = Some(value),
+                ArgDefinition::Named { name, value } if name == LEVEL_PARAM => level = Some(value),
+                _ => return Err(LoomError::parameter_validation(
+                    self.directive_name(),
+                    "@log expects a message and an optional level, e.g. @log(\"deploy\", level: \"debug\")"
+                )),
+            }
+        }
+
+        let message = message.ok_or_else(|| LoomError::parameter_validation(MESSAGE_PARAM, "@log requires a message"))?;
+        let mut params = HashMap::from([
+            (MESSAGE_PARAM.to_string(), LoomValue::Expression(Arc::new(message.clone()))),
+        ]);
+        // Senza level, il default dichiarato in `parameters` viene aggiunto dal manager
+        if let Some(level) = level {
+            let level = level.evaluate(loom_context, execution_context, Some(call.position.clone()))?
+                .stringify(loom_context, execution_context)?
+                .to_lowercase();
+            if Self::parse_level(&level).is_none() {
+                return Err(LoomError::parameter_validation(
+                    LEVEL_PARAM,
+                    format!("level must be one of trace, debug, info, warn or error, found \"{}\"", level)
+                ));
+            }
+            params.insert(LEVEL_PARAM.to_string(), LoomValue::Literal(LiteralValue::String(level)));
+        }
+
+        Ok(params)
+    }
+
+    fn parameters(&self) -> Vec<ParameterDefinition> {
+        vec![
+            ParameterDefinition {
+                name: MESSAGE_PARAM.to_string(),
+                param_type: ParameterType::String,
+                required: true,
+                default_value: None,
+                description: "Message to log, evaluated when the target runs".to_string(),
+                deprecated: false,
+            },
+            ParameterDefinition {
+                name: LEVEL_PARAM.to_string(),
+                param_type: ParameterType::Enum(LEVELS.iter().map(|level| level.to_string()).collect()),
+                required: false,
+                default_value: Some(LoomValue::Literal(LiteralValue::String("info".to_string()))),
+                description: "Log level".to_string(),
+                deprecated: false,
+            },
+        ]
+    }
+
+    fn priority(&self) -> i32 { 700 } // DIRECTIVE_SUPPORT range
+
+    fn need_chain(&self) -> bool {
+        true
+    }
+}
@@ -1,3 +1,39 @@
+use std::collections::HashMap;
+use serde_json::json;
+use loom_core::types::{LiteralValue, LoomValue, Position};
+
 pub mod doc;
-mod parallel;
-mod if_else;
\ No newline at end of file
+pub mod parallel;
+pub mod if_else;
+pub mod for_loop;
+pub mod timeout;
+pub mod retry;
+pub mod ignore_errors;
+pub mod env;
+pub mod cwd;
+pub mod log;
+pub mod when;
+
+#[cfg(test)]
+mod test_support;
+
+/// Posizione della direttiva, salvata nei parametri per le espressioni valutate solo in `intercept`
+pub(crate) const POSITION_PARAM: &str = "position";
+
+pub(crate) fn position_param(position: &Position) -> (String, LoomValue) {
+    let position = json!({ "line": position.line, "column": position.column, "file": position.file });
+    (POSITION_PARAM.to_string(), LoomValue::Literal(LiteralValue::Json(position)))
+}
+
+/// Posizione salvata da `position_param`, quella di default se i parametri non la contengono
+pub(crate) fn position_from_params(params: &HashMap<String, LoomValue>) -> Position {
+    let Some(LoomValue::Literal(LiteralValue::Json(position))) = params.get(POSITION_PARAM) else {
+        return Position::default();
+    };
+    let default = Position::default();
+    Position {
+        line: position["line"].as_u64().map_or(default.line, |line| line as usize),
+        column: position["column"].as_u64().map_or(default.column, |column| column as usize),
+        file: position["file"].as_str().map(str::to_string),
+    }
+}
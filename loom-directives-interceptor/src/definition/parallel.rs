@@ -1,43 +1,93 @@
 use std::collections::HashMap;
-use std::sync::Arc;
-use loom_core::ast::DirectiveCall;
+use loom_core::ast::{DirectiveCall, Expression};
 use loom_core::context::LoomContext;
-use loom_core::error::LoomResult;
+use loom_core::definition::ArgDefinition;
+use loom_core::error::{LoomError, LoomResult};
 use loom_core::interceptor::context::{ExecutionContext, InterceptorContext};
 use loom_core::interceptor::directive::interceptor::DirectiveInterceptor;
+use loom_core::interceptor::directive::params::DirectiveParams;
 use loom_core::interceptor::{InterceptorChain, InterceptorResult};
-use loom_core::types::{LoomValue, ParallelizationKind};
+use loom_core::types::{LiteralValue, LoomValue, ParallelizationKind};
+
+const THREADS_PARAM: &str = "threads";
 
 /// Interceptor di direttiva @parallel (priorità DIRECTIVE_NORMAL)
-struct ParallelDirectiveInterceptor;
+#[derive(Default)]
+pub struct ParallelDirectiveInterceptor;
 
 impl ParallelDirectiveInterceptor {
-    fn new() -> Self { Self }
+    pub fn new() -> Self { Self }
+
+    /// Numero di CPU logiche, limitato al massimo rappresentabile
+    fn default_threads() -> i64 {
+        std::thread::available_parallelism()
+            .map(|threads| threads.get().min(u8::MAX as usize) as i64)
+            .unwrap_or(1)
+    }
+
+    fn evaluate_threads(
+        loom_context: &LoomContext,
+        execution_context: &ExecutionContext,
+        call: &DirectiveCall,
+        threads: &Expression,
+    ) -> LoomResult<i64> {
+        match threads.evaluate(loom_context, execution_context, Some(call.position.clone()))? {
+            LoomValue::Literal(LiteralValue::Number(threads)) if (1..=u8::MAX as i64).contains(&threads) => Ok(threads),
+            LoomValue::Literal(LiteralValue::Number(threads)) => Err(LoomError::parameter_validation(
+                THREADS_PARAM,
+                format!("@parallel thread count must be between 1 and {}, found {}", u8::MAX, threads)
+            )),
+            LoomValue::Literal(literal) => Err(LoomError::type_error("number", literal.type_name(), call.position.clone())),
+            value => Err(LoomError::type_error("number", value.type_name(), call.position.clone())),
+        }
+    }
 }
 
 #[async_trait::async_trait]
 impl DirectiveInterceptor for ParallelDirectiveInterceptor {
     fn directive_name(&self) -> &str { "parallel" }
 
-    async fn intercept<'a>(&'a self, mut context: InterceptorContext<'a>, next: Box<InterceptorChain<'a>>) -> InterceptorResult
+    async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult
     {
         println!("⚡ Parallel: Enabling parallel execution...");
         // context.metadata.insert("parallel".to_string(), "true".to_string());
-        context.execution_context.write().map_err(|_| format!("Error while trying to write"))?.parallelization_kind = ParallelizationKind::Parallel { max_thread: 2 };
-        next(context).await
+        let max_thread = DirectiveParams::new(self.directive_name(), params).get_number(THREADS_PARAM)? as u8;
+        let previous = std::mem::replace(
+            &mut context.write_execution_context("enable parallel execution")?.parallelization_kind,
+            ParallelizationKind::Parallel { max_thread }
+        );
+
+        let result = next(context.clone()).await;
+
+        // Il ParallelizationKind non deve propagarsi ai target successivi
+        context.write_execution_context("restore parallelization kind")?.parallelization_kind = previous;
+        result
     }
 
     fn parse_parameters(
         &self,
-        _loom_context: &LoomContext,
-        _execution_context: &ExecutionContext,
-        _call: &DirectiveCall
+        loom_context: &LoomContext,
+        execution_context: &ExecutionContext,
+        call: &DirectiveCall
     ) -> LoomResult<HashMap<String, LoomValue>> {
-        Ok(HashMap::new())
+        let threads = match call.args.as_ref() {
+            [] => Self::default_threads(),
+            [ArgDefinition::Positional(threads)] => Self::evaluate_threads(loom_context, execution_context, call, threads)?,
+            [ArgDefinition::Named { name, value }] if name == THREADS_PARAM || name == "max" =>
+                Self::evaluate_threads(loom_context, execution_context, call, value)?,
+            _ => return Err(LoomError::parameter_validation(
+                self.directive_name(),
+                "@parallel accepts only an optional thread count, e.g. @parallel(threads: 8)"
+            )),
+        };
+
+        Ok(HashMap::from([
+            (THREADS_PARAM.to_string(), LoomValue::Literal(LiteralValue::Number(threads)))
+        ]))
     }
 
     fn need_chain(&self) -> bool {
-        false
+        true
     }
 
     fn priority(&self) -> i32 { 4000 } // DIRECTIVE_NORMAL range
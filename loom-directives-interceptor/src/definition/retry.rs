@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use loom_core::ast::{DirectiveCall, Expression};
+use loom_core::context::LoomContext;
+use loom_core::definition::ArgDefinition;
+use loom_core::error::{LoomError, LoomResult};
+use loom_core::event::channel::ExecutionEventKind;
+use loom_core::interceptor::cancellation::CancellationTokenExt;
+use loom_core::interceptor::context::{ExecutionContext, InterceptorContext};
+use loom_core::interceptor::directive::interceptor::DirectiveInterceptor;
+use loom_core::interceptor::{InterceptorChain, InterceptorResult};
+use loom_core::types::{parse_duration, LiteralValue, LoomValue};
+
+const ATTEMPTS_PARAM: &str = "attempts";
+const BACKOFF_PARAM: &str = "backoff";
+const DELAY_PARAM: &str = "delay";
+const DELAY_MS_KEY: &str = "delay_ms";
+const EXPONENTIAL_KEY: &str = "exponential";
+
+/// Tentativi massimi accettati da @retry, per evitare che un target rotto venga rieseguito all'infinito
+const MAX_RETRY_ATTEMPTS: i64 = 10;
+/// Attesa tra un tentativo e l'altro quando `delay` non è indicato
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// Limite dell'attesa con backoff esponenziale
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Interceptor di direttiva @retry (priorità DIRECTIVE_HIGH): `@retry(3)` riesegue il target finché non
+/// termina con successo, fino al numero di tentativi indicato. `delay` imposta l'attesa tra i tentativi e
+/// `backoff: "exponential"` la raddoppia ad ogni fallimento. Un'esecuzione annullata non viene ritentata
+#[derive(Default)]
+pub struct RetryDirectiveInterceptor;
+
+impl RetryDirectiveInterceptor {
+    pub fn new() -> Self { Self }
+
+    fn evaluate(
+        loom_context: &LoomContext,
+        execution_context: &ExecutionContext,
+        call: &DirectiveCall,
+        expression: &Expression,
+    ) -> LoomResult<LiteralValue> {
+        match expression.evaluate(loom_context, execution_context, Some(call.position.clone()))? {
+            LoomValue::Literal(literal) => Ok(literal),
+            value => Err(LoomError::type_error("literal", value.type_name(), call.position.clone())),
+        }
+    }
+
+    fn evaluate_string(
+        loom_context: &LoomContext,
+        execution_context: &ExecutionContext,
+        call: &DirectiveCall,
+        expression: &Expression,
+    ) -> LoomResult<String> {
+        match Self::evaluate(loom_context, execution_context, call, expression)? {
+            LiteralValue::String(value) => Ok(value),
+            literal => Err(LoomError::type_error("string", literal.type_name(), call.position.clone())),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl DirectiveInterceptor for RetryDirectiveInterceptor {
+    fn directive_name(&self) -> &str {
+        "retry"
+    }
+
+    async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+        let (
+            Some(LoomValue::Literal(LiteralValue::Number(attempts))),
+            Some(LoomValue::Literal(LiteralValue::Number(delay_ms))),
+        ) = (params.get(ATTEMPTS_PARAM), params.get(DELAY_MS_KEY)) else {
+            return Err(LoomError::directive_interceptor(self.directive_name(), "Missing evaluated attempts or delay"));
+        };
+        let max_attempts = *attempts as u32;
+        let exponential = matches!(params.get(EXPONENTIAL_KEY), Some(LoomValue::Literal(LiteralValue::Boolean(true))));
+        let mut delay = Duration::from_millis(*delay_ms as u64);
+
+        let mut attempt = 1;
+        loop {
+            let error = match next(context.clone()).await {
+                Ok(result) => return Ok(result),
+                Err(error) if error.is_cancelled() || attempt >= max_attempts => return Err(error),
+                Err(error) => error,
+            };
+
+            attempt += 1;
+            let _ = context.channel.emit_with_context(
+                ExecutionEventKind::DirectiveRetry {
+                    directive_name: self.directive_name().to_string(),
+                    attempt,
+                    max_attempts,
+                    delay_ms: delay.as_millis() as u64,
+                    error: error.to_string(),
+                },
+                HashMap::new(),
+            );
+            context.cancellation.sleep(delay, "waiting to retry the target").await?;
+
+            if exponential {
+                delay = delay.saturating_mul(2).min(MAX_RETRY_DELAY);
+            }
+        }
+    }
+
+    fn parse_parameters(&self, loom_context: &LoomContext, execution_context: &ExecutionContext, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+        let mut attempts = None;
+        let mut backoff = None;
+        let mut delay = None;
+        for (index, arg) in call.args.iter().enumerate() {
+            match arg {
+                ArgDefinition::Positional(value) if index == 0 => attempts = Some(value),
+                ArgDefinition::Named { name, value } if name == ATTEMPTS_PARAM => attempts = Some(value),
+                ArgDefinition::Named { name, value } if name == BACKOFF_PARAM => backoff = Some(value),
+                ArgDefinition::Named { name, value } if name == DELAY_PARAM => delay = Some(value),
+                _ => return Err(LoomError::parameter_validation(
+                    self.directive_name(),
+                    "@retry expects the attempts and optional backoff and delay, e.g. @retry(3, backoff: \"exponential\", delay: \"500ms\")"
+                )),
+            }
+        }
+
+        let attempts = attempts.ok_or_else(|| LoomError::parameter_validation(ATTEMPTS_PARAM, "@retry requires the number of attempts"))?;
+        let attempts = match Self::evaluate(loom_context, execution_context, call, attempts)? {
+            LiteralValue::Number(attempts) if (1..=MAX_RETRY_ATTEMPTS).contains(&attempts) => attempts,
+            LiteralValue::Number(attempts) => return Err(LoomError::parameter_validation(
+                ATTEMPTS_PARAM,
+                format!("@retry attempts must be between 1 and {}, found {}", MAX_RETRY_ATTEMPTS, attempts)
+            )),
+            literal => return Err(LoomError::type_error("number", literal.type_name(), call.position.clone())),
+        };
+
+        let exponential = match backoff {
+            None => false,
+            Some(value) => match Self::evaluate_string(loom_context, execution_context, call, value)?.as_str() {
+                "fixed" => false,
+                "exponential" => true,
+                other => return Err(LoomError::parameter_validation(
+                    BACKOFF_PARAM,
+                    format!("backoff must be \"fixed\" or \"exponential\", found \"{}\"", other)
+                )),
+            },
+        };
+
+        let delay = match delay {
+            None => DEFAULT_RETRY_DELAY,
+            Some(value) => {
+                let delay = Self::evaluate_string(loom_context, execution_context, call, value)?;
+                parse_duration(&delay).map_err(|_| LoomError::parameter_validation(
+                    DELAY_PARAM,
+                    format!("invalid @retry delay '{}', expected e.g. 500ms, 30s or 2h30m", delay)
+                ))?
+            }
+        };
+
+        Ok(HashMap::from([
+            (ATTEMPTS_PARAM.to_string(), LoomValue::Literal(LiteralValue::Number(attempts))),
+            (DELAY_MS_KEY.to_string(), LoomValue::Literal(LiteralValue::Number(delay.min(MAX_RETRY_DELAY).as_millis() as i64))),
+            (EXPONENTIAL_KEY.to_string(), LoomValue::Literal(LiteralValue::Boolean(exponential))),
+        ]))
+    }
+
+    fn priority(&self) -> i32 { 7700 } // DIRECTIVE_HIGH range, esterna a @timeout così ogni tentativo ha il suo limite
+
+    fn need_chain(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::Semaphore;
+use loom_core::ast::DirectiveCall;
+use loom_core::context::LoomContext;
+use loom_core::definition::ArgDefinition;
+use loom_core::event::channel::ExecutionEventChannel;
+use loom_core::interceptor::InterceptorChain;
+use loom_core::interceptor::cancellation::CancellationToken;
+use loom_core::interceptor::context::{ExecutionContext, InterceptorContext};
+use loom_core::interceptor::hook::registry::HookRegistry;
+use loom_core::interceptor::result::ExecutionResult;
+use loom_core::interceptor::scope::ExecutionScope;
+use loom_core::types::{LoomValue, ParallelizationKind, Position};
+
+/// DirectiveCall `@name(args)` in una posizione fissa
+pub(crate) fn directive_call(name: &str, args: Vec<ArgDefinition>) -> DirectiveCall {
+    DirectiveCall { name: Arc::from(name), args: args.into(), position: Position::default() }
+}
+
+/// ExecutionContext vuoto con le sole variabili indicate
+pub(crate) fn execution_context(variables: Vec<(&str, LoomValue)>) -> ExecutionContext {
+    ExecutionContext {
+        variables: variables.into_iter().map(|(name, value)| (Arc::from(name), value)).collect(),
+        env_vars: HashMap::new(),
+        working_dir: None,
+        dry_run: false,
+        scope: ExecutionScope::Command,
+        parallelization_kind: ParallelizationKind::Sequential,
+        metadata: HashMap::new(),
+        trace: None,
+    }
+}
+
+pub(crate) fn interceptor_context<'a>(
+    loom_context: &'a LoomContext,
+    hook_registry: &'a HookRegistry,
+    execution_context: ExecutionContext,
+) -> InterceptorContext<'a> {
+    InterceptorContext {
+        loom_context,
+        execution_context: Arc::new(RwLock::new(execution_context)),
+        hook_registry,
+        channel: ExecutionEventChannel::new().0,
+        command_slots: Arc::new(Semaphore::new(1)),
+        cancellation: CancellationToken::new(),
+    }
+}
+
+/// Target che ritorna come output il valore di `probe` nell'ExecutionContext al momento dell'esecuzione
+pub(crate) fn probe_next<'a>(probe: &'static str) -> Box<InterceptorChain<'a>> {
+    Box::new(move |context: InterceptorContext<'a>| Box::pin(async move {
+        let output = context.read_execution_context("read probe")?
+            .get_variable(probe)
+            .map(|value| value.to_string());
+        Ok(ExecutionResult::new(output, Some(0)))
+    }))
+}
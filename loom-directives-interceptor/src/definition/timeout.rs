@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use loom_core::ast::{DirectiveCall, Expression};
+use loom_core::context::LoomContext;
+use loom_core::definition::ArgDefinition;
+use loom_core::error::{LoomError, LoomResult};
+use loom_core::interceptor::context::{ExecutionContext, InterceptorContext};
+use loom_core::interceptor::directive::interceptor::DirectiveInterceptor;
+use loom_core::interceptor::{InterceptorChain, InterceptorResult};
+use loom_core::interceptor::result::ExecutionResult;
+use loom_core::types::{parse_duration, LiteralValue, LoomValue};
+
+const DURATION_PARAM: &str = "duration";
+const ON_TIMEOUT_PARAM: &str = "on_timeout";
+const DURATION_MS_KEY: &str = "duration_ms";
+const SKIP_KEY: &str = "skip";
+
+/// Interceptor di direttiva @timeout (priorità DIRECTIVE_HIGH): `@timeout("30s")` interrompe il target
+/// se non termina entro la durata indicata, terminando i processi ancora in esecuzione.
+/// Con `on_timeout: "skip"` il target risulta saltato invece di fallire
+#[derive(Default)]
+pub struct TimeoutDirectiveInterceptor;
+
+impl TimeoutDirectiveInterceptor {
+    pub fn new() -> Self { Self }
+
+    fn evaluate_string(
+        loom_context: &LoomContext,
+        execution_context: &ExecutionContext,
+        call: &DirectiveCall,
+        expression: &Expression,
+    ) -> LoomResult<String> {
+        match expression.evaluate(loom_context, execution_context, Some(call.position.clone()))? {
+            LoomValue::Literal(LiteralValue::String(value)) => Ok(value),
+            LoomValue::Literal(literal) => Err(LoomError::type_error("string", literal.type_name(), call.position.clone())),
+            value => Err(LoomError::type_error("string", value.type_name(), call.position.clone())),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl DirectiveInterceptor for TimeoutDirectiveInterceptor {
+    fn directive_name(&self) -> &str {
+        "timeout"
+    }
+
+    async fn intercept<'a>(&'a self, mut context: InterceptorContext<'a>, params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+        let duration = match params.get(DURATION_MS_KEY) {
+            Some(LoomValue::Literal(LiteralValue::Number(millis))) => Duration::from_millis(*millis as u64),
+            _ => return Err(LoomError::directive_interceptor(self.directive_name(), "Missing evaluated duration")),
+        };
+        let skip = matches!(params.get(SKIP_KEY), Some(LoomValue::Literal(LiteralValue::Boolean(true))));
+
+        // Il token figlio permette di fermare solo il target, non l'intera esecuzione
+        let token = context.cancellation.child_token();
+        context.cancellation = token.clone();
+
+        let mut target = next(context);
+        match tokio::time::timeout(duration, &mut target).await {
+            Ok(result) => result,
+            Err(_) => {
+                // Si attende la fine del target, così i processi in corso vengono terminati prima di proseguire
+                token.cancel();
+                let _ = target.await;
+
+                let message = format!("Timed out after {:?}", duration);
+                if skip {
+                    Ok(ExecutionResult::skipped(message))
+                } else {
+                    Err(LoomError::directive_interceptor(self.directive_name(), message))
+                }
+            }
+        }
+    }
+
+    fn parse_parameters(&self, loom_context: &LoomContext, execution_context: &ExecutionContext, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+        let mut duration = None;
+        let mut on_timeout = None;
+        for (index, arg) in call.args.iter().enumerate() {
+            match arg {
+                ArgDefinition::Positional(value) if index == 0 => duration = Some(value),
+                ArgDefinition::Named { name, value } if name == DURATION_PARAM => duration = Some(value),
+                ArgDefinition::Named { name, value } if name == ON_TIMEOUT_PARAM => on_timeout = Some(value),
+                _ => return Err(LoomError::parameter_validation(
+                    self.directive_name(),
+                    "@timeout expects a duration and an optional on_timeout, e.g. @timeout(\"30s\", on_timeout: \"skip\")"
+                )),
+            }
+        }
+
+        let duration = duration.ok_or_else(|| LoomError::parameter_validation(DURATION_PARAM, "@timeout requires a duration"))?;
+        let duration = Self::evaluate_string(loom_context, execution_context, call, duration)?;
+        let duration = parse_duration(&duration)
+            .map_err(|_| LoomError::parameter_validation(
+                DURATION_PARAM,
+                format!("invalid @timeout duration '{}', expected e.g. 500ms, 30s or 2h30m", duration)
+            ))?;
+        if duration.is_zero() {
+            return Err(LoomError::parameter_validation(DURATION_PARAM, "@timeout requires a non-zero duration"));
+        }
+
+        let skip = match on_timeout {
+            None => false,
+            Some(value) => match Self::evaluate_string(loom_context, execution_context, call, value)?.as_str() {
+                "fail" => false,
+                "skip" => true,
+                other => return Err(LoomError::parameter_validation(
+                    ON_TIMEOUT_PARAM,
+                    format!("on_timeout must be \"fail\" or \"skip\", found \"{}\"", other)
+                )),
+            },
+        };
+
+        Ok(HashMap::from([
+            (DURATION_MS_KEY.to_string(), LoomValue::Literal(LiteralValue::Number(duration.as_millis() as i64))),
+            (SKIP_KEY.to_string(), LoomValue::Literal(LiteralValue::Boolean(skip))),
+        ]))
+    }
+
+    fn priority(&self) -> i32 { 7600 } // DIRECTIVE_HIGH range
+
+    fn need_chain(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use loom_core::ast::DirectiveCall;
+use loom_core::context::LoomContext;
+use loom_core::definition::ArgDefinition;
+use loom_core::error::{LoomError, LoomResult};
+use loom_core::interceptor::context::{ExecutionContext, InterceptorContext};
+use loom_core::interceptor::directive::interceptor::DirectiveInterceptor;
+use loom_core::interceptor::{InterceptorChain, InterceptorResult};
+use loom_core::interceptor::result::ExecutionResult;
+use loom_core::types::{LiteralValue, LoomValue};
+
+const ENVIRONMENT_PARAM: &str = "environment";
+
+/// Interceptor di direttiva @when (priorità DIRECTIVE_HIGH): `@when(environment: "production")` esegue il target
+/// solo se l'environment corrente è tra quelli indicati, altrimenti lo salta. L'environment viene letto come per
+/// `ActivationCondition::Environment` degli interceptor globali, da `LOOM_ENV` o `ENVIRONMENT`
+#[derive(Default)]
+pub struct WhenDirectiveInterceptor;
+
+impl WhenDirectiveInterceptor {
+    pub fn new() -> Self { Self }
+}
+
+#[async_trait::async_trait]
+impl DirectiveInterceptor for WhenDirectiveInterceptor {
+    fn directive_name(&self) -> &str {
+        "when"
+    }
+
+    async fn intercept<'a>(&'a self, context: InterceptorContext<'a>, params: &HashMap<String, LoomValue>, next: Box<InterceptorChain<'a>>) -> InterceptorResult {
+        let Some(LoomValue::Literal(LiteralValue::Array(environments))) = params.get(ENVIRONMENT_PARAM) else {
+            return Err(LoomError::directive_interceptor(self.directive_name(), "Missing evaluated environments"));
+        };
+
+        let current = context.read_execution_context("read current environment")?
+            .current_environment()
+            .to_string();
+        let matches = environments.iter()
+            .any(|environment| matches!(environment, LiteralValue::String(environment) if *environment == current));
+
+        if matches {
+            next(context).await
+        } else {
+            Ok(ExecutionResult::skipped(format!("@when environment does not match '{}'", current)))
+        }
+    }
+
+    fn parse_parameters(&self, loom_context: &LoomContext, execution_context: &ExecutionContext, call: &DirectiveCall) -> LoomResult<HashMap<String, LoomValue>> {
+        let environments = match call.args.as_ref() {
+            [ArgDefinition::Positional(value)] => value,
+            [ArgDefinition::Named { name, value }] if name == ENVIRONMENT_PARAM => value,
+            _ => return Err(LoomError::parameter_validation(
+                self.directive_name(),
+                "@when expects the environments, e.g. @when(environment: \"production\") or @when([\"staging\", \"production\"])"
+            )),
+        };
+
+        let environments = match environments.evaluate(loom_context, execution_context, Some(call.position.clone()))? {
+            LoomValue::Literal(LiteralValue::String(environment)) => vec![LiteralValue::String(environment)],
+            LoomValue::Literal(LiteralValue::Array(environments)) => {
+                if let Some(other) = environments.iter().find(|environment| !matches!(environment, LiteralValue::String(_))) {
+                    return Err(LoomError::type_error("string", other.type_name(), call.position.clone()));
+                }
+                environments
+            }
+            LoomValue::Literal(literal) => return Err(LoomError::type_error("string or array", literal.type_name(), call.position.clone())),
+            value => return Err(LoomError::type_error("string or array", value.type_name(), call.position.clone())),
+        };
+        if environments.is_empty() {
+            return Err(LoomError::parameter_validation(ENVIRONMENT_PARAM, "@when requires at least one environment"));
+        }
+
+        Ok(HashMap::from([
+            (ENVIRONMENT_PARAM.to_string(), LoomValue::Literal(LiteralValue::Array(environments))),
+        ]))
+    }
+
+    fn priority(&self) -> i32 { 7900 } // DIRECTIVE_HIGH range, il target saltato non attiva le altre direttive
+
+    fn need_chain(&self) -> bool {
+        true
+    }
+}
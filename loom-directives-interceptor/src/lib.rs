@@ -1,3 +1,19 @@
+use std::sync::Arc;
+use loom_core::context::LoomContext;
+use loom_core::error::LoomResult;
+use loom_core::interceptor::directive::interceptor::DirectiveInterceptor;
+use loom_core::workflow::Workflow;
+use crate::definition::cwd::CwdDirectiveInterceptor;
+use crate::definition::env::EnvDirectiveInterceptor;
+use crate::definition::for_loop::ForDirectiveInterceptor;
+use crate::definition::if_else::{ElseDirectiveInterceptor, IfDirectiveInterceptor};
+use crate::definition::ignore_errors::IgnoreErrorsDirectiveInterceptor;
+use crate::definition::log::LogDirectiveInterceptor;
+use crate::definition::parallel::ParallelDirectiveInterceptor;
+use crate::definition::retry::RetryDirectiveInterceptor;
+use crate::definition::timeout::TimeoutDirectiveInterceptor;
+use crate::definition::when::WhenDirectiveInterceptor;
+
 pub mod definition;
 pub mod implementation;
 mod validator;
@@ -5,4 +21,42 @@ mod engine;
 
 // pub fn init_builtin_directive() -> LoomResult<()> {
 //     register_directive!(DocDirective, DocExecutor)
-// }
\ No newline at end of file
+// }
+
+/// Direttive standard: `@if`, `@else`, `@for`, `@parallel`, `@timeout`, `@retry`, `@ignore-errors`, `@env`, `@cwd`, `@log` e `@when`
+pub fn standard_directives() -> Vec<Arc<dyn DirectiveInterceptor>> {
+    vec![
+        Arc::new(IfDirectiveInterceptor::new()),
+        Arc::new(ElseDirectiveInterceptor::new()),
+        Arc::new(ForDirectiveInterceptor::new()),
+        Arc::new(ParallelDirectiveInterceptor::new()),
+        Arc::new(TimeoutDirectiveInterceptor::new()),
+        Arc::new(RetryDirectiveInterceptor::new()),
+        Arc::new(IgnoreErrorsDirectiveInterceptor::new()),
+        Arc::new(EnvDirectiveInterceptor::new()),
+        Arc::new(CwdDirectiveInterceptor::new()),
+        Arc::new(LogDirectiveInterceptor::new()),
+        Arc::new(WhenDirectiveInterceptor::new()),
+    ]
+}
+
+/// Workflow con le direttive standard già registrate
+pub fn standard_workflow(context: LoomContext) -> LoomResult<Workflow> {
+    let mut workflow = Workflow::new(context);
+    workflow.register_directives(standard_directives())?;
+    Ok(workflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use loom_core::interceptor::engine::InterceptorEngine;
+    use super::*;
+
+    #[test]
+    fn standard_directives_have_distinct_priorities() {
+        let mut engine = InterceptorEngine::new();
+        engine.register_directives(standard_directives()).unwrap();
+
+        assert_eq!(engine.validate_priority_conflicts(), Ok(()));
+    }
+}